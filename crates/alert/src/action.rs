@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use ymb_assets::Sound;
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum AlertSound {
+    Builtin(Sound),
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AlertAction {
+    PlaySound { sound: AlertSound },
+    Speak { text: String },
+    FlashMuteStatusWindow { duration_ms: u64 },
+    Notify { title: String, body: String },
+}
+
+/// One step of the alert, along with its own volume and throttling.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct AlertActionConfig {
+    pub action: AlertAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Linear gain in `[0.0, 1.0]`, ignored by actions that make no sound.
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Minimum time between two runs of this action.
+    #[serde(default)]
+    pub throttle_ms: u64,
+}
+impl AlertActionConfig {
+    pub fn new(action: AlertAction) -> Self {
+        Self {
+            action,
+            enabled: default_enabled(),
+            volume: default_volume(),
+            throttle_ms: 0,
+        }
+    }
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume.clamp(0.0, 1.0);
+        self
+    }
+    pub fn with_throttle(mut self, throttle: Duration) -> Self {
+        self.throttle_ms = throttle.as_millis() as u64;
+        self
+    }
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+    pub fn throttle(&self) -> Duration {
+        Duration::from_millis(self.throttle_ms)
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// The actions to run, in order, whenever an alert is raised.
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub actions: Vec<AlertActionConfig>,
+}
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            actions: vec![
+                AlertActionConfig::new(AlertAction::PlaySound {
+                    sound: AlertSound::Builtin(Sound::ShortSoft),
+                })
+                .with_volume(0.8),
+                AlertActionConfig::new(AlertAction::FlashMuteStatusWindow { duration_ms: 1_500 }),
+                AlertActionConfig::new(AlertAction::Speak {
+                    text: "You're muted btw".to_string(),
+                })
+                .with_throttle(Duration::from_secs(30))
+                .with_enabled(false),
+                AlertActionConfig::new(AlertAction::Notify {
+                    title: "You're muted btw".to_string(),
+                    body: "You were talking while muted.".to_string(),
+                })
+                .with_throttle(Duration::from_secs(60))
+                .with_enabled(false),
+            ],
+        }
+    }
+}
+impl AlertConfig {
+    /// Plays `sound` for the first sound action, returning `false` when there is none.
+    pub fn select_sound(&mut self, sound: AlertSound) -> bool {
+        let Some(AlertAction::PlaySound { sound: selected }) = self
+            .actions
+            .iter_mut()
+            .map(|action| &mut action.action)
+            .find(|action| matches!(action, AlertAction::PlaySound { .. }))
+        else {
+            return false;
+        };
+        *selected = sound;
+        true
+    }
+}
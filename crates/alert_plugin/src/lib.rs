@@ -0,0 +1,361 @@
+mod settings;
+mod sink;
+mod sound_library;
+mod system_sink;
+
+pub use sink::*;
+pub use sound_library::*;
+pub use system_sink::*;
+
+use bevy::prelude::*;
+use eyre::OptionExt;
+use settings::apply_alert_settings;
+use settings::select_imported_alert_sounds;
+use std::time::Duration;
+use ymb_ipc_plugin::BevyboundIPCMessage;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+
+pub use ymb_alert::AlertAction;
+pub use ymb_alert::AlertActionConfig;
+pub use ymb_alert::AlertConfig;
+pub use ymb_alert::AlertSound;
+
+pub struct AlertPlugin;
+
+impl Plugin for AlertPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RaiseAlert>();
+        app.add_event::<FlashMuteStatusWindow>();
+        app.add_event::<ImportAlertSound>();
+        app.add_event::<AlertSoundImportResult>();
+        app.add_event::<PauseAlerts>();
+        app.add_event::<IpcWorkerGameboundMessage>();
+        app.add_event::<SettingsChanged>();
+        // Starts from the loaded settings, the settings plugin is added first
+        let config = app
+            .world()
+            .get_resource::<Settings>()
+            .map(|settings| settings.alerts.clone())
+            .unwrap_or_default();
+        app.insert_resource(config);
+        app.init_resource::<AlertDispatchState>();
+        app.init_resource::<AlertSoundLibrary>();
+        app.init_resource::<AlertPause>();
+        app.register_type::<AlertConfig>();
+        app.register_type::<AlertDispatchState>();
+        app.register_type::<AlertSoundLibrary>();
+        app.register_type::<AlertPause>();
+        app.register_type::<PauseAlerts>();
+        app.register_type::<RaiseAlert>();
+        app.register_type::<FlashMuteStatusWindow>();
+        app.add_systems(Startup, init_alert_output);
+        app.add_systems(Update, handle_ipc_pause_alerts);
+        app.add_systems(Update, pause_alerts.before(dispatch_alerts));
+        app.add_systems(Update, dispatch_alerts);
+        app.add_systems(Update, handle_import_alert_sound);
+        app.add_systems(
+            Update,
+            (
+                select_imported_alert_sounds.after(handle_import_alert_sound),
+                apply_alert_settings.before(dispatch_alerts),
+            )
+                .chain()
+                .run_if(resource_exists::<Settings>),
+        );
+    }
+}
+
+/// Runs every enabled [`AlertAction`] in [`AlertConfig`] that is not being throttled.
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct RaiseAlert {
+    pub reason: String,
+}
+
+/// Asks the mute status window to draw attention to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Event, Reflect)]
+pub struct FlashMuteStatusWindow {
+    pub duration: Duration,
+}
+
+/// Drops every alert raised during the next `duration`, replacing any earlier pause.
+#[derive(Debug, Clone, Copy, PartialEq, Event, Reflect)]
+pub struct PauseAlerts {
+    pub duration: Duration,
+}
+
+/// Alerts raised before [`AlertPause::until`], a [`Time::elapsed`] timestamp, are dropped.
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct AlertPause {
+    pub until: Option<Duration>,
+}
+impl AlertPause {
+    /// How much longer alerts stay paused, `None` once they are not.
+    pub fn remaining(&self, now: Duration) -> Option<Duration> {
+        self.until
+            .and_then(|until| until.checked_sub(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+/// When each action in [`AlertConfig::actions`] last ran, by index.
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct AlertDispatchState {
+    pub last_run_at: Vec<Option<Duration>>,
+}
+
+fn init_alert_output(mut commands: Commands, output: Option<Res<AlertOutput>>) -> Result {
+    if output.is_none() {
+        commands.insert_resource(AlertOutput::new(SystemAlertSink::new()?));
+    }
+    Ok(())
+}
+
+fn handle_ipc_pause_alerts(
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    mut pauses: EventWriter<PauseAlerts>,
+) {
+    for msg in messages.read() {
+        if let IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::PauseAlerts {
+            duration_ms,
+        }) = msg
+        {
+            pauses.write(PauseAlerts {
+                duration: Duration::from_millis(*duration_ms),
+            });
+        }
+    }
+}
+
+fn pause_alerts(
+    time: Res<Time>,
+    mut pauses: EventReader<PauseAlerts>,
+    mut pause: ResMut<AlertPause>,
+) {
+    for event in pauses.read() {
+        info!("Pausing alerts for {:?}", event.duration);
+        pause.until = Some(time.elapsed() + event.duration);
+    }
+}
+
+fn dispatch_alerts(
+    time: Res<Time>,
+    config: Res<AlertConfig>,
+    pause: Res<AlertPause>,
+    output: Option<Res<AlertOutput>>,
+    mut state: ResMut<AlertDispatchState>,
+    mut alerts: EventReader<RaiseAlert>,
+    mut flashes: EventWriter<FlashMuteStatusWindow>,
+) {
+    if config.is_changed() {
+        state.last_run_at = vec![None; config.actions.len()];
+    }
+    let now = time.elapsed();
+    for alert in alerts.read() {
+        if let Some(remaining) = pause.remaining(now) {
+            debug!(
+                "Alerts are paused for another {:?}, dropping: {}",
+                remaining, alert.reason
+            );
+            continue;
+        }
+        debug!("Raising alert: {}", alert.reason);
+        for (i, action) in config.actions.iter().enumerate() {
+            if !action.enabled {
+                continue;
+            }
+            if let Some(last_run_at) = state.last_run_at[i]
+                && now < last_run_at + action.throttle()
+            {
+                continue;
+            }
+            state.last_run_at[i] = Some(now);
+            let volume = action.volume.clamp(0.0, 1.0);
+            let sink = output
+                .as_deref()
+                .map(|output| &*output.0)
+                .ok_or_eyre("No alert output available");
+            let result = match &action.action {
+                AlertAction::FlashMuteStatusWindow { duration_ms } => {
+                    flashes.write(FlashMuteStatusWindow {
+                        duration: Duration::from_millis(*duration_ms),
+                    });
+                    Ok(())
+                }
+                AlertAction::PlaySound { sound } => {
+                    sink.and_then(|sink| sink.play_sound(sound, volume))
+                }
+                AlertAction::Speak { text } => sink.and_then(|sink| sink.speak(text, volume)),
+                AlertAction::Notify { title, body } => {
+                    sink.and_then(|sink| sink.notify(title, body))
+                }
+            };
+            if let Err(e) = result {
+                warn!("Alert action {:?} failed: {}", action.action, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AlertAction;
+    use crate::AlertActionConfig;
+    use crate::AlertConfig;
+    use crate::AlertOutput;
+    use crate::AlertPlugin;
+    use crate::AlertSound;
+    use crate::FlashMuteStatusWindow;
+    use crate::PauseAlerts;
+    use crate::RaiseAlert;
+    use crate::RecordingAlertSink;
+    use crate::SinkRecord;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use ymb_assets::Sound;
+
+    const STEP: Duration = Duration::from_millis(100);
+
+    fn app(config: AlertConfig) -> (App, RecordingAlertSink) {
+        let sink = RecordingAlertSink::default();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(AlertPlugin);
+        app.insert_resource(AlertOutput::new(sink.clone()));
+        app.insert_resource(config);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(STEP));
+        app.update();
+        (app, sink)
+    }
+
+    fn raise(app: &mut App) {
+        app.world_mut().send_event(RaiseAlert {
+            reason: "test".to_string(),
+        });
+        app.update();
+    }
+
+    #[test]
+    fn actions_run_in_order_with_their_volume() {
+        let (mut app, sink) = app(AlertConfig {
+            actions: vec![
+                AlertActionConfig::new(AlertAction::Speak {
+                    text: "muted".to_string(),
+                })
+                .with_volume(0.25),
+                AlertActionConfig::new(AlertAction::PlaySound {
+                    sound: AlertSound::File(PathBuf::from("custom.wav")),
+                })
+                .with_volume(0.5),
+                AlertActionConfig::new(AlertAction::Notify {
+                    title: "title".to_string(),
+                    body: "body".to_string(),
+                }),
+            ],
+        });
+        raise(&mut app);
+        assert_eq!(
+            sink.records(),
+            vec![
+                SinkRecord::Speak {
+                    text: "muted".to_string(),
+                    volume: 0.25,
+                },
+                SinkRecord::PlaySound {
+                    sound: AlertSound::File(PathBuf::from("custom.wav")),
+                    volume: 0.5,
+                },
+                SinkRecord::Notify {
+                    title: "title".to_string(),
+                    body: "body".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn each_action_is_throttled_separately() {
+        let (mut app, sink) = app(AlertConfig {
+            actions: vec![
+                AlertActionConfig::new(AlertAction::PlaySound {
+                    sound: AlertSound::Builtin(Sound::ShortSoft),
+                }),
+                AlertActionConfig::new(AlertAction::Speak {
+                    text: "muted".to_string(),
+                })
+                .with_throttle(Duration::from_secs(1)),
+            ],
+        });
+        raise(&mut app);
+        raise(&mut app);
+        assert_eq!(sink.take().len(), 3);
+        for _ in 0..10 {
+            app.update();
+        }
+        raise(&mut app);
+        assert_eq!(sink.take().len(), 2);
+    }
+
+    #[test]
+    fn disabled_actions_are_skipped() {
+        let (mut app, sink) = app(AlertConfig {
+            actions: vec![
+                AlertActionConfig::new(AlertAction::PlaySound {
+                    sound: AlertSound::Builtin(Sound::SimpleTone),
+                })
+                .with_enabled(false),
+            ],
+        });
+        raise(&mut app);
+        assert!(sink.records().is_empty());
+    }
+
+    #[test]
+    fn flash_is_sent_as_an_event() {
+        let (mut app, sink) = app(AlertConfig {
+            actions: vec![AlertActionConfig::new(AlertAction::FlashMuteStatusWindow {
+                duration_ms: 500,
+            })],
+        });
+        raise(&mut app);
+        assert!(sink.records().is_empty());
+        let flashes = app
+            .world_mut()
+            .resource_mut::<Events<FlashMuteStatusWindow>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flashes,
+            vec![FlashMuteStatusWindow {
+                duration: Duration::from_millis(500),
+            }]
+        );
+    }
+
+    #[test]
+    fn paused_alerts_are_dropped_until_the_pause_ends() {
+        let (mut app, sink) = app(AlertConfig {
+            actions: vec![AlertActionConfig::new(AlertAction::Speak {
+                text: "muted".to_string(),
+            })],
+        });
+        app.world_mut().send_event(PauseAlerts {
+            duration: Duration::from_secs(1),
+        });
+        raise(&mut app);
+        for _ in 0..5 {
+            raise(&mut app);
+        }
+        assert!(sink.records().is_empty());
+        for _ in 0..5 {
+            app.update();
+        }
+        raise(&mut app);
+        assert_eq!(sink.take().len(), 1);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::AlertConfig;
+use crate::AlertSoundImportResult;
+use bevy::prelude::*;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsPath;
+use ymb_settings::SettingsSection;
+
+/// Selects imported sounds in the settings and saves them, [`apply_alert_settings`] then updates [`AlertConfig`].
+pub(crate) fn select_imported_alert_sounds(
+    mut results: EventReader<AlertSoundImportResult>,
+    path: Res<SettingsPath>,
+    mut settings: ResMut<Settings>,
+    mut events: EventWriter<SettingsChanged>,
+) {
+    let mut selected = false;
+    for result in results.read() {
+        let AlertSoundImportResult::Imported { sound } = result else {
+            continue;
+        };
+        if settings.alerts.select_sound(sound.clone()) {
+            selected = true;
+        } else {
+            warn!(
+                "Imported {:?}, but there is no sound action to play it",
+                sound
+            );
+        }
+    }
+    if !selected {
+        return;
+    }
+    if let Some(path) = &path.0
+        && let Err(e) = settings.save(path)
+    {
+        warn!("Failed to save the imported alert sound: {:?}", e);
+    }
+    events.write(SettingsChanged {
+        section: SettingsSection::Alerts,
+    });
+}
+
+pub(crate) fn apply_alert_settings(
+    mut events: EventReader<SettingsChanged>,
+    settings: Res<Settings>,
+    mut alert_config: ResMut<AlertConfig>,
+) {
+    if events
+        .read()
+        .any(|event| event.section == SettingsSection::Alerts)
+    {
+        *alert_config = settings.alerts.clone();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AlertAction;
+    use crate::AlertConfig;
+    use crate::AlertOutput;
+    use crate::AlertPlugin;
+    use crate::AlertSound;
+    use crate::AlertSoundImportResult;
+    use crate::RecordingAlertSink;
+    use bevy::prelude::*;
+    use std::path::Path;
+    use ymb_settings::Settings;
+    use ymb_settings::SettingsChanged;
+    use ymb_settings::SettingsPath;
+    use ymb_settings::SettingsSection;
+
+    fn played(config: &AlertConfig) -> Option<AlertSound> {
+        config
+            .actions
+            .iter()
+            .find_map(|action| match &action.action {
+                AlertAction::PlaySound { sound } => Some(sound.clone()),
+                _ => None,
+            })
+    }
+
+    fn app(settings: Settings, path: &Path) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(settings);
+        app.insert_resource(SettingsPath(Some(path.to_path_buf())));
+        app.add_plugins(AlertPlugin);
+        app.insert_resource(AlertOutput::new(RecordingAlertSink::default()));
+        app.update();
+        app
+    }
+
+    #[test]
+    fn config_follows_the_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut settings = Settings::default();
+        settings.alerts.actions[0].enabled = false;
+        let mut app = app(settings.clone(), &dir.path().join("settings.toml"));
+        assert_eq!(*app.world().resource::<AlertConfig>(), settings.alerts);
+
+        settings.alerts.actions[0].volume = 0.1;
+        app.insert_resource(settings.clone());
+        app.world_mut().send_event(SettingsChanged {
+            section: SettingsSection::Alerts,
+        });
+        app.update();
+        assert_eq!(*app.world().resource::<AlertConfig>(), settings.alerts);
+    }
+
+    #[test]
+    fn imported_sounds_are_selected_and_saved() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        let mut app = app(Settings::default(), &path);
+
+        let sound = AlertSound::File(dir.path().join("ding.wav"));
+        app.world_mut()
+            .send_event(AlertSoundImportResult::Imported {
+                sound: sound.clone(),
+            });
+        app.update();
+
+        let settings = app.world().resource::<Settings>();
+        assert_eq!(played(&settings.alerts), Some(sound.clone()));
+        assert_eq!(*app.world().resource::<AlertConfig>(), settings.alerts);
+        let (saved, _) = Settings::from_toml(&std::fs::read_to_string(&path)?)?;
+        assert_eq!(played(&saved.alerts), Some(sound));
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::AlertSound;
+use bevy::prelude::*;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// Where audible and out-of-app alerts end up.
+///
+/// Implementations must return quickly, anything slow belongs on another thread.
+pub trait AlertSink: Send + Sync + 'static {
+    fn play_sound(&self, sound: &AlertSound, volume: f32) -> eyre::Result<()>;
+    fn speak(&self, text: &str, volume: f32) -> eyre::Result<()>;
+    fn notify(&self, title: &str, body: &str) -> eyre::Result<()>;
+}
+
+/// The sink used by the alert dispatcher.
+///
+/// Inserted with a [`crate::SystemAlertSink`] at startup unless one is already present.
+#[derive(Resource)]
+pub struct AlertOutput(pub Box<dyn AlertSink>);
+impl AlertOutput {
+    pub fn new(sink: impl AlertSink) -> Self {
+        Self(Box::new(sink))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SinkRecord {
+    PlaySound { sound: AlertSound, volume: f32 },
+    Speak { text: String, volume: f32 },
+    Notify { title: String, body: String },
+}
+
+/// Remembers everything it was asked to do instead of doing it.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingAlertSink {
+    records: Arc<Mutex<Vec<SinkRecord>>>,
+}
+impl RecordingAlertSink {
+    pub fn records(&self) -> Vec<SinkRecord> {
+        self.records.lock().unwrap().clone()
+    }
+    pub fn take(&self) -> Vec<SinkRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap())
+    }
+    fn record(&self, record: SinkRecord) {
+        self.records.lock().unwrap().push(record);
+    }
+}
+impl AlertSink for RecordingAlertSink {
+    fn play_sound(&self, sound: &AlertSound, volume: f32) -> eyre::Result<()> {
+        self.record(SinkRecord::PlaySound {
+            sound: sound.clone(),
+            volume,
+        });
+        Ok(())
+    }
+    fn speak(&self, text: &str, volume: f32) -> eyre::Result<()> {
+        self.record(SinkRecord::Speak {
+            text: text.to_string(),
+            volume,
+        });
+        Ok(())
+    }
+    fn notify(&self, title: &str, body: &str) -> eyre::Result<()> {
+        self.record(SinkRecord::Notify {
+            title: title.to_string(),
+            body: body.to_string(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use crate::AlertSound;
+use bevy::prelude::*;
+use eyre::Context;
+use eyre::OptionExt;
+use eyre::bail;
+use rodio::Decoder;
+use rodio::Source;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use strum::VariantArray;
+use ymb_assets::Sound;
+
+pub const USER_SOUND_EXTENSIONS: [&str; 3] = ["ogg", "wav", "mp3"];
+
+/// Alert sounds are meant to be short, longer files are rejected on import.
+pub const MAX_USER_SOUND_DURATION: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct AlertSoundEntry {
+    pub name: String,
+    pub sound: AlertSound,
+}
+
+/// Every sound that can be picked for a [`crate::AlertAction::PlaySound`].
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct AlertSoundLibrary {
+    pub entries: Vec<AlertSoundEntry>,
+    /// Where imported sounds are copied to.
+    pub user_sounds_dir: Option<PathBuf>,
+}
+impl Default for AlertSoundLibrary {
+    fn default() -> Self {
+        Self::new(user_sounds_dir())
+    }
+}
+impl AlertSoundLibrary {
+    /// Builds a library of the built-in sounds plus any valid files already in `user_sounds_dir`.
+    pub fn new(user_sounds_dir: Option<PathBuf>) -> Self {
+        let mut library = Self {
+            entries: Sound::VARIANTS
+                .iter()
+                .map(|sound| AlertSoundEntry {
+                    name: format!("{sound:?}"),
+                    sound: AlertSound::Builtin(*sound),
+                })
+                .collect(),
+            user_sounds_dir,
+        };
+        if let Some(dir) = library.user_sounds_dir.clone()
+            && let Ok(read_dir) = std::fs::read_dir(&dir)
+        {
+            let mut paths = read_dir
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| has_supported_extension(path))
+                .collect::<Vec<_>>();
+            paths.sort();
+            for path in paths {
+                library.register(path);
+            }
+        }
+        library
+    }
+
+    pub fn register(&mut self, path: PathBuf) -> AlertSound {
+        let sound = AlertSound::File(path.clone());
+        if !self.entries.iter().any(|entry| entry.sound == sound) {
+            self.entries.push(AlertSoundEntry {
+                name: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string()),
+                sound: sound.clone(),
+            });
+        }
+        sound
+    }
+
+    /// Validates `path` and copies it into [`Self::user_sounds_dir`], replacing any file of the same name.
+    pub fn import(&mut self, path: &Path) -> eyre::Result<AlertSound> {
+        validate_sound_file(path, MAX_USER_SOUND_DURATION)?;
+        let dir = self
+            .user_sounds_dir
+            .as_ref()
+            .ok_or_eyre("No app data directory to store sounds in")?;
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        let file_name = path
+            .file_name()
+            .ok_or_eyre("Dropped path has no file name")?;
+        let destination = dir.join(file_name);
+        if destination != path {
+            std::fs::copy(path, &destination)
+                .wrap_err_with(|| format!("Failed to copy into {}", destination.display()))?;
+        }
+        Ok(self.register(destination))
+    }
+}
+
+pub fn user_sounds_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("youre-muted-btw").join("sounds"))
+}
+
+fn has_supported_extension(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| USER_SOUND_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Decodes the whole file, returning its length if it is playable and no longer than `max_duration`.
+pub fn validate_sound_file(path: &Path, max_duration: Duration) -> eyre::Result<Duration> {
+    if !has_supported_extension(path) {
+        bail!(
+            "{} is not one of {}",
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            USER_SOUND_EXTENSIONS.join(", ")
+        );
+    }
+    let file = File::open(path).wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+    let decoder = Decoder::new(BufReader::new(file))
+        .wrap_err_with(|| format!("{} is not a supported sound file", path.display()))?;
+    let samples_per_second = decoder.sample_rate() as u64 * decoder.channels() as u64;
+    if samples_per_second == 0 {
+        bail!("{} has no audio", path.display());
+    }
+    // Header durations are missing for some formats, so count samples but stop once over the limit
+    let max_samples = (max_duration.as_secs_f64() * samples_per_second as f64).ceil() as usize;
+    let samples = decoder.take(max_samples + 1).count();
+    if samples == 0 {
+        bail!("{} has no audio", path.display());
+    }
+    let duration = Duration::from_secs_f64(samples as f64 / samples_per_second as f64);
+    if samples > max_samples {
+        bail!(
+            "{} is longer than the {:?} limit",
+            path.display(),
+            max_duration
+        );
+    }
+    Ok(duration)
+}
+
+/// Validates, copies and registers a sound file.
+///
+/// The settings select it for the first sound action once [`AlertSoundImportResult::Imported`] is sent,
+/// so the choice is saved and [`crate::AlertConfig`] follows from them.
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct ImportAlertSound {
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Event, Reflect)]
+pub enum AlertSoundImportResult {
+    Imported { sound: AlertSound },
+    Failed { path: PathBuf, error: String },
+}
+
+pub(crate) fn handle_import_alert_sound(
+    mut events: EventReader<ImportAlertSound>,
+    mut library: ResMut<AlertSoundLibrary>,
+    mut results: EventWriter<AlertSoundImportResult>,
+) {
+    for event in events.read() {
+        match library.import(&event.path) {
+            Ok(sound) => {
+                info!("Imported alert sound {:?}", sound);
+                results.write(AlertSoundImportResult::Imported { sound });
+            }
+            Err(e) => {
+                warn!("Rejected alert sound {}: {:?}", event.path.display(), e);
+                results.write(AlertSoundImportResult::Failed {
+                    path: event.path.clone(),
+                    error: format!("{e}"),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AlertSound;
+    use crate::AlertSoundLibrary;
+    use crate::MAX_USER_SOUND_DURATION;
+    use crate::validate_sound_file;
+    use bevy::asset::AssetPath;
+    use std::path::Path;
+    use std::time::Duration;
+    use strum::VariantArray;
+    use ymb_assets::Sound;
+
+    fn write_tone(path: &Path, duration: Duration) -> eyre::Result<()> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec)?;
+        let samples = (duration.as_secs_f64() * spec.sample_rate as f64) as usize;
+        for i in 0..samples {
+            let t = i as f32 / spec.sample_rate as f32;
+            let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.5;
+            writer.write_sample((sample * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()?;
+        Ok(())
+    }
+
+    #[test]
+    fn short_wav_is_imported_and_registered() -> eyre::Result<()> {
+        let dropped = tempfile::tempdir()?;
+        let data = tempfile::tempdir()?;
+        let path = dropped.path().join("beep.wav");
+        write_tone(&path, Duration::from_millis(500))?;
+        let mut library = AlertSoundLibrary::new(Some(data.path().to_path_buf()));
+        let sound = library.import(&path)?;
+        let copied = data.path().join("beep.wav");
+        assert_eq!(sound, AlertSound::File(copied.clone()));
+        assert!(copied.exists());
+        let names = library
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["ShortSoft", "SimpleTone", "beep"]);
+
+        // Sounds already in the directory are picked up on the next start
+        let reloaded = AlertSoundLibrary::new(Some(data.path().to_path_buf()));
+        assert_eq!(reloaded.entries, library.entries);
+        Ok(())
+    }
+
+    #[test]
+    fn long_file_is_rejected() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("long.wav");
+        write_tone(&path, Duration::from_secs(2))?;
+        assert!(validate_sound_file(&path, Duration::from_secs(1)).is_err());
+        let duration = validate_sound_file(&path, Duration::from_secs(3))?;
+        assert!((duration.as_secs_f32() - 2.0).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn garbage_and_unsupported_files_are_rejected() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let garbage = dir.path().join("garbage.ogg");
+        std::fs::write(&garbage, b"definitely not vorbis")?;
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, b"hello")?;
+        let mut library = AlertSoundLibrary::new(Some(dir.path().join("sounds")));
+        assert!(library.import(&garbage).is_err());
+        assert!(library.import(&text).is_err());
+        assert_eq!(library.entries.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn builtin_sounds_pass_validation() -> eyre::Result<()> {
+        let assets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+        for sound in Sound::VARIANTS.iter().cloned() {
+            let asset_path: AssetPath = sound.into();
+            validate_sound_file(&assets_dir.join(asset_path.path()), MAX_USER_SOUND_DURATION)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn builtin_sounds_are_always_available() {
+        let library = AlertSoundLibrary::new(None);
+        assert_eq!(
+            library
+                .entries
+                .iter()
+                .map(|entry| entry.sound.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                AlertSound::Builtin(Sound::ShortSoft),
+                AlertSound::Builtin(Sound::SimpleTone),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::AlertSink;
+use crate::AlertSound;
+use bevy::asset::AssetPath;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::log::error;
+use bevy::log::warn;
+use crossbeam_channel::Sender;
+use crossbeam_channel::bounded;
+use eyre::Context;
+use rodio::Decoder;
+use rodio::OutputStream;
+use rodio::Sink;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+
+struct PlayRequest {
+    path: PathBuf,
+    volume: f32,
+}
+
+/// Plays sounds through the default output device and uses PowerShell for speech and toasts.
+pub struct SystemAlertSink {
+    play_tx: Sender<PlayRequest>,
+}
+impl SystemAlertSink {
+    pub fn new() -> eyre::Result<Self> {
+        let (play_tx, play_rx) = bounded::<PlayRequest>(8);
+        // The output stream is not Send, so it lives on its own thread for the life of the sink
+        thread::Builder::new()
+            .name("AlertAudio".to_string())
+            .spawn(move || {
+                let (_stream, handle) = match OutputStream::try_default() {
+                    Ok(x) => x,
+                    Err(e) => {
+                        error!("No audio output for alerts: {e}");
+                        return;
+                    }
+                };
+                for request in play_rx.iter() {
+                    let result: eyre::Result<()> = (|| {
+                        let file = File::open(&request.path)?;
+                        let source = Decoder::new(BufReader::new(file))?;
+                        let sink = Sink::try_new(&handle)?;
+                        sink.set_volume(request.volume);
+                        sink.append(source);
+                        sink.detach();
+                        Ok(())
+                    })();
+                    if let Err(e) = result {
+                        warn!("Failed to play {}: {e}", request.path.display());
+                    }
+                }
+            })?;
+        Ok(Self { play_tx })
+    }
+}
+
+pub fn resolve_alert_sound(sound: &AlertSound) -> PathBuf {
+    match sound {
+        AlertSound::Builtin(sound) => {
+            let asset_path: AssetPath = (*sound).into();
+            FileAssetReader::get_base_path()
+                .join("assets")
+                .join(asset_path.path())
+        }
+        AlertSound::File(path) => path.clone(),
+    }
+}
+
+impl AlertSink for SystemAlertSink {
+    fn play_sound(&self, sound: &AlertSound, volume: f32) -> eyre::Result<()> {
+        let path = resolve_alert_sound(sound);
+        if !Path::new(&path).exists() {
+            eyre::bail!("Alert sound {} does not exist", path.display());
+        }
+        self.play_tx
+            .try_send(PlayRequest { path, volume })
+            .wrap_err("Alert audio thread is busy or gone")?;
+        Ok(())
+    }
+
+    fn speak(&self, text: &str, volume: f32) -> eyre::Result<()> {
+        let volume = (volume.clamp(0.0, 1.0) * 100.0).round() as u32;
+        spawn_powershell(&format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $s.Volume = {volume}; \
+             $s.Speak({})",
+            quote(text)
+        ))
+    }
+
+    fn notify(&self, title: &str, body: &str) -> eyre::Result<()> {
+        spawn_powershell(&format!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+             $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+             $text = $template.GetElementsByTagName('text'); \
+             $text.Item(0).AppendChild($template.CreateTextNode({})) > $null; \
+             $text.Item(1).AppendChild($template.CreateTextNode({})) > $null; \
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier({}).Show([Windows.UI.Notifications.ToastNotification]::new($template))",
+            quote(title),
+            quote(body),
+            // Toasts need a registered app id, borrow the one PowerShell ships with
+            quote(r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe"),
+        ))
+    }
+}
+
+/// Single-quoted PowerShell string literal.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+#[cfg(windows)]
+fn spawn_powershell(script: &str) -> eyre::Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .wrap_err("Failed to start PowerShell")?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn spawn_powershell(_script: &str) -> eyre::Result<()> {
+    eyre::bail!("Speech and notifications are only supported on Windows")
+}
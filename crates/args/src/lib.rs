@@ -1,6 +1,8 @@
 use clap::Parser;
 use clap::Subcommand;
 use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Parser)]
 #[command(name = "youre-muted-btw", bin_name = "youre-muted-btw", version= env!("CARGO_PKG_VERSION"))]
@@ -36,6 +38,66 @@ impl Args {
 pub enum Command {
     Tray,
     WelcomeGui,
+    /// Remote control the running instance
+    Ctl(CtlArgs),
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct CtlArgs {
+    #[command(subcommand)]
+    pub verb: CtlVerb,
+}
+
+#[derive(Debug, Subcommand, Clone, PartialEq)]
+pub enum CtlVerb {
+    /// Show or hide the mute status window
+    ToggleWindow,
+    /// Open the world inspector
+    ShowInspector,
+    /// Print the mute, voice activity, microphone and alert state as JSON
+    Status,
+    /// Print message counts, drops, queue depths and handler latency of every worker thread as JSON
+    Metrics,
+    /// Listen to the microphone with this name
+    SetMic { name: String },
+    /// Flip the mute state as if the mute button was clicked, until it is next read
+    MuteSim,
+    /// Stop alerting for a while, such as 30s, 15m or 1h
+    PauseAlerts {
+        #[arg(value_parser = parse_duration)]
+        duration: Duration,
+    },
+    /// Log a message in the GUI
+    DebugMsg { text: String },
+    /// Save every UI element on the desktop to a .json.gz or .ron.gz file to attach to a bug report
+    Snapshot { path: PathBuf },
+}
+
+/// Parses a whole number followed by `ms`, `s`, `m` or `h`, a bare number is seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("{text:?} does not start with a number"))?;
+    let millis_per_unit = match unit.trim() {
+        "ms" => 1,
+        "" | "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        other => {
+            return Err(format!(
+                "Unknown duration unit {other:?}, use ms, s, m or h"
+            ));
+        }
+    };
+    number
+        .checked_mul(millis_per_unit)
+        .map(Duration::from_millis)
+        .ok_or_else(|| format!("{text:?} is too long"))
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -44,3 +106,47 @@ pub struct GlobalArgs {
     #[arg(long, global = true, default_value = "false")]
     pub debug: bool,
 }
+
+#[cfg(test)]
+mod test {
+    use crate::Args;
+    use crate::Command;
+    use crate::CtlVerb;
+    use crate::parse_duration;
+    use clap::Parser;
+    use std::time::Duration;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("3 days").is_err());
+    }
+
+    #[test]
+    fn ctl_verbs_parse() {
+        let args = Args::parse_from(["youre-muted-btw", "ctl", "pause-alerts", "10m"]);
+        let Some(Command::Ctl(ctl)) = args.command else {
+            panic!("{:?}", args.command);
+        };
+        assert_eq!(
+            ctl.verb,
+            CtlVerb::PauseAlerts {
+                duration: Duration::from_secs(600)
+            }
+        );
+        let args = Args::parse_from(["youre-muted-btw", "ctl", "set-mic", "Headset Microphone"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Ctl(ctl)) if ctl.verb == CtlVerb::SetMic { name: "Headset Microphone".to_string() }
+        ));
+        let args = Args::parse_from(["youre-muted-btw", "ctl", "snapshot", "discord.ron.gz"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Ctl(ctl)) if ctl.verb == CtlVerb::Snapshot { path: "discord.ron.gz".into() }
+        ));
+    }
+}
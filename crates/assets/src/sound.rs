@@ -1,7 +1,10 @@
 use bevy::asset::AssetPath;
+use bevy::reflect::Reflect;
+use serde::Deserialize;
+use serde::Serialize;
 use strum::VariantArray;
 
-#[derive(VariantArray, Clone, Copy, Eq, Debug, PartialEq)]
+#[derive(VariantArray, Clone, Copy, Eq, Debug, PartialEq, Reflect, Serialize, Deserialize)]
 pub enum Sound {
     ShortSoft,
     SimpleTone,
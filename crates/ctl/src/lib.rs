@@ -0,0 +1,210 @@
+//! `youre-muted-btw ctl`, which sends one request to the running GUI and exits.
+//!
+//! `ctl snapshot` is the exception, it reads the desktop itself so it works without a running GUI.
+
+use eyre::bail;
+use eyre::eyre;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use ymb_args::CtlArgs;
+use ymb_args::CtlVerb;
+use ymb_ipc::BevyboundIPCMessage;
+use ymb_ipc::Frame;
+use ymb_ipc::FrameWriter;
+use ymb_ipc::InstanceRecord;
+use ymb_ipc::InstanceRegistry;
+use ymb_ipc::IpcMessageHandler;
+use ymb_ipc::IpcTransport;
+use ymb_ipc::TrayboundIPCMessage;
+use ymb_ipc::connect;
+use ymb_ipc::spawn_reader;
+
+/// How long `ctl status` and `ctl metrics` wait for the GUI to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn run(args: CtlArgs) -> eyre::Result<()> {
+    if let CtlVerb::Snapshot { path } = &args.verb {
+        return save_snapshot(path);
+    }
+    let message = message_for(&args.verb)
+        .ok_or_else(|| eyre!("{:?} is not sent to the running instance", args.verb))?;
+    let registry = registry()?;
+    let instance = registry
+        .newest_other()?
+        .ok_or_else(|| eyre!("No running instance found in {}", registry.path().display()))?;
+    let (reply_tx, reply_rx) = mpsc::channel();
+    let (mut writer, _reader) = spawn_reader(
+        connect(&instance.endpoint)?,
+        instance.secret,
+        AwaitReply(reply_tx),
+    )?;
+    let request_id = writer.send(&message)?;
+    let what = match args.verb {
+        CtlVerb::Status => "status",
+        CtlVerb::Metrics => "metrics",
+        _ => return Ok(()),
+    };
+    let reply = reply_rx
+        .recv_timeout(REPLY_TIMEOUT)
+        .map_err(|_| eyre!("The running instance did not report its {}", what))?;
+    if reply.request_id != request_id {
+        bail!(
+            "The running instance answered request {} instead of request {}",
+            reply.request_id,
+            request_id
+        );
+    }
+    let json = match (&args.verb, reply.message) {
+        (CtlVerb::Status, TrayboundIPCMessage::Status(status)) => {
+            serde_json::to_string_pretty(&status)?
+        }
+        (CtlVerb::Metrics, TrayboundIPCMessage::Metrics(metrics)) => {
+            serde_json::to_string_pretty(&metrics)?
+        }
+        (_, message) => bail!(
+            "The running instance answered with {:?} instead of its {}",
+            message,
+            what
+        ),
+    };
+    println!("{json}");
+    Ok(())
+}
+
+/// Hands a plain launch to the instance that is already running, returning `false` when there is none.
+///
+/// The running instance opens its settings, so launching again shows the app instead of starting a second tray.
+pub fn forward_launch() -> eyre::Result<bool> {
+    forward_launch_to(&registry()?)
+}
+
+/// An instance that cannot be reached is forgotten, its pid may belong to another process by now
+/// or its GUI may be hung, and either way a fresh launch is the only way to get the app back.
+fn forward_launch_to(registry: &InstanceRegistry) -> eyre::Result<bool> {
+    let Some(instance) = registry.newest_other()? else {
+        return Ok(false);
+    };
+    eprintln!(
+        "Already running as pid {} since {}, showing it instead",
+        instance.pid, instance.started_at
+    );
+    if let Err(e) = send(&instance, &BevyboundIPCMessage::ShowSettings) {
+        eprintln!(
+            "Could not reach the running instance at {}, starting a new one: {}",
+            instance.endpoint, e
+        );
+        registry.unregister(instance.pid)?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn registry() -> eyre::Result<InstanceRegistry> {
+    InstanceRegistry::for_current_user()
+        .ok_or_else(|| eyre!("Could not determine where running instances are recorded"))
+}
+
+fn send(instance: &InstanceRecord, message: &BevyboundIPCMessage) -> eyre::Result<()> {
+    let (_, writer) = connect(&instance.endpoint)?.split()?;
+    FrameWriter::new(writer, instance.secret.clone()).send(message)?;
+    Ok(())
+}
+
+/// The request the GUI handles for `verb`, `None` for the verbs ctl handles itself.
+pub fn message_for(verb: &CtlVerb) -> Option<BevyboundIPCMessage> {
+    Some(match verb {
+        CtlVerb::ToggleWindow => BevyboundIPCMessage::TrayIconClicked,
+        CtlVerb::ShowInspector => BevyboundIPCMessage::ShowWorldInspector,
+        CtlVerb::Status => BevyboundIPCMessage::RequestStatus,
+        CtlVerb::Metrics => BevyboundIPCMessage::RequestMetrics,
+        CtlVerb::SetMic { name } => BevyboundIPCMessage::SelectMic(name.clone()),
+        CtlVerb::MuteSim => BevyboundIPCMessage::SimulateMuteToggle,
+        CtlVerb::PauseAlerts { duration } => BevyboundIPCMessage::PauseAlerts {
+            duration_ms: duration.as_millis() as u64,
+        },
+        CtlVerb::DebugMsg { text } => BevyboundIPCMessage::DebugMessageReceived(text.clone()),
+        CtlVerb::Snapshot { .. } => return None,
+    })
+}
+
+/// Writes the desktop's element tree to `path`, to be read back with `UISnapshot::load` anywhere.
+#[cfg(windows)]
+fn save_snapshot(path: &Path) -> eyre::Result<()> {
+    ymb_ui_automation::UISnapshot::capture_desktop()?.save(path)?;
+    eprintln!("Saved the UI tree to {}", path.display());
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn save_snapshot(_path: &Path) -> eyre::Result<()> {
+    bail!("UI snapshots can only be taken on Windows")
+}
+
+/// Passes along status and metrics answers and ignores the tray updates every client receives.
+///
+/// Answers only go to the client that asked, so the first one is ours.
+struct AwaitReply(mpsc::Sender<Frame<TrayboundIPCMessage>>);
+impl IpcMessageHandler<TrayboundIPCMessage> for AwaitReply {
+    fn handle(&mut self, frame: Frame<TrayboundIPCMessage>) {
+        if matches!(
+            frame.message,
+            TrayboundIPCMessage::Status(_) | TrayboundIPCMessage::Metrics(_)
+        ) {
+            let _ = self.0.send(frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::forward_launch_to;
+    use crate::message_for;
+    use std::time::Duration;
+    use ymb_args::CtlVerb;
+    use ymb_ipc::BevyboundIPCMessage;
+    use ymb_ipc::InstanceRecord;
+    use ymb_ipc::InstanceRegistry;
+    use ymb_ipc::SessionSecret;
+
+    #[test]
+    fn pause_alerts_is_sent_in_milliseconds() {
+        assert_eq!(
+            message_for(&CtlVerb::PauseAlerts {
+                duration: Duration::from_secs(90),
+            }),
+            Some(BevyboundIPCMessage::PauseAlerts {
+                duration_ms: 90_000
+            })
+        );
+    }
+
+    #[test]
+    fn snapshots_are_not_sent() {
+        assert_eq!(
+            message_for(&CtlVerb::Snapshot {
+                path: "tree.json.gz".into(),
+            }),
+            None
+        );
+    }
+
+    /// The test runner's parent is running but is not listening for us.
+    #[cfg(unix)]
+    #[test]
+    fn unreachable_instances_are_forgotten() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let registry = InstanceRegistry::new(dir.path().join("instances.json"));
+        let stale = InstanceRecord {
+            pid: std::os::unix::process::parent_id(),
+            ..InstanceRecord::current(
+                dir.path().join("gone.sock").display().to_string(),
+                SessionSecret::from_bytes([7; 32]),
+            )
+        };
+        registry.register(stale)?;
+        assert!(!forward_launch_to(&registry)?);
+        assert_eq!(registry.live_instances()?, Vec::new());
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use hmac::Hmac;
+use hmac::Mac;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use sha2::Sha256;
+
+/// How the tray hands the secret to the GUI it spawns.
+pub const SECRET_ENV_VAR: &str = "YMB_IPC_SECRET";
+
+pub const SECRET_LEN: usize = 32;
+
+/// Length of the HMAC-SHA256 tag that ends every frame.
+pub const TAG_LEN: usize = 32;
+
+/// The key one tray and its GUI tag every frame with, so other local processes that find the endpoint cannot speak for either.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionSecret([u8; SECRET_LEN]);
+
+impl SessionSecret {
+    pub fn generate() -> Result<Self, SecretError> {
+        let mut bytes = [0; SECRET_LEN];
+        getrandom::fill(&mut bytes).map_err(SecretError::Random)?;
+        Ok(Self(bytes))
+    }
+
+    pub const fn from_bytes(bytes: [u8; SECRET_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Reads the secret the tray put in [`SECRET_ENV_VAR`].
+    pub fn from_env() -> Result<Self, SecretError> {
+        let text = std::env::var(SECRET_ENV_VAR).map_err(|_| SecretError::Missing)?;
+        Self::from_hex(&text)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    pub fn from_hex(text: &str) -> Result<Self, SecretError> {
+        let text = text.trim();
+        if text.len() != SECRET_LEN * 2 || !text.is_ascii() {
+            return Err(SecretError::Malformed);
+        }
+        let mut bytes = [0; SECRET_LEN];
+        for (byte, pair) in bytes.iter_mut().zip(text.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| SecretError::Malformed)?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| SecretError::Malformed)?;
+        }
+        Ok(Self(bytes))
+    }
+
+    /// The tag for a frame with this header and payload.
+    pub fn tag(&self, header: &[u8], payload: &[u8]) -> [u8; TAG_LEN] {
+        self.mac(header, payload).finalize().into_bytes().into()
+    }
+
+    /// Compares in constant time, so a forger learns nothing from how long a rejection took.
+    pub fn verify(&self, header: &[u8], payload: &[u8], tag: &[u8]) -> bool {
+        self.mac(header, payload).verify_slice(tag).is_ok()
+    }
+
+    fn mac(&self, header: &[u8], payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(header);
+        mac.update(payload);
+        mac
+    }
+}
+
+impl std::fmt::Debug for SessionSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionSecret(..)")
+    }
+}
+
+impl Serialize for SessionSecret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionSecret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Self::from_hex(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug)]
+pub enum SecretError {
+    /// [`SECRET_ENV_VAR`] is not set, so this process was not spawned by a tray.
+    Missing,
+    Malformed,
+    Random(getrandom::Error),
+}
+impl std::fmt::Display for SecretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretError::Missing => write!(f, "{SECRET_ENV_VAR} is not set"),
+            SecretError::Malformed => {
+                write!(f, "IPC secret is not {} hex digits", SECRET_LEN * 2)
+            }
+            SecretError::Random(e) => write!(f, "Failed to generate an IPC secret: {e}"),
+        }
+    }
+}
+impl std::error::Error for SecretError {}
+
+#[cfg(test)]
+mod test {
+    use crate::SessionSecret;
+
+    #[test]
+    fn tags_depend_on_key_and_content() {
+        let secret = SessionSecret::from_bytes([7; 32]);
+        let tag = secret.tag(b"header", b"payload");
+        assert!(secret.verify(b"header", b"payload", &tag));
+        assert!(!secret.verify(b"header", b"payloaf", &tag));
+        assert!(!secret.verify(b"headex", b"payload", &tag));
+        assert!(!SessionSecret::from_bytes([8; 32]).verify(b"header", b"payload", &tag));
+        assert!(!secret.verify(b"header", b"payload", &tag[..31]));
+    }
+
+    #[test]
+    fn secrets_survive_hex() -> eyre::Result<()> {
+        let secret = SessionSecret::generate()?;
+        assert_eq!(SessionSecret::from_hex(&secret.to_hex())?, secret);
+        assert!(SessionSecret::from_hex("abc").is_err());
+        assert!(SessionSecret::from_hex(&"zz".repeat(32)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use crate::Frame;
+use crate::FrameError;
+use crate::FrameWriter;
+use crate::IpcTransport;
+use crate::SessionSecret;
+use crate::read_frame;
+use bevy::reflect::Reflect;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::TrySendError;
+use std::thread::JoinHandle;
+use tracing::warn;
+
+/// Reacts to the messages arriving on one connection.
+pub trait IpcMessageHandler<T>: Send + 'static {
+    /// Called by [`serve_connection`] once the client can be broadcast and replied to.
+    fn connected(&mut self, _client: ClientId) {}
+
+    fn handle(&mut self, frame: Frame<T>);
+
+    /// Called once when reading stops, with [`FrameError::Closed`] for a clean hang up.
+    fn disconnected(&mut self, _reason: &FrameError) {}
+}
+
+/// Hands frames to `handler` until the peer hangs up or sends something unrecoverable.
+///
+/// Frames that do not decode are skipped, so a newer peer sending an unknown message does not drop the connection.
+pub fn read_frames<T: DeserializeOwned>(
+    reader: &mut impl Read,
+    secret: &SessionSecret,
+    handler: &mut impl IpcMessageHandler<T>,
+) -> FrameError {
+    let reason = loop {
+        match read_frame(reader, secret) {
+            Ok(frame) => handler.handle(frame),
+            Err(e) if !e.is_fatal() => warn!("Skipping IPC frame: {}", e),
+            Err(e) => break e,
+        }
+    };
+    handler.disconnected(&reason);
+    reason
+}
+
+/// Reads on a background thread, returning the write half for the caller to send with.
+pub fn spawn_reader<In, T>(
+    transport: T,
+    secret: SessionSecret,
+    mut handler: impl IpcMessageHandler<In>,
+) -> std::io::Result<(FrameWriter<T::Writer>, JoinHandle<FrameError>)>
+where
+    In: DeserializeOwned + 'static,
+    T: IpcTransport,
+{
+    let (mut reader, writer) = transport.split()?;
+    let handle = std::thread::Builder::new()
+        .name("IpcReader".to_string())
+        .spawn({
+            let secret = secret.clone();
+            move || read_frames(&mut reader, &secret, &mut handler)
+        })?;
+    Ok((FrameWriter::new(writer, secret), handle))
+}
+
+/// Registers the write half with `clients`, then handles incoming frames on this thread until the connection ends.
+pub fn serve_connection<In, Out, T>(
+    transport: T,
+    secret: &SessionSecret,
+    clients: &IpcClients<Out>,
+    handler: &mut impl IpcMessageHandler<In>,
+) -> FrameError
+where
+    In: DeserializeOwned,
+    Out: Serialize + Send + 'static,
+    T: IpcTransport,
+{
+    let (mut reader, writer) = match transport.split() {
+        Ok(halves) => halves,
+        Err(e) => {
+            let reason = FrameError::Io(e);
+            handler.disconnected(&reason);
+            return reason;
+        }
+    };
+    let client = match clients.add(writer, secret.clone()) {
+        Ok(client) => client,
+        Err(e) => {
+            let reason = FrameError::Io(e);
+            handler.disconnected(&reason);
+            return reason;
+        }
+    };
+    handler.connected(client);
+    read_frames(&mut reader, secret, handler)
+}
+
+/// How many messages can wait for a client before it is considered stuck and dropped.
+pub const CLIENT_QUEUE_LEN: usize = 64;
+
+/// Tells the clients in one [`IpcClients`] apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct ClientId(u64);
+
+/// Who asked, so the answer goes back to them alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct IpcRequester {
+    pub client: ClientId,
+    pub request_id: u64,
+}
+
+/// A message waiting for a client's writer thread, with the request id it answers if it is a reply.
+struct Queued<T> {
+    request_id: Option<u64>,
+    message: T,
+}
+
+type ClientQueues<T> = Vec<(ClientId, SyncSender<Queued<T>>)>;
+
+/// The write halves of every connected client, so messages can be pushed to all of them.
+///
+/// Each client is written to from its own thread, so a client that stops reading never blocks the sender.
+pub struct IpcClients<T> {
+    queues: Arc<Mutex<ClientQueues<T>>>,
+    next_id: Arc<AtomicU64>,
+}
+impl<T> Clone for IpcClients<T> {
+    fn clone(&self) -> Self {
+        Self {
+            queues: self.queues.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+impl<T> Default for IpcClients<T> {
+    fn default() -> Self {
+        Self {
+            queues: Arc::new(Mutex::new(Vec::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+}
+impl<T> std::fmt::Debug for IpcClients<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpcClients")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+impl<T: Serialize + Send + 'static> IpcClients<T> {
+    /// Starts the thread writing to the client, which ends when the client is forgotten or its connection is gone.
+    pub fn add(
+        &self,
+        writer: impl Write + Send + 'static,
+        secret: SessionSecret,
+    ) -> std::io::Result<ClientId> {
+        let (queue, messages) = mpsc::sync_channel::<Queued<T>>(CLIENT_QUEUE_LEN);
+        let mut writer = FrameWriter::new(writer, secret);
+        std::thread::Builder::new()
+            .name("IpcWriter".to_string())
+            .spawn(move || {
+                for queued in messages {
+                    let sent = match queued.request_id {
+                        Some(request_id) => writer.reply(request_id, &queued.message),
+                        None => writer.send(&queued.message).map(drop),
+                    };
+                    if let Err(e) = sent {
+                        warn!("Dropping IPC client: {}", e);
+                        return;
+                    }
+                }
+            })?;
+        let client = ClientId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.lock().push((client, queue));
+        Ok(client)
+    }
+
+    /// Queues an answer for the client that asked, returning whether it is still connected.
+    pub fn reply(&self, to: &IpcRequester, message: T) -> bool {
+        let mut queues = self.lock();
+        let Some(index) = queues.iter().position(|(client, _)| *client == to.client) else {
+            return false;
+        };
+        let queued = Queued {
+            request_id: Some(to.request_id),
+            message,
+        };
+        if !try_queue(&queues[index].1, queued) {
+            queues.remove(index);
+            return false;
+        }
+        true
+    }
+}
+impl<T: Clone> IpcClients<T> {
+    /// Queues for every client without waiting on any of them,
+    /// forgetting the ones whose connection is gone or that fell [`CLIENT_QUEUE_LEN`] messages behind.
+    ///
+    /// Returns how many clients the message was queued for.
+    pub fn broadcast(&self, message: &T) -> usize {
+        let mut queues = self.lock();
+        queues.retain(|(_, queue)| {
+            try_queue(
+                queue,
+                Queued {
+                    request_id: None,
+                    message: message.clone(),
+                },
+            )
+        });
+        queues.len()
+    }
+}
+impl<T> IpcClients<T> {
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ClientQueues<T>> {
+        // Nothing panics while holding the lock, but a poisoned list is still usable
+        self.queues.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns `false` when the client should be forgotten.
+fn try_queue<T>(queue: &SyncSender<Queued<T>>, queued: Queued<T>) -> bool {
+    match queue.try_send(queued) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            warn!("Dropping IPC client, it is not reading its messages");
+            false
+        }
+        Err(TrySendError::Disconnected(_)) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::BevyboundIPCMessage;
+    use crate::CLIENT_QUEUE_LEN;
+    use crate::Frame;
+    use crate::FrameError;
+    use crate::IpcClients;
+    use crate::IpcMessageHandler;
+    use crate::IpcRequester;
+    use crate::IpcTransport;
+    use crate::SessionSecret;
+    use crate::TrayMuteState;
+    use crate::TrayboundIPCMessage;
+    use crate::memory_duplex;
+    use crate::read_frame;
+    use crate::serve_connection;
+    use crate::spawn_reader;
+    use std::io::Write;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    /// Forwards to a channel so the test thread can wait on it.
+    struct Forward<T>(mpsc::Sender<T>);
+    impl<T: Send + 'static> IpcMessageHandler<T> for Forward<T> {
+        fn handle(&mut self, frame: Frame<T>) {
+            let _ = self.0.send(frame.message);
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    const SECRET: SessionSecret = SessionSecret::from_bytes([7; 32]);
+
+    #[test]
+    fn tray_and_gui_round_trip() -> eyre::Result<()> {
+        let (tray_end, gui_end) = memory_duplex();
+        let clients = IpcClients::<TrayboundIPCMessage>::default();
+
+        let (bevybound_tx, bevybound_rx) = mpsc::channel::<BevyboundIPCMessage>();
+        let gui = std::thread::spawn({
+            let clients = clients.clone();
+            move || serve_connection(gui_end, &SECRET, &clients, &mut Forward(bevybound_tx))
+        });
+
+        let (traybound_tx, traybound_rx) = mpsc::channel();
+        let (mut tray_writer, tray_reader) =
+            spawn_reader::<TrayboundIPCMessage, _>(tray_end, SECRET, Forward(traybound_tx))?;
+
+        tray_writer.send(&BevyboundIPCMessage::ShowSettings)?;
+        tray_writer.send(&BevyboundIPCMessage::TrayIconClicked)?;
+        assert_eq!(
+            bevybound_rx.recv_timeout(TIMEOUT)?,
+            BevyboundIPCMessage::ShowSettings
+        );
+        assert_eq!(
+            bevybound_rx.recv_timeout(TIMEOUT)?,
+            BevyboundIPCMessage::TrayIconClicked
+        );
+
+        // The GUI registers the client before reading, so it can be pushed to by now
+        assert_eq!(
+            clients.broadcast(&TrayboundIPCMessage::MuteState(TrayMuteState::Muted)),
+            1
+        );
+        assert_eq!(
+            traybound_rx.recv_timeout(TIMEOUT)?,
+            TrayboundIPCMessage::MuteState(TrayMuteState::Muted)
+        );
+
+        // Either side hanging up ends the other cleanly
+        drop(tray_writer);
+        assert!(matches!(gui.join().unwrap(), FrameError::Closed));
+        drop(clients);
+        assert!(matches!(tray_reader.join().unwrap(), FrameError::Closed));
+        Ok(())
+    }
+
+    #[test]
+    fn gone_clients_are_forgotten() -> eyre::Result<()> {
+        let (ours, theirs) = memory_duplex();
+        let (_, writer) = ours.split()?;
+        let clients = IpcClients::<TrayboundIPCMessage>::default();
+        clients.add(writer, SECRET)?;
+        assert_eq!(clients.broadcast(&TrayboundIPCMessage::Error(None)), 1);
+        drop(theirs);
+        // The writer thread notices on its next write, so the client is forgotten a broadcast later
+        let deadline = Instant::now() + TIMEOUT;
+        while clients.broadcast(&TrayboundIPCMessage::Error(None)) > 0 {
+            assert!(Instant::now() < deadline, "the client was never forgotten");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(clients.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn replies_go_to_the_requester_alone() -> eyre::Result<()> {
+        let clients = IpcClients::<TrayboundIPCMessage>::default();
+        let (asker_ours, asker_theirs) = memory_duplex();
+        let (bystander_ours, bystander_theirs) = memory_duplex();
+        let asker = clients.add(asker_ours.split()?.1, SECRET)?;
+        clients.add(bystander_ours.split()?.1, SECRET)?;
+        let (mut asker_reader, _) = asker_theirs.split()?;
+        let (mut bystander_reader, _) = bystander_theirs.split()?;
+
+        let requester = IpcRequester {
+            client: asker,
+            request_id: 7,
+        };
+        assert!(clients.reply(&requester, TrayboundIPCMessage::Error(None)));
+        clients.broadcast(&TrayboundIPCMessage::MuteState(TrayMuteState::Muted));
+
+        let reply = read_frame::<TrayboundIPCMessage>(&mut asker_reader, &SECRET)?;
+        assert_eq!(reply.request_id, 7);
+        assert_eq!(reply.message, TrayboundIPCMessage::Error(None));
+        // The bystander only hears the broadcast
+        assert_eq!(
+            read_frame::<TrayboundIPCMessage>(&mut bystander_reader, &SECRET)?.message,
+            TrayboundIPCMessage::MuteState(TrayMuteState::Muted)
+        );
+        assert_eq!(
+            read_frame::<TrayboundIPCMessage>(&mut asker_reader, &SECRET)?.message,
+            TrayboundIPCMessage::MuteState(TrayMuteState::Muted)
+        );
+        Ok(())
+    }
+
+    /// Blocks every write until told to fail, like a client that stopped reading.
+    struct Stuck(mpsc::Receiver<()>);
+    impl Write for Stuck {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            let _ = self.0.recv();
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stuck_clients_do_not_block_broadcasts() {
+        let (unstick, stuck) = mpsc::channel();
+        let clients = IpcClients::<TrayboundIPCMessage>::default();
+        clients.add(Stuck(stuck), SECRET).unwrap();
+        let started = Instant::now();
+        let mut queued = 0;
+        while clients.broadcast(&TrayboundIPCMessage::Error(None)) > 0 {
+            queued += 1;
+        }
+        // The queue fills up, plus the message the blocked writer took off it
+        assert!((CLIENT_QUEUE_LEN..=CLIENT_QUEUE_LEN + 1).contains(&queued));
+        assert!(started.elapsed() < TIMEOUT);
+        assert!(clients.is_empty());
+        drop(unstick);
+    }
+}
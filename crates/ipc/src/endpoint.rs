@@ -0,0 +1,191 @@
+use std::io;
+
+#[cfg(unix)]
+use crate::create_private_dir;
+#[cfg(windows)]
+use interprocess::os::windows::named_pipe::DuplexPipeStream;
+#[cfg(windows)]
+use interprocess::os::windows::named_pipe::PipeListener;
+#[cfg(windows)]
+use interprocess::os::windows::named_pipe::PipeListenerOptions;
+#[cfg(windows)]
+use interprocess::os::windows::named_pipe::pipe_mode;
+#[cfg(unix)]
+use std::fs::Permissions;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// A connection to or from the GUI, a named pipe on Windows and a Unix domain socket elsewhere.
+#[cfg(windows)]
+pub type IpcStream = DuplexPipeStream<pipe_mode::Bytes>;
+/// A connection to or from the GUI, a named pipe on Windows and a Unix domain socket elsewhere.
+#[cfg(unix)]
+pub type IpcStream = UnixStream;
+
+/// Turns a name unique to one GUI into the path it listens on.
+pub fn endpoint_path(name: &str) -> String {
+    #[cfg(windows)]
+    {
+        format!(r"\\.\pipe\{name}")
+    }
+    #[cfg(unix)]
+    {
+        socket_dir()
+            .join(format!("{name}.sock"))
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Where sockets are created, the runtime directory is already private to the user.
+#[cfg(unix)]
+fn socket_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("youre-muted-btw"),
+        None => {
+            // The temp directory is shared, so each user gets their own directory in it
+            let uid = unsafe { libc::getuid() };
+            std::env::temp_dir().join(format!("youre-muted-btw-{uid}"))
+        }
+    }
+}
+
+/// Accepts clients on a path from [`endpoint_path`].
+pub struct IpcListener {
+    #[cfg(windows)]
+    inner: PipeListener<pipe_mode::Bytes, pipe_mode::Bytes>,
+    #[cfg(unix)]
+    inner: UnixListener,
+    #[cfg(unix)]
+    path: String,
+}
+
+impl IpcListener {
+    pub fn bind(path: &str) -> io::Result<Self> {
+        #[cfg(windows)]
+        {
+            Ok(Self {
+                inner: PipeListenerOptions::new()
+                    .path(path)
+                    .create_duplex::<pipe_mode::Bytes>()?,
+            })
+        }
+        #[cfg(unix)]
+        {
+            if let Some(dir) = Path::new(path).parent() {
+                create_private_dir(dir)?;
+            }
+            let inner = UnixListener::bind(path)?;
+            // Connecting takes write access to the socket, so other users cannot send us frames
+            std::fs::set_permissions(path, Permissions::from_mode(0o600))?;
+            Ok(Self {
+                inner,
+                path: path.to_string(),
+            })
+        }
+    }
+
+    /// Blocks until a client connects.
+    pub fn accept(&self) -> io::Result<IpcStream> {
+        #[cfg(windows)]
+        {
+            self.inner.accept()
+        }
+        #[cfg(unix)]
+        {
+            self.inner.accept().map(|(stream, _)| stream)
+        }
+    }
+
+    /// Accepts clients forever, for use in a `for` loop.
+    pub fn incoming(&self) -> impl Iterator<Item = io::Result<IpcStream>> + '_ {
+        std::iter::repeat_with(|| self.accept())
+    }
+}
+
+/// Unlike pipes, socket files outlive their listener.
+#[cfg(unix)]
+impl Drop for IpcListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Connects to a GUI listening on `path`.
+pub fn connect(path: &str) -> io::Result<IpcStream> {
+    #[cfg(windows)]
+    {
+        DuplexPipeStream::<pipe_mode::Bytes>::connect_by_path(path)
+    }
+    #[cfg(unix)]
+    {
+        UnixStream::connect(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::BevyboundIPCMessage;
+    use crate::FrameWriter;
+    use crate::IpcListener;
+    use crate::IpcTransport;
+    use crate::SessionSecret;
+    use crate::TrayMuteState;
+    use crate::TrayboundIPCMessage;
+    use crate::connect;
+    use crate::endpoint_path;
+    use crate::read_frame;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+    #[cfg(unix)]
+    use std::path::Path;
+
+    #[test]
+    fn both_directions_cross_a_real_endpoint() -> eyre::Result<()> {
+        let path = endpoint_path(&format!("ymb-ipc-test-{}", std::process::id()));
+        let secret = SessionSecret::generate()?;
+        let listener = IpcListener::bind(&path)?;
+        let (mut tray_reader, tray_writer) = connect(&path)?.split()?;
+        let (mut gui_reader, gui_writer) = listener.accept()?.split()?;
+
+        // The tray is blocked reading while the GUI writes and the tray writes
+        let tray = std::thread::spawn({
+            let secret = secret.clone();
+            move || read_frame::<TrayboundIPCMessage>(&mut tray_reader, &secret)
+        });
+        FrameWriter::new(tray_writer, secret.clone()).send(&BevyboundIPCMessage::ShowSettings)?;
+        FrameWriter::new(gui_writer, secret.clone())
+            .send(&TrayboundIPCMessage::MuteState(TrayMuteState::Muted))?;
+
+        assert_eq!(
+            read_frame::<BevyboundIPCMessage>(&mut gui_reader, &secret)?.message,
+            BevyboundIPCMessage::ShowSettings
+        );
+        assert_eq!(
+            tray.join().unwrap()?.message,
+            TrayboundIPCMessage::MuteState(TrayMuteState::Muted)
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_the_owner_can_connect() -> eyre::Result<()> {
+        let path = endpoint_path(&format!("ymb-ipc-mode-test-{}", std::process::id()));
+        let _listener = IpcListener::bind(&path)?;
+        let mode = |path: &Path| -> eyre::Result<u32> {
+            Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+        };
+        assert_eq!(mode(Path::new(&path))?, 0o600);
+        assert_eq!(mode(Path::new(&path).parent().unwrap())?, 0o700);
+        Ok(())
+    }
+}
@@ -0,0 +1,425 @@
+use crate::SessionSecret;
+use crate::TAG_LEN;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+/// Starts every frame, so a peer speaking something else is rejected before its bytes are decoded.
+pub const FRAME_MAGIC: [u8; 4] = *b"YMBF";
+
+/// Bumped whenever the header or any message enum sent over IPC changes shape.
+pub const PROTOCOL_VERSION: u16 = 4;
+
+/// Magic, protocol version, payload length and request id, all little endian.
+///
+/// The payload follows, then a [`TAG_LEN`] byte HMAC of the header and payload.
+pub const FRAME_HEADER_LEN: usize = 4 + 2 + 4 + 8;
+
+/// Messages are tiny, so a longer payload means the length is garbage.
+pub const MAX_FRAME_PAYLOAD_LEN: u32 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: u16,
+    pub length: u32,
+    pub request_id: u64,
+}
+impl FrameHeader {
+    pub fn to_bytes(&self) -> [u8; FRAME_HEADER_LEN] {
+        let mut bytes = [0; FRAME_HEADER_LEN];
+        bytes[0..4].copy_from_slice(&FRAME_MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6..10].copy_from_slice(&self.length.to_le_bytes());
+        bytes[10..18].copy_from_slice(&self.request_id.to_le_bytes());
+        bytes
+    }
+
+    /// Checks the magic and version before trusting anything else in the header.
+    pub fn from_bytes(bytes: &[u8; FRAME_HEADER_LEN]) -> Result<Self, FrameError> {
+        let magic: [u8; 4] = bytes[0..4].try_into().unwrap();
+        if magic != FRAME_MAGIC {
+            return Err(FrameError::BadMagic(magic));
+        }
+        let version = u16::from_le_bytes(bytes[4..6].try_into().unwrap());
+        if version != PROTOCOL_VERSION {
+            return Err(FrameError::VersionMismatch {
+                ours: PROTOCOL_VERSION,
+                theirs: version,
+            });
+        }
+        let length = u32::from_le_bytes(bytes[6..10].try_into().unwrap());
+        if length > MAX_FRAME_PAYLOAD_LEN {
+            return Err(FrameError::TooLarge { length });
+        }
+        Ok(Self {
+            version,
+            length,
+            request_id: u64::from_le_bytes(bytes[10..18].try_into().unwrap()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame<T> {
+    pub request_id: u64,
+    pub message: T,
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(std::io::Error),
+    /// The peer hung up between two frames.
+    Closed,
+    /// The peer is not speaking this protocol, such as a build from before framing existed.
+    BadMagic([u8; 4]),
+    /// The peer is a different build of the app.
+    VersionMismatch {
+        ours: u16,
+        theirs: u16,
+    },
+    TooLarge {
+        length: u32,
+    },
+    Encode(bincode::Error),
+    /// The frame was not tagged with our session secret, so it did not come from our tray or GUI.
+    ///
+    /// The frame has been consumed, so the next frame can still be read.
+    BadTag {
+        request_id: u64,
+    },
+    /// The frame was well formed but its payload was not a message we know.
+    ///
+    /// The payload has been consumed, so the next frame can still be read.
+    Decode {
+        request_id: u64,
+        error: bincode::Error,
+    },
+}
+impl FrameError {
+    /// Whether the connection can no longer be trusted to be at a frame boundary or to speak our version.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            FrameError::Decode { .. } | FrameError::Encode(_) | FrameError::BadTag { .. }
+        )
+    }
+}
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "IPC I/O error: {e}"),
+            FrameError::Closed => write!(f, "IPC connection closed"),
+            FrameError::BadMagic(magic) => {
+                write!(f, "IPC peer sent {magic:02x?} instead of a frame header")
+            }
+            FrameError::VersionMismatch { ours, theirs } => write!(
+                f,
+                "IPC protocol version mismatch, we speak version {ours} but the peer speaks version {theirs}, restart both the tray and the GUI"
+            ),
+            FrameError::TooLarge { length } => write!(
+                f,
+                "IPC frame of {length} bytes is over the {MAX_FRAME_PAYLOAD_LEN} byte limit"
+            ),
+            FrameError::Encode(e) => write!(f, "Failed to encode IPC message: {e}"),
+            FrameError::BadTag { request_id } => write!(
+                f,
+                "IPC request {request_id} was not signed with our session secret, rejecting it"
+            ),
+            FrameError::Decode { request_id, error } => {
+                write!(f, "Failed to decode IPC request {request_id}: {error}")
+            }
+        }
+    }
+}
+impl std::error::Error for FrameError {}
+impl From<std::io::Error> for FrameError {
+    fn from(error: std::io::Error) -> Self {
+        FrameError::Io(error)
+    }
+}
+
+/// Writes the header, payload and tag with a single `write_all` so a frame is never split between writers.
+pub fn write_frame<T: Serialize>(
+    writer: &mut impl Write,
+    secret: &SessionSecret,
+    request_id: u64,
+    message: &T,
+) -> Result<(), FrameError> {
+    let payload = bincode::serialize(message).map_err(FrameError::Encode)?;
+    let length = u32::try_from(payload.len()).unwrap_or(u32::MAX);
+    if length > MAX_FRAME_PAYLOAD_LEN {
+        return Err(FrameError::TooLarge { length });
+    }
+    let header = FrameHeader {
+        version: PROTOCOL_VERSION,
+        length,
+        request_id,
+    };
+    let header = header.to_bytes();
+    let mut bytes = Vec::with_capacity(FRAME_HEADER_LEN + payload.len() + TAG_LEN);
+    bytes.extend_from_slice(&header);
+    bytes.extend_from_slice(&payload);
+    bytes.extend_from_slice(&secret.tag(&header, &payload));
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Blocks until a whole frame has been read.
+///
+/// Returns [`FrameError::Closed`] when the peer hangs up cleanly between frames.
+/// The tag is checked before the payload is decoded, so forged frames never reach bincode.
+pub fn read_frame<T: DeserializeOwned>(
+    reader: &mut impl Read,
+    secret: &SessionSecret,
+) -> Result<Frame<T>, FrameError> {
+    let mut header_bytes = [0; FRAME_HEADER_LEN];
+    let mut filled = 0;
+    while filled < FRAME_HEADER_LEN {
+        match reader.read(&mut header_bytes[filled..]) {
+            Ok(0) if filled == 0 => return Err(FrameError::Closed),
+            Ok(0) => return Err(FrameError::Io(ErrorKind::UnexpectedEof.into())),
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) if e.kind() == ErrorKind::BrokenPipe && filled == 0 => {
+                return Err(FrameError::Closed);
+            }
+            Err(e) => return Err(FrameError::Io(e)),
+        }
+    }
+    let header = FrameHeader::from_bytes(&header_bytes)?;
+    let mut payload = vec![0; header.length as usize];
+    reader.read_exact(&mut payload)?;
+    let mut tag = [0; TAG_LEN];
+    reader.read_exact(&mut tag)?;
+    if !secret.verify(&header_bytes, &payload, &tag) {
+        return Err(FrameError::BadTag {
+            request_id: header.request_id,
+        });
+    }
+    let message = bincode::deserialize(&payload).map_err(|error| FrameError::Decode {
+        request_id: header.request_id,
+        error,
+    })?;
+    Ok(Frame {
+        request_id: header.request_id,
+        message,
+    })
+}
+
+/// Numbers and tags outgoing frames so replies and errors can refer back to them.
+pub struct FrameWriter<W> {
+    inner: W,
+    secret: SessionSecret,
+    next_request_id: u64,
+}
+impl<W: Write> FrameWriter<W> {
+    pub fn new(inner: W, secret: SessionSecret) -> Self {
+        Self {
+            inner,
+            secret,
+            next_request_id: 1,
+        }
+    }
+
+    /// Returns the request id the message was sent with.
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<u64, FrameError> {
+        let request_id = self.next_request_id;
+        write_frame(&mut self.inner, &self.secret, request_id, message)?;
+        self.next_request_id += 1;
+        Ok(request_id)
+    }
+
+    /// Sends with the request id of the request being answered, so the peer can tell which reply is its own.
+    pub fn reply<T: Serialize>(&mut self, request_id: u64, message: &T) -> Result<(), FrameError> {
+        write_frame(&mut self.inner, &self.secret, request_id, message)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::FRAME_HEADER_LEN;
+    use crate::Frame;
+    use crate::FrameError;
+    use crate::FrameHeader;
+    use crate::FrameWriter;
+    use crate::PROTOCOL_VERSION;
+    use crate::SessionSecret;
+    use crate::TAG_LEN;
+    use crate::read_frame;
+    use crate::write_frame;
+    use serde::Deserialize;
+    use serde::Serialize;
+    use std::io::Cursor;
+
+    /// A fixed key, so failures reproduce.
+    const SECRET: SessionSecret = SessionSecret::from_bytes([7; 32]);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum TestMessage {
+        Ping,
+        Say(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum OtherMessage {
+        A,
+        B,
+        C,
+        D,
+    }
+
+    #[test]
+    fn many_messages_share_one_stream() -> eyre::Result<()> {
+        let mut writer = FrameWriter::new(Vec::new(), SECRET);
+        assert_eq!(writer.send(&TestMessage::Ping)?, 1);
+        assert_eq!(writer.send(&TestMessage::Say("hello".to_string()))?, 2);
+        let mut reader = Cursor::new(writer.into_inner());
+        assert_eq!(
+            read_frame::<TestMessage>(&mut reader, &SECRET)?,
+            Frame {
+                request_id: 1,
+                message: TestMessage::Ping
+            }
+        );
+        assert_eq!(
+            read_frame::<TestMessage>(&mut reader, &SECRET)?,
+            Frame {
+                request_id: 2,
+                message: TestMessage::Say("hello".to_string())
+            }
+        );
+        assert!(matches!(
+            read_frame::<TestMessage>(&mut reader, &SECRET),
+            Err(FrameError::Closed)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn other_versions_are_reported() -> eyre::Result<()> {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, &SECRET, 7, &TestMessage::Ping)?;
+        bytes[4..6].copy_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+        let error = read_frame::<TestMessage>(&mut Cursor::new(bytes), &SECRET).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                FrameError::VersionMismatch { ours, theirs }
+                    if ours == PROTOCOL_VERSION && theirs == PROTOCOL_VERSION + 1
+            ),
+            "{error:?}"
+        );
+        assert!(error.is_fatal());
+        Ok(())
+    }
+
+    #[test]
+    fn unframed_bincode_is_rejected() -> eyre::Result<()> {
+        // What the tray sent before frames existed
+        let mut bytes = bincode::serialize(&TestMessage::Say("legacy".to_string()))?;
+        bytes.resize(FRAME_HEADER_LEN.max(bytes.len()), 0);
+        let error = read_frame::<TestMessage>(&mut Cursor::new(bytes), &SECRET).unwrap_err();
+        assert!(matches!(error, FrameError::BadMagic(_)), "{error:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_payload_keeps_stream_in_sync() -> eyre::Result<()> {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, &SECRET, 1, &OtherMessage::D)?;
+        write_frame(&mut bytes, &SECRET, 2, &TestMessage::Ping)?;
+        let mut reader = Cursor::new(bytes);
+        let error = read_frame::<TestMessage>(&mut reader, &SECRET).unwrap_err();
+        assert!(
+            matches!(error, FrameError::Decode { request_id: 1, .. }),
+            "{error:?}"
+        );
+        assert!(!error.is_fatal());
+        assert_eq!(
+            read_frame::<TestMessage>(&mut reader, &SECRET)?.request_id,
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_and_oversized_frames_are_errors() -> eyre::Result<()> {
+        let mut bytes = Vec::new();
+        write_frame(
+            &mut bytes,
+            &SECRET,
+            1,
+            &TestMessage::Say("cut short".to_string()),
+        )?;
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(
+            read_frame::<TestMessage>(&mut Cursor::new(bytes), &SECRET),
+            Err(FrameError::Io(_))
+        ));
+
+        let header = FrameHeader {
+            version: PROTOCOL_VERSION,
+            length: u32::MAX,
+            request_id: 1,
+        };
+        assert!(matches!(
+            read_frame::<TestMessage>(&mut Cursor::new(header.to_bytes()), &SECRET),
+            Err(FrameError::TooLarge { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn frames_from_other_keys_are_rejected() -> eyre::Result<()> {
+        let mut bytes = Vec::new();
+        write_frame(
+            &mut bytes,
+            &SessionSecret::from_bytes([8; 32]),
+            1,
+            &TestMessage::Say("forged".to_string()),
+        )?;
+        write_frame(&mut bytes, &SECRET, 2, &TestMessage::Ping)?;
+        let mut reader = Cursor::new(bytes);
+        let error = read_frame::<TestMessage>(&mut reader, &SECRET).unwrap_err();
+        assert!(
+            matches!(error, FrameError::BadTag { request_id: 1 }),
+            "{error:?}"
+        );
+        assert!(!error.is_fatal());
+        assert_eq!(
+            read_frame::<TestMessage>(&mut reader, &SECRET)?.request_id,
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_payloads_are_rejected() -> eyre::Result<()> {
+        let mut bytes = Vec::new();
+        write_frame(
+            &mut bytes,
+            &SECRET,
+            1,
+            &TestMessage::Say("hello".to_string()),
+        )?;
+        let last_payload_byte = bytes.len() - TAG_LEN - 1;
+        bytes[last_payload_byte] ^= 1;
+        let error = read_frame::<TestMessage>(&mut Cursor::new(bytes), &SECRET).unwrap_err();
+        assert!(
+            matches!(error, FrameError::BadTag { request_id: 1 }),
+            "{error:?}"
+        );
+        Ok(())
+    }
+}
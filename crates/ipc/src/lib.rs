@@ -0,0 +1,21 @@
+//! The wire format shared by the tray and the GUI, and the pipe or socket that carries it.
+
+mod auth;
+mod connection;
+mod endpoint;
+mod frame;
+mod message;
+mod private_dir;
+mod registry;
+mod status;
+mod transport;
+
+pub use auth::*;
+pub use connection::*;
+pub use endpoint::*;
+pub use frame::*;
+pub use message::*;
+pub use private_dir::*;
+pub use registry::*;
+pub use status::*;
+pub use transport::*;
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use ymb_worker_plugin::WorkerMetrics;
+
+/// Requests from the tray and other clients to the GUI.
+#[derive(Debug, Clone, PartialEq, Reflect, Event, Serialize, Deserialize)]
+pub enum BevyboundIPCMessage {
+    TrayIconClicked,
+    ShowWorldInspector,
+    ShowSettings,
+    DebugMessageReceived(String),
+    /// Listen to the microphone with this name from now on.
+    SelectMic(String),
+    /// Flip the mute state until the mute button is next read.
+    SimulateMuteToggle,
+    PauseAlerts {
+        duration_ms: u64,
+    },
+    /// Answered with [`TrayboundIPCMessage::Status`].
+    RequestStatus,
+    /// Answered with [`TrayboundIPCMessage::Metrics`].
+    RequestMetrics,
+}
+impl BevyboundIPCMessage {
+    /// Whether the sender waits for an answer, which goes to it alone.
+    pub fn is_request(&self) -> bool {
+        matches!(
+            self,
+            BevyboundIPCMessage::RequestStatus | BevyboundIPCMessage::RequestMetrics
+        )
+    }
+}
+
+/// The mute button as the tray shows it, independent of how the GUI found out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum TrayMuteState {
+    Muted,
+    NotMuted,
+    /// The mute button has not been found yet.
+    #[default]
+    Unknown,
+}
+
+/// GUI state pushed to the tray so its icon, tooltip and menu can reflect it.
+#[derive(Debug, Clone, PartialEq, Reflect, Event, Serialize, Deserialize)]
+pub enum TrayboundIPCMessage {
+    MuteState(TrayMuteState),
+    VoiceActivity {
+        is_speaking: bool,
+    },
+    CurrentMic(Option<String>),
+    /// The latest problem worth showing to the user, or `None` once it has cleared.
+    Error(Option<String>),
+    Status(InstanceStatus),
+    /// How the GUI's worker threads are keeping up, for `ctl metrics`.
+    Metrics(WorkerMetrics),
+}
+
+/// Everything `ctl status` reports about a running instance.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct InstanceStatus {
+    pub version: String,
+    pub mute_state: TrayMuteState,
+    pub is_speaking: bool,
+    pub mic: Option<String>,
+    pub error: Option<String>,
+    /// How much longer alerts stay paused, if they are.
+    pub alerts_paused_for_ms: Option<u64>,
+}
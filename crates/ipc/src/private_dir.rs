@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+#[cfg(windows)]
+use windows::Win32::Foundation::HLOCAL;
+#[cfg(windows)]
+use windows::Win32::Foundation::LocalFree;
+#[cfg(windows)]
+use windows::Win32::Security::ACL;
+#[cfg(windows)]
+use windows::Win32::Security::Authorization::ConvertStringSecurityDescriptorToSecurityDescriptorW;
+#[cfg(windows)]
+use windows::Win32::Security::Authorization::SDDL_REVISION_1;
+#[cfg(windows)]
+use windows::Win32::Security::Authorization::SE_FILE_OBJECT;
+#[cfg(windows)]
+use windows::Win32::Security::Authorization::SetNamedSecurityInfoW;
+#[cfg(windows)]
+use windows::Win32::Security::DACL_SECURITY_INFORMATION;
+#[cfg(windows)]
+use windows::Win32::Security::GetSecurityDescriptorDacl;
+#[cfg(windows)]
+use windows::Win32::Security::PROTECTED_DACL_SECURITY_INFORMATION;
+#[cfg(windows)]
+use windows::Win32::Security::PSECURITY_DESCRIPTOR;
+#[cfg(windows)]
+use windows::core::BOOL;
+#[cfg(windows)]
+use windows::core::HSTRING;
+#[cfg(windows)]
+use windows::core::w;
+
+#[cfg(unix)]
+use std::fs::DirBuilder;
+#[cfg(unix)]
+use std::fs::Permissions;
+#[cfg(unix)]
+use std::io::ErrorKind;
+#[cfg(unix)]
+use std::os::unix::fs::DirBuilderExt;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// Creates `dir` and its parents so that only the current user can look inside `dir`.
+///
+/// An existing `dir` is tightened if it is ours and refused if it belongs to someone else,
+/// since whoever owns it could read or replace what we put there.
+/// On Windows the per-user profile directories are already private, so this only creates it.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        std::fs::create_dir_all(dir)
+    }
+    #[cfg(unix)]
+    {
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        let metadata = std::fs::symlink_metadata(dir)?;
+        if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } {
+            return Err(io::Error::new(
+                ErrorKind::PermissionDenied,
+                format!("{} is not a directory of the current user", dir.display()),
+            ));
+        }
+        if metadata.mode() & 0o077 != 0 {
+            std::fs::set_permissions(dir, Permissions::from_mode(0o700))?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates a file for writing that only the current user can open, replacing any file at `path`.
+///
+/// Access is restricted before anything is written, so secrets put in it are never readable by others.
+pub fn create_private_file(path: &Path) -> io::Result<File> {
+    // Permissions only apply to new files, so nobody can hand us one they can read
+    if let Err(e) = std::fs::remove_file(path)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        return Err(e);
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(path)?;
+    #[cfg(windows)]
+    restrict_to_owner(path)?;
+    Ok(file)
+}
+
+/// Replaces the inherited access of `path` with full access for its owner alone.
+#[cfg(windows)]
+fn restrict_to_owner(path: &Path) -> io::Result<()> {
+    unsafe {
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        // A protected DACL with one entry for the owner, nothing is inherited from the directory
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            w!("D:P(A;;FA;;;OW)"),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )?;
+        let mut present = BOOL::default();
+        let mut defaulted = BOOL::default();
+        let mut dacl: *mut ACL = std::ptr::null_mut();
+        let result = GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted)
+            .and_then(|()| {
+                SetNamedSecurityInfoW(
+                    &HSTRING::from(path),
+                    SE_FILE_OBJECT,
+                    DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                    None,
+                    None,
+                    Some(dacl.cast_const()),
+                    None,
+                )
+                .ok()
+            });
+        let _ = LocalFree(Some(HLOCAL(descriptor.0)));
+        result.map_err(io::Error::from)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use crate::create_private_dir;
+    use crate::create_private_file;
+    use std::fs::Permissions;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn only_the_owner_can_enter() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let private = dir.path().join("a").join("b");
+        create_private_dir(&private)?;
+        assert_eq!(
+            std::fs::metadata(&private)?.permissions().mode() & 0o777,
+            0o700
+        );
+
+        // Directories left open by older builds are closed again
+        std::fs::set_permissions(&private, Permissions::from_mode(0o755))?;
+        create_private_dir(&private)?;
+        assert_eq!(
+            std::fs::metadata(&private)?.permissions().mode() & 0o777,
+            0o700
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_owner_can_read() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("secret");
+        std::fs::write(&path, "left behind")?;
+        std::fs::set_permissions(&path, Permissions::from_mode(0o644))?;
+        create_private_file(&path)?;
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(std::fs::read_to_string(&path)?, "");
+        Ok(())
+    }
+}
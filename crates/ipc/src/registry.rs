@@ -0,0 +1,288 @@
+use crate::SessionSecret;
+use crate::create_private_dir;
+use crate::create_private_file;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// One running tray and the endpoint its GUI listens on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceRecord {
+    pub pid: u32,
+    pub endpoint: String,
+    /// Frames to the endpoint must be tagged with this, so the registry is only readable by the user who wrote it.
+    pub secret: SessionSecret,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+}
+
+impl InstanceRecord {
+    /// A record for the current process.
+    pub fn current(endpoint: String, secret: SessionSecret) -> Self {
+        Self {
+            pid: std::process::id(),
+            endpoint,
+            secret,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    instances: Vec<InstanceRecord>,
+}
+
+/// The running instances of the current user, kept in one JSON file so other processes can find them.
+///
+/// Entries whose process has exited are dropped whenever the file is read.
+/// Changes hold a lock on a file beside it, so launches at the same time don't lose each other's records.
+#[derive(Debug, Clone)]
+pub struct InstanceRegistry {
+    path: PathBuf,
+}
+
+impl InstanceRegistry {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The registry in the user's runtime directory, or wherever `YMB_INSTANCE_REGISTRY` points.
+    pub fn for_current_user() -> Option<Self> {
+        if let Some(path) = std::env::var_os("YMB_INSTANCE_REGISTRY") {
+            return Some(Self::new(path));
+        }
+        dirs::runtime_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| Self::new(dir.join("youre-muted-btw").join("instances.json")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Adds `record`, replacing any earlier record with the same pid.
+    pub fn register(&self, record: InstanceRecord) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let mut instances = self.live_instances_locked()?;
+        instances.retain(|instance| instance.pid != record.pid);
+        instances.push(record);
+        self.save(instances)
+    }
+
+    pub fn unregister(&self, pid: u32) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let mut instances = self.live_instances_locked()?;
+        instances.retain(|instance| instance.pid != pid);
+        self.save(instances)
+    }
+
+    /// The instances whose process is still running, oldest first.
+    pub fn live_instances(&self) -> io::Result<Vec<InstanceRecord>> {
+        let _lock = self.lock()?;
+        self.live_instances_locked()
+    }
+
+    fn live_instances_locked(&self) -> io::Result<Vec<InstanceRecord>> {
+        let mut instances = self.load()?;
+        let count = instances.len();
+        instances.retain(|instance| is_process_alive(instance.pid));
+        if instances.len() != count {
+            self.save(instances.clone())?;
+        }
+        instances.sort_by_key(|instance| instance.started_at);
+        Ok(instances)
+    }
+
+    pub fn find(&self, pid: u32) -> io::Result<Option<InstanceRecord>> {
+        Ok(self
+            .live_instances()?
+            .into_iter()
+            .find(|instance| instance.pid == pid))
+    }
+
+    /// The most recently started instance other than this process.
+    pub fn newest_other(&self) -> io::Result<Option<InstanceRecord>> {
+        let pid = std::process::id();
+        Ok(self
+            .live_instances()?
+            .into_iter()
+            .rfind(|instance| instance.pid != pid))
+    }
+
+    /// Blocks until no other process is changing the registry, which lasts until the file is dropped.
+    fn lock(&self) -> io::Result<File> {
+        if let Some(parent) = self.path.parent() {
+            create_private_dir(parent)?;
+        }
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_file_name(name))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    fn load(&self) -> io::Result<Vec<InstanceRecord>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        match serde_json::from_str::<RegistryFile>(&text) {
+            Ok(file) => Ok(file.instances),
+            Err(e) => {
+                // Starting over only forgets instances, which then cannot be reached by other tools
+                warn!(
+                    "Ignoring unreadable instance registry {}: {}",
+                    self.path.display(),
+                    e
+                );
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Writes beside the registry and renames, so readers never see a half written file.
+    ///
+    /// The file holds the session secrets, so only the current user may read it.
+    fn save(&self, instances: Vec<InstanceRecord>) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_private_dir(parent)?;
+        }
+        let text = serde_json::to_string_pretty(&RegistryFile { instances })?;
+        let temp = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        create_private_file(&temp)?.write_all(text.as_bytes())?;
+        std::fs::rename(&temp, &self.path)
+    }
+}
+
+#[cfg(windows)]
+pub fn is_process_alive(pid: u32) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::Foundation::STILL_ACTIVE;
+    use windows::Win32::System::Threading::GetExitCodeProcess;
+    use windows::Win32::System::Threading::OpenProcess;
+    use windows::Win32::System::Threading::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let mut exit_code = 0;
+        let alive = GetExitCodeProcess(process, &mut exit_code).is_ok()
+            && exit_code == STILL_ACTIVE.0 as u32;
+        let _ = CloseHandle(process);
+        alive
+    }
+}
+
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    // Zero and negative pids address process groups rather than one process
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks that the process exists, EPERM means it does but belongs to someone else
+    let signalled = unsafe { libc::kill(pid, 0) } == 0;
+    signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::InstanceRecord;
+    use crate::InstanceRegistry;
+    use crate::SessionSecret;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+    #[cfg(unix)]
+    use std::path::Path;
+    use std::time::Duration;
+
+    /// Above the largest pid either platform hands out, so never running.
+    const DEAD_PID: u32 = 0x7FFF_FFF0;
+
+    #[test]
+    fn exited_instances_are_dropped() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let registry =
+            InstanceRegistry::new(dir.path().join("youre-muted-btw").join("instances.json"));
+        assert_eq!(registry.live_instances()?, Vec::new());
+
+        let secret = SessionSecret::from_bytes([7; 32]);
+        let ours = InstanceRecord::current("ours".to_string(), secret.clone());
+        registry.register(InstanceRecord {
+            pid: DEAD_PID,
+            ..InstanceRecord::current("crashed".to_string(), secret)
+        })?;
+        registry.register(ours.clone())?;
+        assert_eq!(registry.live_instances()?, vec![ours.clone()]);
+        assert_eq!(registry.find(ours.pid)?, Some(ours.clone()));
+        assert!(!std::fs::read_to_string(registry.path())?.contains("crashed"));
+        // Only ourselves are running, and we do not forward to ourselves
+        assert_eq!(registry.newest_other()?, None);
+
+        registry.unregister(ours.pid)?;
+        assert_eq!(registry.live_instances()?, Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn changes_wait_for_the_lock() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let registry = InstanceRegistry::new(dir.path().join("instances.json"));
+        let ours = InstanceRecord::current("ours".to_string(), SessionSecret::from_bytes([7; 32]));
+        let lock = registry.lock()?;
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let writer = {
+            let registry = registry.clone();
+            let ours = ours.clone();
+            std::thread::spawn(move || {
+                registry.register(ours).unwrap();
+                done_tx.send(()).unwrap();
+            })
+        };
+        assert!(done_rx.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(lock);
+        done_rx.recv_timeout(Duration::from_secs(5))?;
+        writer.join().unwrap();
+        assert_eq!(registry.live_instances()?, vec![ours]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secrets_are_only_readable_by_their_user() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let registry =
+            InstanceRegistry::new(dir.path().join("youre-muted-btw").join("instances.json"));
+        registry.register(InstanceRecord::current(
+            "ours".to_string(),
+            SessionSecret::from_bytes([7; 32]),
+        ))?;
+        let mode = |path: &Path| -> eyre::Result<u32> {
+            Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+        };
+        assert_eq!(mode(registry.path())?, 0o600);
+        assert_eq!(mode(registry.path().parent().unwrap())?, 0o700);
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use crate::TrayMuteState;
+use crate::TrayboundIPCMessage;
+
+/// Everything the GUI has told the tray, rebuilt from [`TrayboundIPCMessage`]s.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayStatus {
+    pub mute_state: TrayMuteState,
+    pub is_speaking: bool,
+    pub mic: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Which icon the tray should show for a [`TrayStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayIconKind {
+    Normal,
+    Muted,
+    Error,
+}
+
+impl TrayStatus {
+    pub fn apply(&mut self, message: TrayboundIPCMessage) {
+        match message {
+            TrayboundIPCMessage::MuteState(mute_state) => self.mute_state = mute_state,
+            TrayboundIPCMessage::VoiceActivity { is_speaking } => self.is_speaking = is_speaking,
+            TrayboundIPCMessage::CurrentMic(mic) => self.mic = mic,
+            TrayboundIPCMessage::Error(error) => self.error = error,
+            TrayboundIPCMessage::Status(status) => {
+                self.mute_state = status.mute_state;
+                self.is_speaking = status.is_speaking;
+                self.mic = status.mic;
+                self.error = status.error;
+            }
+            TrayboundIPCMessage::Metrics(_) => {}
+        }
+    }
+
+    /// The messages that turn a default status into this one, for clients that just connected.
+    pub fn messages(&self) -> Vec<TrayboundIPCMessage> {
+        vec![
+            TrayboundIPCMessage::MuteState(self.mute_state),
+            TrayboundIPCMessage::VoiceActivity {
+                is_speaking: self.is_speaking,
+            },
+            TrayboundIPCMessage::CurrentMic(self.mic.clone()),
+            TrayboundIPCMessage::Error(self.error.clone()),
+        ]
+    }
+
+    /// The messages that turn `self` into `newer`.
+    pub fn diff(&self, newer: &TrayStatus) -> Vec<TrayboundIPCMessage> {
+        let mut messages = Vec::new();
+        if self.mute_state != newer.mute_state {
+            messages.push(TrayboundIPCMessage::MuteState(newer.mute_state));
+        }
+        if self.is_speaking != newer.is_speaking {
+            messages.push(TrayboundIPCMessage::VoiceActivity {
+                is_speaking: newer.is_speaking,
+            });
+        }
+        if self.mic != newer.mic {
+            messages.push(TrayboundIPCMessage::CurrentMic(newer.mic.clone()));
+        }
+        if self.error != newer.error {
+            messages.push(TrayboundIPCMessage::Error(newer.error.clone()));
+        }
+        messages
+    }
+
+    pub fn icon(&self) -> TrayIconKind {
+        if self.error.is_some() {
+            TrayIconKind::Error
+        } else if self.mute_state == TrayMuteState::Muted {
+            TrayIconKind::Muted
+        } else {
+            TrayIconKind::Normal
+        }
+    }
+
+    /// The greyed out first line of the tray menu.
+    pub fn menu_label(&self) -> String {
+        let state = match self.mute_state {
+            TrayMuteState::Muted => "Muted",
+            TrayMuteState::NotMuted => "Not muted",
+            TrayMuteState::Unknown => "Looking for the mute button",
+        };
+        if self.is_speaking {
+            format!("Currently: {state}, speaking")
+        } else {
+            format!("Currently: {state}")
+        }
+    }
+
+    pub fn tooltip(&self) -> String {
+        let mut lines = vec!["You're Muted Btw".to_string(), self.menu_label()];
+        if let Some(mic) = &self.mic {
+            lines.push(format!("Mic: {mic}"));
+        }
+        if let Some(error) = &self.error {
+            lines.push(format!("Error: {error}"));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TrayIconKind;
+    use crate::TrayMuteState;
+    use crate::TrayStatus;
+    use crate::TrayboundIPCMessage;
+
+    #[test]
+    fn messages_rebuild_the_status() {
+        let status = TrayStatus {
+            mute_state: TrayMuteState::Muted,
+            is_speaking: true,
+            mic: Some("Headset Microphone".to_string()),
+            error: None,
+        };
+        let mut rebuilt = TrayStatus::default();
+        for message in status.messages() {
+            rebuilt.apply(message);
+        }
+        assert_eq!(rebuilt, status);
+    }
+
+    #[test]
+    fn diff_only_contains_changes() {
+        let before = TrayStatus::default();
+        let mut after = before.clone();
+        after.mute_state = TrayMuteState::Muted;
+        after.error = Some("Microphone unplugged".to_string());
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                TrayboundIPCMessage::MuteState(TrayMuteState::Muted),
+                TrayboundIPCMessage::Error(Some("Microphone unplugged".to_string())),
+            ]
+        );
+        assert_eq!(after.diff(&after), vec![]);
+    }
+
+    #[test]
+    fn errors_take_over_the_icon() {
+        let mut status = TrayStatus::default();
+        assert_eq!(status.icon(), TrayIconKind::Normal);
+        status.apply(TrayboundIPCMessage::MuteState(TrayMuteState::Muted));
+        assert_eq!(status.icon(), TrayIconKind::Muted);
+        assert_eq!(status.menu_label(), "Currently: Muted");
+        status.apply(TrayboundIPCMessage::Error(Some(
+            "No microphone".to_string(),
+        )));
+        assert_eq!(status.icon(), TrayIconKind::Error);
+        assert!(status.tooltip().ends_with("Error: No microphone"));
+        status.apply(TrayboundIPCMessage::Error(None));
+        assert_eq!(status.icon(), TrayIconKind::Muted);
+    }
+}
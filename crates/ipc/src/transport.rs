@@ -0,0 +1,208 @@
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::sync::mpsc;
+
+/// A connected, bidirectional byte stream between two processes.
+pub trait IpcTransport: Send + 'static {
+    type Reader: Read + Send + 'static;
+    type Writer: Write + Send + 'static;
+
+    /// Separates the halves so one thread can block on reads while others write.
+    fn split(self) -> std::io::Result<(Self::Reader, Self::Writer)>;
+}
+
+#[cfg(unix)]
+impl IpcTransport for std::os::unix::net::UnixStream {
+    type Reader = std::os::unix::net::UnixStream;
+    type Writer = std::os::unix::net::UnixStream;
+
+    fn split(self) -> std::io::Result<(Self::Reader, Self::Writer)> {
+        Ok((self.try_clone()?, self))
+    }
+}
+
+#[cfg(windows)]
+pub use named_pipe::PipeReader;
+#[cfg(windows)]
+pub use named_pipe::PipeWriter;
+
+#[cfg(windows)]
+mod named_pipe {
+    use crate::IpcStream;
+    use crate::IpcTransport;
+    use std::io::Read;
+    use std::io::Write;
+    use std::os::windows::io::AsHandle;
+    use std::os::windows::io::AsRawHandle;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::MutexGuard;
+    use std::time::Duration;
+    use windows::Win32::Foundation::ERROR_BROKEN_PIPE;
+    use windows::Win32::Foundation::ERROR_PIPE_NOT_CONNECTED;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Pipes::PeekNamedPipe;
+
+    /// How long the reader waits before checking for more bytes.
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    // A pipe handle runs one operation at a time, a read blocked waiting for the peer would stall every write.
+    // Both halves share the stream and the reader only reads bytes that have already arrived.
+    impl IpcTransport for IpcStream {
+        type Reader = PipeReader;
+        type Writer = PipeWriter;
+
+        fn split(self) -> std::io::Result<(Self::Reader, Self::Writer)> {
+            let stream = Arc::new(Mutex::new(self));
+            Ok((
+                PipeReader {
+                    stream: stream.clone(),
+                },
+                PipeWriter { stream },
+            ))
+        }
+    }
+
+    fn lock(stream: &Mutex<IpcStream>) -> MutexGuard<'_, IpcStream> {
+        stream.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub struct PipeReader {
+        stream: Arc<Mutex<IpcStream>>,
+    }
+    impl Read for PipeReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            loop {
+                {
+                    let mut stream = lock(&self.stream);
+                    let mut available = 0;
+                    let handle = HANDLE(stream.as_handle().as_raw_handle());
+                    if let Err(e) =
+                        unsafe { PeekNamedPipe(handle, None, 0, None, Some(&mut available), None) }
+                    {
+                        let code = e.code();
+                        if code == ERROR_BROKEN_PIPE.to_hresult()
+                            || code == ERROR_PIPE_NOT_CONNECTED.to_hresult()
+                        {
+                            return Ok(0);
+                        }
+                        return Err(e.into());
+                    }
+                    if available > 0 {
+                        let len = buf.len().min(available as usize);
+                        return stream.read(&mut buf[..len]);
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+
+    pub struct PipeWriter {
+        stream: Arc<Mutex<IpcStream>>,
+    }
+    impl Write for PipeWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            lock(&self.stream).write(buf)
+        }
+        /// Flushing a pipe waits for the peer to read everything, which must not happen while holding the stream.
+        ///
+        /// Written bytes are already in the pipe, and dropping the stream flushes it.
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+/// One end of an in-process duplex stream, for exercising the protocol without a real pipe.
+pub struct MemoryTransport {
+    reader: MemoryReader,
+    writer: MemoryWriter,
+}
+
+/// Returns two connected ends, bytes written to one are read from the other.
+pub fn memory_duplex() -> (MemoryTransport, MemoryTransport) {
+    let (a_tx, a_rx) = mpsc::channel();
+    let (b_tx, b_rx) = mpsc::channel();
+    (
+        MemoryTransport {
+            reader: MemoryReader::new(b_rx),
+            writer: MemoryWriter { tx: a_tx },
+        },
+        MemoryTransport {
+            reader: MemoryReader::new(a_rx),
+            writer: MemoryWriter { tx: b_tx },
+        },
+    )
+}
+
+impl IpcTransport for MemoryTransport {
+    type Reader = MemoryReader;
+    type Writer = MemoryWriter;
+
+    fn split(self) -> std::io::Result<(Self::Reader, Self::Writer)> {
+        Ok((self.reader, self.writer))
+    }
+}
+impl Read for MemoryTransport {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+impl Write for MemoryTransport {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+pub struct MemoryReader {
+    rx: mpsc::Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    position: usize,
+}
+impl MemoryReader {
+    fn new(rx: mpsc::Receiver<Vec<u8>>) -> Self {
+        Self {
+            rx,
+            pending: Vec::new(),
+            position: 0,
+        }
+    }
+}
+impl Read for MemoryReader {
+    /// Blocks until the other end writes, reading nothing once it has been dropped.
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.pending.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.pending = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let read = buf.len().min(self.pending.len() - self.position);
+        buf[..read].copy_from_slice(&self.pending[self.position..self.position + read]);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+pub struct MemoryWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+}
+impl Write for MemoryWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.tx
+            .send(buf.to_vec())
+            .map_err(|_| std::io::Error::from(ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
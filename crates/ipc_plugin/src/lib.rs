@@ -1,17 +1,24 @@
 use bevy::prelude::*;
-use interprocess::os::windows::named_pipe::PipeListener;
-use interprocess::os::windows::named_pipe::PipeListenerOptions;
-use interprocess::os::windows::named_pipe::pipe_mode;
-use std::io::Read;
-use std::io::{self};
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
+use ymb_ipc::ClientId;
+use ymb_ipc::Frame;
+use ymb_ipc::FrameError;
+use ymb_ipc::IpcClients;
+use ymb_ipc::IpcListener;
+use ymb_ipc::IpcMessageHandler;
+use ymb_ipc::IpcRequester;
+use ymb_ipc::SessionSecret;
+use ymb_ipc::serve_connection;
 use ymb_worker_plugin::Sender;
-use ymb_worker_plugin::WorkerConfig;
+use ymb_worker_plugin::WorkerCancellation;
+use ymb_worker_plugin::WorkerMetrics;
 use ymb_worker_plugin::WorkerPlugin;
-use serde::{Deserialize, Serialize};
+
+pub use ymb_ipc::BevyboundIPCMessage;
+pub use ymb_ipc::TrayboundIPCMessage;
 
 pub struct IpcPlugin;
 
@@ -19,21 +26,16 @@ impl Plugin for IpcPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<IpcWorkerThreadboundMessage>();
         app.register_type::<IpcWorkerGameboundMessage>();
-        app.add_plugins(WorkerPlugin::<
-            IpcWorkerThreadboundMessage,
-            IpcWorkerGameboundMessage,
-            IpcWorkerState,
-        > {
-            config: WorkerConfig {
-                name: "IpcWorker".to_string(),
-                is_ui_automation_thread: false,
-                handle_threadbound_message,
-                threadbound_message_receiver: |thread_rx, _state| {
-                    thread_rx.recv().map_err(bevy::prelude::BevyError::from)
-                },
-                ..default()
-            },
-        });
+        app.init_resource::<TrayClients>();
+        app.add_plugins(
+            WorkerPlugin::<
+                IpcWorkerThreadboundMessage,
+                IpcWorkerGameboundMessage,
+                IpcWorkerState,
+            >::builder("IpcWorker")
+            .on_message(handle_threadbound_message)
+            .build(),
+        );
         app.add_systems(Startup, setup_ipc_and_worker);
         app.add_systems(Update, handle_gamebound_messages);
     }
@@ -41,24 +43,35 @@ impl Plugin for IpcPlugin {
 
 #[derive(Debug, Clone, Reflect, Event)]
 pub enum IpcWorkerThreadboundMessage {
-    InitAndListen,
+    /// Accepts connections until the pipe fails, registering each client with the given [`TrayClients`].
+    InitAndListen(TrayClients),
 }
 
-#[derive(Debug, Clone, Reflect, Event, Serialize, Deserialize)]
-pub enum BevyboundIPCMessage {
-    TrayIconClicked,
-    ShowWorldInspector,
-    DebugMessageReceived(String),
-}
+/// Every connected tray, for pushing [`TrayboundIPCMessage`]s to.
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(opaque)]
+#[reflect(Resource)]
+pub struct TrayClients(pub IpcClients<TrayboundIPCMessage>);
 
 #[derive(Debug, Clone, Reflect, Event)]
 pub enum IpcWorkerGameboundMessage {
     MessageReceived(BevyboundIPCMessage),
+    /// A message the client waits on an answer to, see [`BevyboundIPCMessage::is_request`].
+    ///
+    /// Answer with [`IpcClients::reply`] so other clients do not see it.
+    RequestReceived {
+        from: IpcRequester,
+        request: BevyboundIPCMessage,
+    },
+    /// A client connected and is now part of [`TrayClients`], it knows nothing about the GUI yet.
+    ClientConnected,
+    /// A client from a different build connected, its messages cannot be understood.
+    ProtocolMismatch { ours: u16, theirs: u16 },
 }
 
 #[derive(Default)]
 pub struct IpcWorkerState {
-    listener: Option<PipeListener<pipe_mode::Bytes, pipe_mode::Bytes>>,
+    listener: Option<IpcListener>,
 }
 
 fn handle_threadbound_message(
@@ -68,51 +81,41 @@ fn handle_threadbound_message(
 ) -> Result<(), bevy::prelude::BevyError> {
     info!("IpcWorker: Received threadbound message: {:?}", msg);
     match msg {
-        IpcWorkerThreadboundMessage::InitAndListen => {
+        IpcWorkerThreadboundMessage::InitAndListen(clients) => {
             if state.listener.is_some() {
                 warn!("IpcWorker: Listener already initialized. Ignoring InitAndListen message.");
                 return Ok(());
             }
             let pipe_name = std::env::var("YMB_IPC_PIPE_NAME").map_err(bevy::prelude::BevyError::from)?;
+            // Frames not tagged with the tray's secret are rejected, so other processes that find the pipe cannot drive us
+            let secret = SessionSecret::from_env().map_err(bevy::prelude::BevyError::from)?;
             info!("IpcWorker: Initializing listener for pipe: {}", pipe_name);
-            let listener = PipeListenerOptions::new()
-                .path(pipe_name.clone())
-                .create_duplex::<pipe_mode::Bytes>()
-                .map_err(bevy::prelude::BevyError::from)?;
+            let listener = IpcListener::bind(&pipe_name).map_err(bevy::prelude::BevyError::from)?;
             state.listener = Some(listener);
             info!("IpcWorker: Starting listener accept loop.");
+            let cancellation = WorkerCancellation::current().unwrap_or_default();
+            // Exiting cancels the worker, connecting to ourselves wakes the accept loop so it notices
+            let wake_pipe_name = pipe_name.clone();
+            cancellation.on_cancel(move || _ = ymb_ipc::connect(&wake_pipe_name));
             let listener_ref = state.listener.as_ref().unwrap();
             for incoming_conn in listener_ref.incoming() {
+                if cancellation.is_cancelled() {
+                    info!("IpcWorker: Shutting down, no longer accepting connections.");
+                    break;
+                }
                 match incoming_conn {
-                    Ok(mut stream) => {
+                    Ok(stream) => {
                         debug!("IpcWorker: Accepted new connection.");
-                        let mut buffer = Vec::new();
-                        match stream.read_to_end(&mut buffer) {
-                            Ok(_) => {
-                                let msg: Result<BevyboundIPCMessage, _> = bincode::deserialize(&buffer);
-                                match msg {
-                                    Ok(BevyboundIPCMessage::TrayIconClicked) => {
-                                        reply_tx.send(IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::TrayIconClicked))?;
-                                    }
-                                    Ok(BevyboundIPCMessage::DebugMessageReceived(text)) => {
-                                        reply_tx.send(IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::DebugMessageReceived(text)))?;
-                                    }
-                                    Ok(BevyboundIPCMessage::ShowWorldInspector) => {
-                                        reply_tx.send(IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::ShowWorldInspector))?;
-                                    }
-                                    Err(e) => {
-                                        error!("IpcWorker: Failed to deserialize IPC message: {}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("IpcWorker: Failed to read from stream: {}", e);
-                                if e.kind() == io::ErrorKind::BrokenPipe {
-                                    continue;
-                                }
-                                return Err(bevy::prelude::BevyError::from(e));
-                            }
-                        }
+                        // Clients keep their connection open, so serve each one on its own thread to keep accepting
+                        let clients = clients.0.clone();
+                        let mut handler = ForwardToGame {
+                            tx: reply_tx.clone(),
+                            client: None,
+                        };
+                        let secret = secret.clone();
+                        std::thread::Builder::new()
+                            .name("IpcConnection".to_string())
+                            .spawn(move || serve_connection(stream, &secret, &clients, &mut handler))?;
                     }
                     Err(e) => {
                         error!("IpcWorker: Failed to accept connection: {}", e);
@@ -126,13 +129,63 @@ fn handle_threadbound_message(
     Ok(())
 }
 
-fn setup_ipc_and_worker(mut ipc_worker_events: EventWriter<IpcWorkerThreadboundMessage>) {
+/// Forwards every frame on a connection to the game until the client hangs up or stops making sense.
+struct ForwardToGame {
+    tx: Sender<IpcWorkerGameboundMessage>,
+    /// Set once the client can be answered.
+    client: Option<ClientId>,
+}
+
+impl IpcMessageHandler<BevyboundIPCMessage> for ForwardToGame {
+    fn connected(&mut self, client: ClientId) {
+        self.client = Some(client);
+        let _ = self.tx.send(IpcWorkerGameboundMessage::ClientConnected);
+    }
+
+    fn handle(&mut self, frame: Frame<BevyboundIPCMessage>) {
+        debug!(
+            "IpcWorker: Received request {}: {:?}",
+            frame.request_id, frame.message
+        );
+        let message = match self.client {
+            Some(client) if frame.message.is_request() => IpcWorkerGameboundMessage::RequestReceived {
+                from: IpcRequester {
+                    client,
+                    request_id: frame.request_id,
+                },
+                request: frame.message,
+            },
+            _ => IpcWorkerGameboundMessage::MessageReceived(frame.message),
+        };
+        let _ = self.tx.send(message);
+    }
+
+    fn disconnected(&mut self, reason: &FrameError) {
+        match reason {
+            FrameError::Closed => debug!("IpcWorker: Connection closed."),
+            FrameError::VersionMismatch { ours, theirs } => {
+                let _ = self.tx.send(IpcWorkerGameboundMessage::ProtocolMismatch {
+                    ours: *ours,
+                    theirs: *theirs,
+                });
+            }
+            e => error!("IpcWorker: Dropping connection: {}", e),
+        }
+    }
+}
+
+fn setup_ipc_and_worker(
+    mut ipc_worker_events: EventWriter<IpcWorkerThreadboundMessage>,
+    clients: Res<TrayClients>,
+) {
     // Do not read the pipe name here; let the worker thread read the env var
-    ipc_worker_events.write(IpcWorkerThreadboundMessage::InitAndListen);
+    ipc_worker_events.write(IpcWorkerThreadboundMessage::InitAndListen(clients.clone()));
 }
 
 fn handle_gamebound_messages(
     mut messages: EventReader<IpcWorkerGameboundMessage>,
+    clients: Res<TrayClients>,
+    metrics: Res<WorkerMetrics>,
 ) {
     for msg in messages.read() {
         match msg {
@@ -147,6 +200,53 @@ fn handle_gamebound_messages(
                 info!("Received ShowWorldInspector IPC message (no-op in ipc_plugin)");
                 // This plugin does not handle world inspection logic.
             }
+            IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::ShowSettings) => {
+                info!("Received ShowSettings IPC message (no-op in ipc_plugin)");
+                // The settings window plugin opens the window.
+            }
+            IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::SelectMic(name)) => {
+                info!("Received SelectMic({:?}) IPC message (no-op in ipc_plugin)", name);
+                // The settings window plugin edits the settings.
+            }
+            IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::SimulateMuteToggle) => {
+                info!("Received SimulateMuteToggle IPC message (no-op in ipc_plugin)");
+                // The UI automation plugin owns the mute button state.
+            }
+            IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::PauseAlerts { duration_ms }) => {
+                info!("Received PauseAlerts for {}ms IPC message (no-op in ipc_plugin)", duration_ms);
+                // The alert plugin pauses itself.
+            }
+            IpcWorkerGameboundMessage::MessageReceived(
+                request @ (BevyboundIPCMessage::RequestStatus | BevyboundIPCMessage::RequestMetrics),
+            ) => {
+                warn!("Received {:?} without knowing who asked, not answering", request);
+            }
+            IpcWorkerGameboundMessage::RequestReceived {
+                request: BevyboundIPCMessage::RequestStatus,
+                ..
+            } => {
+                debug!("Received RequestStatus IPC message (no-op in ipc_plugin)");
+                // The tray status plugin answers.
+            }
+            IpcWorkerGameboundMessage::RequestReceived {
+                from,
+                request: BevyboundIPCMessage::RequestMetrics,
+            } => {
+                debug!("Answering RequestMetrics with {} workers", metrics.workers.len());
+                clients.0.reply(from, TrayboundIPCMessage::Metrics(metrics.clone()));
+            }
+            IpcWorkerGameboundMessage::RequestReceived { request, .. } => {
+                debug!("Received {:?} IPC request (no-op in ipc_plugin)", request);
+            }
+            IpcWorkerGameboundMessage::ClientConnected => {
+                debug!("An IPC client connected, {} connected in total", clients.0.len());
+            }
+            IpcWorkerGameboundMessage::ProtocolMismatch { ours, theirs } => {
+                error!(
+                    "An IPC client speaks protocol version {} but this GUI speaks version {}, restart the app so both match",
+                    theirs, ours
+                );
+            }
         }
     }
 }
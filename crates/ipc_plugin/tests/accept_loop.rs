@@ -0,0 +1,130 @@
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::*;
+use std::time::Duration;
+use std::time::Instant;
+use ymb_ipc::FrameWriter;
+use ymb_ipc::IpcStream;
+use ymb_ipc::IpcTransport;
+use ymb_ipc::SECRET_ENV_VAR;
+use ymb_ipc::SessionSecret;
+use ymb_ipc::connect;
+use ymb_ipc::endpoint_path;
+use ymb_ipc::read_frame;
+use ymb_ipc::write_frame;
+use ymb_ipc_plugin::BevyboundIPCMessage;
+use ymb_ipc_plugin::IpcPlugin;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_ipc_plugin::TrayClients;
+use ymb_ipc_plugin::TrayboundIPCMessage;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The worker binds on its own thread, so the first attempts may find nothing listening.
+fn connect_when_listening(path: &str) -> eyre::Result<IpcStream> {
+    let started = Instant::now();
+    loop {
+        match connect(path) {
+            Ok(stream) => return Ok(stream),
+            Err(e) if started.elapsed() > TIMEOUT => return Err(e.into()),
+            Err(_) => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+/// Updates the app until `count` gamebound messages have arrived.
+fn receive(
+    app: &mut App,
+    cursor: &mut EventCursor<IpcWorkerGameboundMessage>,
+    count: usize,
+) -> Vec<IpcWorkerGameboundMessage> {
+    let started = Instant::now();
+    let mut received = Vec::new();
+    while received.len() < count && started.elapsed() < TIMEOUT {
+        app.update();
+        let events = app.world().resource::<Events<IpcWorkerGameboundMessage>>();
+        received.extend(cursor.read(events).cloned());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    received
+}
+
+#[test]
+fn clients_talk_to_the_game_both_ways() -> eyre::Result<()> {
+    let path = endpoint_path(&format!("ymb-ipc-plugin-test-{}", std::process::id()));
+    let secret = SessionSecret::generate()?;
+    // SAFETY: this is the only test in the binary, so nothing reads the environment meanwhile
+    unsafe {
+        std::env::set_var("YMB_IPC_PIPE_NAME", &path);
+        std::env::set_var(SECRET_ENV_VAR, secret.to_hex());
+    }
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.add_plugins(IpcPlugin);
+    let mut cursor = EventCursor::default();
+    // Startup queues InitAndListen and the first update hands it to the worker
+    app.update();
+
+    let (mut reader, mut writer) = connect_when_listening(&path)?.split()?;
+    // Someone who found the pipe but not the secret is ignored
+    write_frame(
+        &mut writer,
+        &SessionSecret::generate()?,
+        1,
+        &BevyboundIPCMessage::TrayIconClicked,
+    )?;
+    let mut writer = FrameWriter::new(writer, secret.clone());
+    writer.send(&BevyboundIPCMessage::ShowSettings)?;
+    writer.send(&BevyboundIPCMessage::DebugMessageReceived(
+        "hello".to_string(),
+    ))?;
+    let received = receive(&mut app, &mut cursor, 3);
+    assert!(
+        matches!(
+            received.as_slice(),
+            [
+                IpcWorkerGameboundMessage::ClientConnected,
+                IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::ShowSettings),
+                IpcWorkerGameboundMessage::MessageReceived(
+                    BevyboundIPCMessage::DebugMessageReceived(text)
+                ),
+            ] if text == "hello"
+        ),
+        "{received:?}"
+    );
+
+    let clients = app.world().resource::<TrayClients>();
+    assert_eq!(
+        clients.0.broadcast(&TrayboundIPCMessage::CurrentMic(Some(
+            "Headset".to_string()
+        ))),
+        1
+    );
+    assert_eq!(
+        read_frame::<TrayboundIPCMessage>(&mut reader, &secret)?.message,
+        TrayboundIPCMessage::CurrentMic(Some("Headset".to_string()))
+    );
+
+    // Requests are answered to the client that asked, echoing its request id
+    let request_id = writer.send(&BevyboundIPCMessage::RequestMetrics)?;
+    let received = receive(&mut app, &mut cursor, 1);
+    assert!(
+        matches!(
+            received.as_slice(),
+            [IpcWorkerGameboundMessage::RequestReceived {
+                from,
+                request: BevyboundIPCMessage::RequestMetrics,
+            }] if from.request_id == request_id
+        ),
+        "{received:?}"
+    );
+    // The message may have arrived after this frame's systems ran
+    app.update();
+    let reply = read_frame::<TrayboundIPCMessage>(&mut reader, &secret)?;
+    assert_eq!(reply.request_id, request_id);
+    assert!(matches!(reply.message, TrayboundIPCMessage::Metrics(_)));
+    // Exiting wakes the accept loop, and the listener removes its socket file as the worker stops
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+    assert!(!std::path::Path::new(&path).exists());
+    Ok(())
+}
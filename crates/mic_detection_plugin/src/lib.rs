@@ -11,8 +11,10 @@ use bevy::reflect::Reflect;
 use image::DynamicImage;
 use mic_list::enumerate_mics_win;
 use mic_list::MicInfo;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsSection;
 use ymb_worker_plugin::Sender;
-use ymb_worker_plugin::WorkerConfig;
 use ymb_worker_plugin::WorkerPlugin;
 
 #[derive(Debug, Clone, Event, Reflect)]
@@ -33,6 +35,10 @@ pub struct Mic {
     pub icon_handle: Option<Handle<Image>>,
 }
 
+/// Marks the [`Mic`] that voice activity is captured from, according to [`Settings`].
+#[derive(Component, Debug, Clone)]
+pub struct SelectedMic;
+
 #[derive(Default)]
 pub struct MicDetectionState;
 
@@ -43,19 +49,19 @@ impl Plugin for MicDetectionPlugin {
         app.register_type::<MicDetectionThreadboundMessage>();
         app.register_type::<MicDetectionGameboundMessage>();
         app.register_type::<MicInfo>();
-        app.add_plugins(WorkerPlugin {
-            config: WorkerConfig::<
+        app.add_plugins(
+            WorkerPlugin::<
                 MicDetectionThreadboundMessage,
                 MicDetectionGameboundMessage,
                 MicDetectionState,
-            > {
-                name: "MicDetectionWorker".to_string(),
-                handle_threadbound_message,
-                ..default()
-            },
-        });
+            >::builder("MicDetectionWorker")
+            .on_message(handle_threadbound_message)
+            .build(),
+        );
         app.add_systems(Startup, trigger_enumerate_mics);
         app.add_systems(Update, handle_mics_enumerated);
+        app.add_systems(Update, enumerate_mics_on_settings_changed);
+        app.add_systems(Update, mark_selected_mic.after(handle_mics_enumerated));
     }
 }
 
@@ -63,6 +69,49 @@ fn trigger_enumerate_mics(mut writer: EventWriter<MicDetectionThreadboundMessage
     writer.write(MicDetectionThreadboundMessage::EnumerateMics);
 }
 
+/// A newly chosen mic may have been plugged in since the last enumeration.
+///
+/// Other voice activity changes, like the sensitivity, leave the mics alone.
+fn enumerate_mics_on_settings_changed(
+    mut events: EventReader<SettingsChanged>,
+    settings: Res<Settings>,
+    mut enumerated_for: Local<Option<Option<String>>>,
+    mut writer: EventWriter<MicDetectionThreadboundMessage>,
+) {
+    let mic = &settings.voice_activity.mic;
+    // Startup enumerated for the mic the settings were loaded with
+    let enumerated_for = enumerated_for.get_or_insert_with(|| mic.clone());
+    let voice_activity_changed = events
+        .read()
+        .any(|event| event.section == SettingsSection::VoiceActivity);
+    if voice_activity_changed && enumerated_for != mic {
+        *enumerated_for = mic.clone();
+        writer.write(MicDetectionThreadboundMessage::EnumerateMics);
+    }
+}
+
+fn mark_selected_mic(
+    settings: Res<Settings>,
+    mics: Query<(Entity, &Mic, Has<SelectedMic>)>,
+    changed: Query<(), Changed<Mic>>,
+    mut commands: Commands,
+) {
+    if !settings.is_changed() && changed.is_empty() {
+        return;
+    }
+    for (entity, mic, was_selected) in mics.iter() {
+        let is_selected = match &settings.voice_activity.mic {
+            Some(name) => *name == mic.name,
+            None => mic.is_default,
+        };
+        if is_selected && !was_selected {
+            commands.entity(entity).insert(SelectedMic);
+        } else if !is_selected && was_selected {
+            commands.entity(entity).remove::<SelectedMic>();
+        }
+    }
+}
+
 fn handle_threadbound_message(
     msg: &MicDetectionThreadboundMessage,
     reply_tx: &Sender<MicDetectionGameboundMessage>,
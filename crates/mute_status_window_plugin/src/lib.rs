@@ -4,9 +4,17 @@ use bevy::window::WindowResolution;
 use bevy_inspector_egui::bevy_egui::EguiContext;
 use bevy_inspector_egui::bevy_egui::EguiMultipassSchedule;
 use bevy_inspector_egui::egui;
+use std::time::Duration;
+use ymb_alert_plugin::AlertSound;
+use ymb_alert_plugin::AlertSoundImportResult;
+use ymb_alert_plugin::FlashMuteStatusWindow;
+use ymb_alert_plugin::ImportAlertSound;
 use ymb_assets::Texture;
 use ymb_ipc_plugin::BevyboundIPCMessage;
 use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsSection;
 use ymb_ui_automation::MuteButtonState;
 use ymb_window_icon_plugin::WindowIcon;
 
@@ -22,16 +30,33 @@ pub struct YMBMuteStatusWindowPlugin;
 impl Plugin for YMBMuteStatusWindowPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<MuteStatusWindowEvent>();
+        app.add_event::<FlashMuteStatusWindow>();
+        app.add_event::<ImportAlertSound>();
+        app.add_event::<AlertSoundImportResult>();
+        app.init_resource::<MuteStatusWindowFlash>();
+        app.init_resource::<MuteStatusWindowBanner>();
+        app.register_type::<MuteStatusWindowFlash>();
+        app.register_type::<MuteStatusWindowBanner>();
         app.add_systems(Startup, fire_spawn_window_event);
         app.add_systems(Update, handle_ipc_toggle_window_event);
         app.add_systems(Update, handle_spawn_window_event);
         app.add_systems(Update, handle_despawn_window_event);
         app.add_systems(Update, handle_toggle_window_event);
+        app.add_systems(Update, handle_flash_event);
+        app.add_systems(Update, resize_on_settings_changed);
+        app.add_systems(Update, handle_dropped_files);
+        app.add_systems(Update, handle_sound_import_results);
         app.add_systems(MuteStatusWindowEguiContextPass, ui);
     }
 }
 
-fn fire_spawn_window_event(mut events: EventWriter<MuteStatusWindowEvent>) {
+fn fire_spawn_window_event(
+    mut events: EventWriter<MuteStatusWindowEvent>,
+    settings: Res<Settings>,
+) {
+    if !settings.mute_status_window.show_on_startup {
+        return;
+    }
     events.write(MuteStatusWindowEvent::SpawnWindow);
 }
 
@@ -54,13 +79,42 @@ pub struct MuteStatusWindow;
 #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MuteStatusWindowEguiContextPass;
 
-const DEFAULT_SIZE: (f32, f32) = (320., 100.);
+const FLASH_PERIOD: Duration = Duration::from_millis(250);
+const BANNER_DURATION: Duration = Duration::from_secs(6);
+
+/// Time at which the current flash stops, from [`Time::elapsed`].
+#[derive(Debug, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MuteStatusWindowFlash {
+    pub until: Option<Duration>,
+}
+
+/// A line of feedback shown under the mute status, such as why a dropped file was rejected.
+#[derive(Debug, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MuteStatusWindowBanner {
+    pub text: String,
+    pub is_error: bool,
+    /// Time at which the banner is hidden, from [`Time::elapsed`].
+    pub until: Option<Duration>,
+}
+impl MuteStatusWindowBanner {
+    pub fn show(&mut self, text: impl Into<String>, is_error: bool, now: Duration) {
+        self.text = text.into();
+        self.is_error = is_error;
+        self.until = Some(now + BANNER_DURATION);
+    }
+    pub fn is_visible(&self, now: Duration) -> bool {
+        self.until.is_some_and(|until| now < until)
+    }
+}
 
 fn handle_spawn_window_event(
     mut events: EventReader<MuteStatusWindowEvent>,
     mut commands: Commands,
     query: Query<Entity, With<MuteStatusWindow>>,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
 ) {
     for event in events.read() {
         if let MuteStatusWindowEvent::SpawnWindow = event {
@@ -68,7 +122,10 @@ fn handle_spawn_window_event(
                 commands.spawn((
                     Window {
                         title: "Mute Status".to_string(),
-                        resolution: WindowResolution::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1),
+                        resolution: WindowResolution::new(
+                            settings.mute_status_window.width,
+                            settings.mute_status_window.height,
+                        ),
                         ..default()
                     },
                     MuteStatusWindow,
@@ -103,6 +160,7 @@ fn handle_toggle_window_event(
     mut events: EventReader<MuteStatusWindowEvent>,
     mut commands: Commands,
     query: Query<Entity, With<MuteStatusWindow>>,
+    settings: Res<Settings>,
 ) {
     for event in events.read() {
         if let MuteStatusWindowEvent::ToggleWindow = event {
@@ -113,7 +171,10 @@ fn handle_toggle_window_event(
                 commands.spawn((
                     Window {
                         title: "Mute Status".to_string(),
-                        resolution: WindowResolution::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1),
+                        resolution: WindowResolution::new(
+                            settings.mute_status_window.width,
+                            settings.mute_status_window.height,
+                        ),
                         ..default()
                     },
                     MuteStatusWindow,
@@ -127,6 +188,96 @@ fn handle_toggle_window_event(
     }
 }
 
+fn resize_on_settings_changed(
+    mut events: EventReader<SettingsChanged>,
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<MuteStatusWindow>>,
+) {
+    if !events
+        .read()
+        .any(|event| event.section == SettingsSection::MuteStatusWindow)
+    {
+        return;
+    }
+    for mut window in windows.iter_mut() {
+        window.resolution.set(
+            settings.mute_status_window.width,
+            settings.mute_status_window.height,
+        );
+    }
+}
+
+fn handle_flash_event(
+    mut events: EventReader<FlashMuteStatusWindow>,
+    mut flash: ResMut<MuteStatusWindowFlash>,
+    time: Res<Time>,
+) {
+    for event in events.read() {
+        let until = time.elapsed() + event.duration;
+        flash.until = Some(flash.until.map_or(until, |current| current.max(until)));
+    }
+}
+
+fn handle_dropped_files(
+    mut drops: EventReader<FileDragAndDrop>,
+    windows: Query<(), With<MuteStatusWindow>>,
+    mut imports: EventWriter<ImportAlertSound>,
+    mut banner: ResMut<MuteStatusWindowBanner>,
+    time: Res<Time>,
+) {
+    for drop in drops.read() {
+        match drop {
+            FileDragAndDrop::DroppedFile { window, path_buf } if windows.contains(*window) => {
+                info!("File dropped on Mute Status window: {}", path_buf.display());
+                imports.write(ImportAlertSound {
+                    path: path_buf.clone(),
+                });
+            }
+            FileDragAndDrop::HoveredFile { window, .. } if windows.contains(*window) => {
+                banner.show(
+                    "Drop an .ogg, .wav or .mp3 to use it as the alert sound",
+                    false,
+                    time.elapsed(),
+                );
+            }
+            FileDragAndDrop::HoveredFileCanceled { window } if windows.contains(*window) => {
+                banner.until = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_sound_import_results(
+    mut results: EventReader<AlertSoundImportResult>,
+    mut banner: ResMut<MuteStatusWindowBanner>,
+    time: Res<Time>,
+) {
+    for result in results.read() {
+        match result {
+            AlertSoundImportResult::Imported {
+                sound: AlertSound::File(path),
+            } => {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                banner.show(format!("Alert sound set to {name}"), false, time.elapsed());
+            }
+            AlertSoundImportResult::Imported { sound } => {
+                banner.show(
+                    format!("Alert sound set to {sound:?}"),
+                    false,
+                    time.elapsed(),
+                );
+            }
+            AlertSoundImportResult::Failed { error, .. } => {
+                banner.show(error.clone(), true, time.elapsed());
+            }
+        }
+    }
+}
+
 fn ui(world: &mut World) -> Result {
     // Query for the window entity and get its size
     let (window_height, _window_width) = {
@@ -134,11 +285,17 @@ fn ui(world: &mut World) -> Result {
         if let Some(window) = window_query.iter(world).next() {
             (window.resolution.height(), window.resolution.width())
         } else {
-            (DEFAULT_SIZE.1, DEFAULT_SIZE.0)
+            let settings = &world.resource::<Settings>().mute_status_window;
+            (settings.height, settings.width)
         }
     };
-    // Set font size proportional to window height (e.g., 40% of height)
-    let font_size = window_height * 0.4;
+    let now = world.resource::<Time>().elapsed();
+    let banner = world.resource::<MuteStatusWindowBanner>();
+    let banner = banner
+        .is_visible(now)
+        .then(|| (banner.text.clone(), banner.is_error));
+    // Set font size proportional to window height (e.g., 40% of height), leaving room for the banner
+    let font_size = window_height * if banner.is_some() { 0.25 } else { 0.4 };
     let mut ctx = world
         .query_filtered::<&mut EguiContext, With<MuteStatusWindow>>()
         .single_mut(world)?
@@ -149,11 +306,32 @@ fn ui(world: &mut World) -> Result {
         .next()
         .cloned()
         .unwrap_or(MuteButtonState::NotMuted);
-    egui::CentralPanel::default().show(ctx.get_mut(), |ui| {
-        let (text, color) = match mute_state {
-            MuteButtonState::Muted => ("You are muted btw.", egui::Color32::RED),
-            _ => ("You are not muted.", egui::Color32::GREEN),
-        };
+    // Swap the text colour into the background every other period while flashing
+    let is_flash_on = world
+        .resource::<MuteStatusWindowFlash>()
+        .until
+        .is_some_and(|until| now < until)
+        && (now.as_millis() / FLASH_PERIOD.as_millis()).is_multiple_of(2);
+    let (text, mut color) = match mute_state {
+        MuteButtonState::Muted => ("You are muted btw.", egui::Color32::RED),
+        _ => ("You are not muted.", egui::Color32::GREEN),
+    };
+    if let Some((banner_text, is_error)) = banner {
+        egui::TopBottomPanel::bottom("mute_status_banner").show(ctx.get_mut(), |ui| {
+            let banner_color = if is_error {
+                egui::Color32::LIGHT_RED
+            } else {
+                ui.visuals().text_color()
+            };
+            ui.colored_label(banner_color, banner_text);
+        });
+    }
+    let mut panel = egui::CentralPanel::default();
+    if is_flash_on {
+        panel = panel.frame(egui::Frame::central_panel(&ctx.get_mut().style()).fill(color));
+        color = egui::Color32::WHITE;
+    }
+    panel.show(ctx.get_mut(), |ui| {
         let style = ui.style_mut();
         style.text_styles.insert(
             egui::TextStyle::Heading,
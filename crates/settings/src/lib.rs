@@ -0,0 +1,91 @@
+mod migrate;
+mod settings;
+mod storage;
+mod watch;
+
+pub use migrate::*;
+pub use settings::*;
+pub use storage::*;
+pub use watch::*;
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+/// Loads [`Settings`] while the app is being built, so other plugins can read it from their systems.
+///
+/// Edits to the file while running are reloaded and announced with [`SettingsChanged`].
+/// Add it before any plugin that reads [`Settings`].
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let path = settings_path();
+        let settings = match &path {
+            Some(path) => {
+                let (settings, outcome) = Settings::load_or_reset(path);
+                match &outcome {
+                    LoadOutcome::Loaded | LoadOutcome::Created => {
+                        info!("Using settings from {}", path.display());
+                    }
+                    LoadOutcome::Migrated { from } => {
+                        info!(
+                            "Migrated settings in {} from version {} to {}",
+                            path.display(),
+                            from,
+                            CURRENT_VERSION
+                        );
+                    }
+                    LoadOutcome::Reset { backup, error } => {
+                        error!(
+                            "Settings in {} were corrupt and have been reset, the old file was moved to {}: {}",
+                            path.display(),
+                            backup.display(),
+                            error
+                        );
+                    }
+                    LoadOutcome::Unreadable { error } => {
+                        error!(
+                            "Failed to read settings from {}, using defaults: {}",
+                            path.display(),
+                            error
+                        );
+                    }
+                }
+                settings
+            }
+            None => {
+                warn!("No config directory found, settings will not be saved");
+                Settings::default()
+            }
+        };
+        if let Some(path) = &path {
+            match SettingsWatcher::new(path) {
+                Ok(watcher) => {
+                    app.insert_resource(watcher);
+                }
+                Err(e) => {
+                    warn!("Settings will not be reloaded when edited: {:?}", e);
+                }
+            }
+        }
+        app.insert_resource(settings);
+        app.insert_resource(SettingsPath(path));
+        app.add_event::<SettingsChanged>();
+        app.add_systems(
+            Update,
+            reload_settings_on_change.run_if(resource_exists::<SettingsWatcher>),
+        );
+        app.add_systems(
+            Update,
+            warn_about_restart_only_settings.after(reload_settings_on_change),
+        );
+        app.register_type::<Settings>();
+        app.register_type::<SettingsPath>();
+        app.register_type::<SettingsChanged>();
+    }
+}
+
+/// Where [`Settings`] was loaded from and should be saved to.
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct SettingsPath(pub Option<PathBuf>);
@@ -0,0 +1,69 @@
+use eyre::bail;
+use toml::Table;
+use toml::Value;
+
+/// The schema version written by this build.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Upgrades the table from `version` to `version + 1`, indexed by `version`.
+const MIGRATIONS: [fn(&mut Table); CURRENT_VERSION as usize] = [
+    // Files written before the version field existed have the same layout as version 1
+    |_| {},
+];
+
+/// Reads the `version` field, treating files without one as version 0.
+pub fn file_version(table: &Table) -> eyre::Result<u32> {
+    match table.get("version") {
+        None => Ok(0),
+        Some(Value::Integer(version)) if *version >= 0 => Ok(*version as u32),
+        Some(other) => bail!("Settings version must be a non-negative integer, got {other}"),
+    }
+}
+
+/// Applies every migration between the file's version and [`CURRENT_VERSION`].
+///
+/// Returns the version the table was at before migrating.
+/// Files from a newer build are left alone, unknown fields are ignored when reading
+/// and [`crate::Settings::save`] refuses to write over them, so they are not lost.
+pub fn migrate(table: &mut Table) -> eyre::Result<u32> {
+    let from = file_version(table)?;
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(table);
+        table.insert("version".to_string(), Value::Integer(version as i64 + 1));
+    }
+    Ok(from)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::CURRENT_VERSION;
+    use crate::migrate;
+    use toml::Table;
+
+    #[test]
+    fn unversioned_file_is_upgraded() -> eyre::Result<()> {
+        let mut table: Table = toml::from_str("[ui_automation]\nrefresh_interval_ms = 500\n")?;
+        assert_eq!(migrate(&mut table)?, 0);
+        assert_eq!(table["version"].as_integer(), Some(CURRENT_VERSION as i64));
+        assert_eq!(
+            table["ui_automation"]["refresh_interval_ms"].as_integer(),
+            Some(500)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn newer_file_is_left_alone() -> eyre::Result<()> {
+        let mut table: Table = toml::from_str("version = 99\n")?;
+        assert_eq!(migrate(&mut table)?, 99);
+        assert_eq!(table["version"].as_integer(), Some(99));
+        Ok(())
+    }
+
+    #[test]
+    fn bad_version_is_an_error() -> eyre::Result<()> {
+        let mut table: Table = toml::from_str("version = \"one\"\n")?;
+        assert!(migrate(&mut table).is_err());
+        Ok(())
+    }
+}
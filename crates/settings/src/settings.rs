@@ -0,0 +1,223 @@
+use crate::CURRENT_VERSION;
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use ymb_alert::AlertConfig;
+use ymb_ui_automation::AppDefinition;
+use ymb_voice_activity::VadConfig;
+
+/// Everything the user can tune, as stored in the settings file.
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version of the file, see [`crate::migrate`].
+    pub version: u32,
+    pub ui_automation: UIAutomationSettings,
+    pub mute_status_window: MuteStatusWindowSettings,
+    /// Channel capacities keyed by worker name, for workers that should not use their built-in sizes.
+    pub workers: BTreeMap<String, WorkerChannelSettings>,
+    pub voice_activity: VoiceActivitySettings,
+    pub detection: DetectionSettings,
+    pub alerts: AlertConfig,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            ui_automation: default(),
+            mute_status_window: default(),
+            workers: default(),
+            voice_activity: default(),
+            detection: default(),
+            alerts: default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UIAutomationSettings {
+    /// How often the mute button is looked up and read.
+    pub refresh_interval_ms: u64,
+    /// The apps whose mute buttons are read, the most trusted reading wins and ties go to the earlier app.
+    #[serde(deserialize_with = "deserialize_apps")]
+    pub apps: Vec<AppDefinition>,
+}
+impl Default for UIAutomationSettings {
+    fn default() -> Self {
+        Self {
+            refresh_interval_ms: 2_000,
+            apps: vec![AppDefinition::discord()],
+        }
+    }
+}
+impl UIAutomationSettings {
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.refresh_interval_ms)
+    }
+}
+
+/// Reads each app on its own, so a typo in a hand written definition, or a field from a newer build,
+/// skips that app instead of failing the whole file.
+fn deserialize_apps<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<AppDefinition>, D::Error> {
+    let apps = Vec::<toml::Value>::deserialize(deserializer)?;
+    Ok(apps
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, app)| match AppDefinition::deserialize(app) {
+            Ok(app) => Some(app),
+            Err(e) => {
+                warn!("Skipping app definition {} in the settings: {}", index, e);
+                None
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MuteStatusWindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub show_on_startup: bool,
+}
+impl Default for MuteStatusWindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 320.,
+            height: 100.,
+            show_on_startup: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerChannelSettings {
+    pub gamebound_channel_capacity: Option<usize>,
+    pub threadbound_channel_capacity: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceActivitySettings {
+    /// Friendly name of the input device, or the default device when unset.
+    pub mic: Option<String>,
+    pub vad: VadConfig,
+}
+
+/// When speech while muted counts as talking while muted.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionSettings {
+    pub min_speech_ms: u64,
+    pub grace_after_mute_ms: u64,
+    pub cooldown_ms: u64,
+    pub ignore_when_push_to_talk: bool,
+}
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        Self {
+            min_speech_ms: 750,
+            grace_after_mute_ms: 1_500,
+            cooldown_ms: 10_000,
+            ignore_when_push_to_talk: true,
+        }
+    }
+}
+
+/// A top level table of [`Settings`], so plugins only react to the parts they use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum SettingsSection {
+    UIAutomation,
+    MuteStatusWindow,
+    Workers,
+    VoiceActivity,
+    Detection,
+    Alerts,
+}
+
+impl Settings {
+    /// The sections that differ between `self` and `other`, in declaration order.
+    pub fn changed_sections(&self, other: &Settings) -> Vec<SettingsSection> {
+        let mut changed = Vec::new();
+        if self.ui_automation != other.ui_automation {
+            changed.push(SettingsSection::UIAutomation);
+        }
+        if self.mute_status_window != other.mute_status_window {
+            changed.push(SettingsSection::MuteStatusWindow);
+        }
+        if self.workers != other.workers {
+            changed.push(SettingsSection::Workers);
+        }
+        if self.voice_activity != other.voice_activity {
+            changed.push(SettingsSection::VoiceActivity);
+        }
+        if self.detection != other.detection {
+            changed.push(SettingsSection::Detection);
+        }
+        if self.alerts != other.alerts {
+            changed.push(SettingsSection::Alerts);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Settings;
+    use crate::SettingsSection;
+
+    #[test]
+    fn identical_settings_have_no_changes() {
+        assert_eq!(
+            Settings::default().changed_sections(&Settings::default()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn bad_app_definitions_are_skipped() -> eyre::Result<()> {
+        let (settings, _) = Settings::from_toml(
+            "version = 1\n\
+             [[ui_automation.apps]]\n\
+             app = \"Typo\"\n\
+             mute_buton = {}\n\
+             [[ui_automation.apps]]\n\
+             app = \"Teams\"\n\
+             mute_button = { control_type = \"Button\" }\n",
+        )?;
+        let apps: Vec<_> = settings
+            .ui_automation
+            .apps
+            .iter()
+            .map(|app| app.app.as_str())
+            .collect();
+        assert_eq!(apps, ["Teams"]);
+        Ok(())
+    }
+
+    #[test]
+    fn each_changed_section_is_reported_once() {
+        let before = Settings::default();
+        let mut after = before.clone();
+        after.ui_automation.refresh_interval_ms = 250;
+        after.voice_activity.mic = Some("Headset Microphone".to_string());
+        after.voice_activity.vad.hangover_ms += 1;
+        after.alerts.actions[0].enabled = false;
+        assert_eq!(
+            before.changed_sections(&after),
+            vec![
+                SettingsSection::UIAutomation,
+                SettingsSection::VoiceActivity,
+                SettingsSection::Alerts,
+            ]
+        );
+    }
+}
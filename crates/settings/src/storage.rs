@@ -0,0 +1,306 @@
+use crate::CURRENT_VERSION;
+use crate::Settings;
+use crate::file_version;
+use crate::migrate;
+use eyre::Context;
+use eyre::bail;
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use toml::Table;
+
+/// Overrides the settings file location, mostly for tests and portable installs.
+pub const SETTINGS_PATH_ENV: &str = "YMB_SETTINGS_PATH";
+
+pub fn settings_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(SETTINGS_PATH_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    dirs::config_dir().map(|dir| dir.join("youre-muted-btw").join("settings.toml"))
+}
+
+/// What [`Settings::load_or_reset`] had to do to produce usable settings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadOutcome {
+    Loaded,
+    Migrated {
+        from: u32,
+    },
+    /// There was no file, so one was written with the defaults.
+    Created,
+    /// The file could not be parsed, it was moved to `backup` and replaced with the defaults.
+    Reset {
+        backup: PathBuf,
+        error: String,
+    },
+    /// The file exists but could not be read, or could not be backed up after failing to parse.
+    /// Defaults are used without touching it.
+    Unreadable {
+        error: String,
+    },
+}
+
+impl Settings {
+    /// Parses a settings file, migrating it to [`CURRENT_VERSION`].
+    ///
+    /// Returns the version the text was written as.
+    pub fn from_toml(text: &str) -> eyre::Result<(Self, u32)> {
+        let mut table: Table = toml::from_str(text)?;
+        let from = migrate(&mut table)?;
+        let settings = Settings::deserialize(toml::Value::Table(table))?;
+        Ok((settings, from))
+    }
+
+    pub fn to_toml(&self) -> eyre::Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Writes to a sibling file first so a crash mid-write cannot leave a truncated file behind.
+    ///
+    /// Refuses to replace a file written by a newer build, which would lose the fields only that build knows.
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(version) = newer_file_version(path) {
+            bail!(
+                "Not saving over {}, it was written by a newer build (version {} > {})",
+                path.display(),
+                version,
+                CURRENT_VERSION
+            );
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let temp = path.with_extension("toml.tmp");
+        std::fs::write(&temp, self.to_toml()?)
+            .wrap_err_with(|| format!("Failed to write {}", temp.display()))?;
+        std::fs::rename(&temp, path)
+            .wrap_err_with(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Loads the settings file, falling back to the defaults rather than failing.
+    ///
+    /// Missing, migrated and corrupt files are rewritten so the file on disk always matches what is in use.
+    pub fn load_or_reset(path: &Path) -> (Self, LoadOutcome) {
+        Self::load_or_reset_to(path, &backup_path(path))
+    }
+
+    fn load_or_reset_to(path: &Path, backup: &Path) -> (Self, LoadOutcome) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let settings = Settings::default();
+                save_or_warn(&settings, path);
+                return (settings, LoadOutcome::Created);
+            }
+            Err(e) => {
+                return (
+                    Settings::default(),
+                    LoadOutcome::Unreadable {
+                        error: format!("{e}"),
+                    },
+                );
+            }
+        };
+        match Settings::from_toml(&text) {
+            Ok((settings, from)) if from < CURRENT_VERSION => {
+                save_or_warn(&settings, path);
+                (settings, LoadOutcome::Migrated { from })
+            }
+            Ok((settings, _)) => (settings, LoadOutcome::Loaded),
+            Err(e) => {
+                // Without a backup the file is all that is left of the user's settings, so it stays
+                if let Err(rename_error) = std::fs::rename(path, backup) {
+                    return (
+                        Settings::default(),
+                        LoadOutcome::Unreadable {
+                            error: format!(
+                                "{e}, and failed to back it up to {}: {rename_error}",
+                                backup.display()
+                            ),
+                        },
+                    );
+                }
+                let settings = Settings::default();
+                save_or_warn(&settings, path);
+                (
+                    settings,
+                    LoadOutcome::Reset {
+                        backup: backup.to_path_buf(),
+                        error: format!("{e}"),
+                    },
+                )
+            }
+        }
+    }
+}
+
+fn save_or_warn(settings: &Settings, path: &Path) {
+    if let Err(e) = settings.save(path) {
+        bevy::log::warn!("Failed to save settings to {}: {:?}", path.display(), e);
+    }
+}
+
+/// The version of the file at `path` when it is newer than this build, unreadable files count as not newer.
+fn newer_file_version(path: &Path) -> Option<u32> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let table: Table = toml::from_str(&text).ok()?;
+    file_version(&table)
+        .ok()
+        .filter(|version| *version > CURRENT_VERSION)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{timestamp}"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::CURRENT_VERSION;
+    use crate::LoadOutcome;
+    use crate::Settings;
+    use crate::WorkerChannelSettings;
+    use ymb_alert::AlertAction;
+    use ymb_alert::AlertSound;
+
+    #[test]
+    fn missing_file_is_created_with_defaults() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("nested").join("settings.toml");
+        let (settings, outcome) = Settings::load_or_reset(&path);
+        assert_eq!(outcome, LoadOutcome::Created);
+        assert_eq!(settings, Settings::default());
+        assert_eq!(
+            Settings::load_or_reset(&path),
+            (settings, LoadOutcome::Loaded)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn defaults_round_trip() -> eyre::Result<()> {
+        let mut settings = Settings::default();
+        settings.voice_activity.mic = Some("Headset Microphone".to_string());
+        settings.workers.insert(
+            "ElementInfoPluginWorker".to_string(),
+            WorkerChannelSettings {
+                gamebound_channel_capacity: Some(32),
+                threadbound_channel_capacity: None,
+            },
+        );
+        settings.alerts.actions[0].action = AlertAction::PlaySound {
+            sound: AlertSound::File("C:\\sounds\\beep.ogg".into()),
+        };
+        let text = settings.to_toml()?;
+        let (parsed, from) = Settings::from_toml(&text)?;
+        assert_eq!(from, CURRENT_VERSION);
+        assert_eq!(parsed, settings);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() -> eyre::Result<()> {
+        let (settings, _) = Settings::from_toml(
+            "version = 1\n\
+             [mute_status_window]\n\
+             width = 500.0\n\
+             [voice_activity.vad]\n\
+             hangover_ms = 50\n",
+        )?;
+        assert_eq!(settings.mute_status_window.width, 500.0);
+        assert_eq!(
+            settings.mute_status_window.height,
+            Settings::default().mute_status_window.height
+        );
+        assert_eq!(settings.voice_activity.vad.hangover_ms, 50);
+        assert_eq!(
+            settings.voice_activity.vad.attack_ms,
+            Settings::default().voice_activity.vad.attack_ms
+        );
+        assert_eq!(settings.alerts, Settings::default().alerts);
+        Ok(())
+    }
+
+    #[test]
+    fn unversioned_file_is_migrated_and_rewritten() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        std::fs::write(&path, "[ui_automation]\nrefresh_interval_ms = 250\n")?;
+        let (settings, outcome) = Settings::load_or_reset(&path);
+        assert_eq!(outcome, LoadOutcome::Migrated { from: 0 });
+        assert_eq!(settings.ui_automation.refresh_interval_ms, 250);
+        let (_, outcome) = Settings::load_or_reset(&path);
+        assert_eq!(outcome, LoadOutcome::Loaded);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_and_reset() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        std::fs::write(&path, "version = 1\n[ui_automation\n")?;
+        let (settings, outcome) = Settings::load_or_reset(&path);
+        assert_eq!(settings, Settings::default());
+        let LoadOutcome::Reset { backup, .. } = outcome else {
+            panic!("Expected a reset, got {outcome:?}");
+        };
+        assert_eq!(
+            std::fs::read_to_string(&backup)?,
+            "version = 1\n[ui_automation\n"
+        );
+        assert_eq!(Settings::load_or_reset(&path).1, LoadOutcome::Loaded);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_file_is_kept_when_the_backup_fails() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        std::fs::write(&path, "version = 1\n[ui_automation\n")?;
+        let backup = dir.path().join("missing").join("settings.toml.corrupt");
+        let (settings, outcome) = Settings::load_or_reset_to(&path, &backup);
+        assert_eq!(settings, Settings::default());
+        assert!(
+            matches!(outcome, LoadOutcome::Unreadable { .. }),
+            "{outcome:?}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "version = 1\n[ui_automation\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn newer_files_are_not_saved_over() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        let newer = "version = 99\n[future_section]\nenabled = true\n";
+        std::fs::write(&path, newer)?;
+        let (settings, outcome) = Settings::load_or_reset(&path);
+        assert_eq!(outcome, LoadOutcome::Loaded);
+        assert!(settings.save(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path)?, newer);
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_types_are_treated_as_corrupt() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        std::fs::write(&path, "[ui_automation]\nrefresh_interval_ms = \"fast\"\n")?;
+        let (_, outcome) = Settings::load_or_reset(&path);
+        assert!(matches!(outcome, LoadOutcome::Reset { .. }), "{outcome:?}");
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::Settings;
+use crate::SettingsPath;
+use crate::SettingsSection;
+use bevy::prelude::*;
+use crossbeam_channel::Receiver;
+use eyre::Context;
+use eyre::OptionExt;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+use std::path::Path;
+
+/// Sent once per section after the settings file was edited and reloaded.
+///
+/// The [`Settings`] resource already holds the new values when this is read.
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct SettingsChanged {
+    pub section: SettingsSection,
+}
+
+/// Watches the settings file for edits made while the app is running.
+#[derive(Resource)]
+pub struct SettingsWatcher {
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+}
+impl SettingsWatcher {
+    /// Watches the directory holding `path` rather than the file itself,
+    /// since editors and [`Settings::save`] replace the file instead of writing to it.
+    pub fn new(path: &Path) -> eyre::Result<Self> {
+        let dir = path
+            .parent()
+            .ok_or_eyre("Settings path has no parent directory")?;
+        let file_name = path
+            .file_name()
+            .ok_or_eyre("Settings path has no file name")?
+            .to_os_string();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Settings watcher error: {:?}", e);
+                        return;
+                    }
+                };
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+                if event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == Some(file_name.as_os_str()))
+                {
+                    let _ = tx.send(());
+                }
+            })?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .wrap_err_with(|| format!("Failed to watch {}", dir.display()))?;
+        Ok(Self {
+            _watcher: watcher,
+            changes: rx,
+        })
+    }
+
+    /// Drains pending notifications, returning whether there were any.
+    pub fn take_changes(&self) -> bool {
+        self.changes.try_iter().count() > 0
+    }
+}
+
+/// Unlike startup, a file that fails to parse is left alone so a half-finished edit does not wipe the user's settings.
+pub(crate) fn reload_settings_on_change(
+    watcher: Res<SettingsWatcher>,
+    path: Res<SettingsPath>,
+    mut settings: ResMut<Settings>,
+    mut events: EventWriter<SettingsChanged>,
+) {
+    if !watcher.take_changes() {
+        return;
+    }
+    let Some(path) = &path.0 else {
+        return;
+    };
+    let reloaded = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))
+        .and_then(|text| Settings::from_toml(&text));
+    let reloaded = match reloaded {
+        Ok((reloaded, _)) => reloaded,
+        Err(e) => {
+            warn!("Keeping current settings, failed to reload: {:?}", e);
+            return;
+        }
+    };
+    let changed = settings.changed_sections(&reloaded);
+    if changed.is_empty() {
+        return;
+    }
+    info!(
+        "Reloaded settings from {}, changed {:?}",
+        path.display(),
+        changed
+    );
+    *settings = reloaded;
+    for section in changed {
+        events.write(SettingsChanged { section });
+    }
+}
+
+pub(crate) fn warn_about_restart_only_settings(mut events: EventReader<SettingsChanged>) {
+    for event in events.read() {
+        if event.section == SettingsSection::Workers {
+            warn!("Worker channel capacities only take effect after a restart");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Settings;
+    use crate::SettingsChanged;
+    use crate::SettingsPath;
+    use crate::SettingsSection;
+    use crate::SettingsWatcher;
+    use crate::watch::reload_settings_on_change;
+    use bevy::prelude::*;
+    use std::path::Path;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Resource, Default)]
+    struct Received(Vec<SettingsSection>);
+
+    fn collect(mut events: EventReader<SettingsChanged>, mut received: ResMut<Received>) {
+        received.0.extend(events.read().map(|event| event.section));
+    }
+
+    fn app(path: &Path) -> eyre::Result<App> {
+        let settings = Settings::default();
+        settings.save(path)?;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<SettingsChanged>();
+        app.init_resource::<Received>();
+        app.insert_resource(settings);
+        app.insert_resource(SettingsPath(Some(path.to_path_buf())));
+        app.insert_resource(SettingsWatcher::new(path)?);
+        app.add_systems(Update, (reload_settings_on_change, collect).chain());
+        Ok(app)
+    }
+
+    /// Updates until something is received, since the watcher reports from its own thread.
+    fn wait_for_changes(app: &mut App) -> Vec<SettingsSection> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            app.update();
+            let received = std::mem::take(&mut app.world_mut().resource_mut::<Received>().0);
+            if !received.is_empty() {
+                return received;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        vec![]
+    }
+
+    #[test]
+    fn edits_are_reloaded_by_section() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        let mut app = app(&path)?;
+
+        let mut edited = Settings::default();
+        edited.ui_automation.refresh_interval_ms = 250;
+        edited.alerts.actions[0].enabled = false;
+        edited.save(&path)?;
+
+        assert_eq!(
+            wait_for_changes(&mut app),
+            vec![SettingsSection::UIAutomation, SettingsSection::Alerts]
+        );
+        assert_eq!(*app.world().resource::<Settings>(), edited);
+        Ok(())
+    }
+
+    #[test]
+    fn broken_edits_keep_current_settings() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.toml");
+        let mut app = app(&path)?;
+
+        std::fs::write(&path, "version = 1\n[ui_automation\n")?;
+        assert_eq!(wait_for_changes(&mut app), vec![]);
+        assert_eq!(*app.world().resource::<Settings>(), Settings::default());
+
+        // Fixing the file afterwards is still picked up
+        let mut edited = Settings::default();
+        edited.detection.cooldown_ms = 1;
+        edited.save(&path)?;
+        assert_eq!(wait_for_changes(&mut app), vec![SettingsSection::Detection]);
+        Ok(())
+    }
+}
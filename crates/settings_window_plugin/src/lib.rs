@@ -0,0 +1,264 @@
+mod sections;
+
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy::window::WindowResolution;
+use bevy_inspector_egui::bevy_egui::EguiContext;
+use bevy_inspector_egui::bevy_egui::EguiMultipassSchedule;
+use bevy_inspector_egui::egui;
+use std::time::Duration;
+use ymb_alert_plugin::AlertSoundLibrary;
+use ymb_alert_plugin::RaiseAlert;
+use ymb_ipc_plugin::BevyboundIPCMessage;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_mic_detection_plugin::Mic;
+use ymb_mic_detection_plugin::MicDetectionThreadboundMessage;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsPath;
+use ymb_settings::SettingsSection;
+use ymb_voice_activity_plugin::VoiceActivity;
+
+#[derive(Event, Debug, Clone)]
+pub enum SettingsWindowEvent {
+    SpawnWindow,
+    DespawnWindow,
+    ToggleWindow,
+}
+
+pub struct YMBSettingsWindowPlugin;
+
+impl Plugin for YMBSettingsWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SettingsWindowEvent>();
+        app.init_resource::<SettingsWindowEdits>();
+        app.register_type::<SettingsWindowEdits>();
+        app.add_systems(Update, handle_ipc_show_settings);
+        app.add_systems(Update, handle_ipc_select_mic);
+        app.add_systems(Update, handle_spawn_window_event);
+        app.add_systems(Update, handle_despawn_window_event);
+        app.add_systems(Update, handle_toggle_window_event);
+        app.add_systems(Update, save_edited_settings);
+        app.add_systems(SettingsWindowEguiContextPass, ui);
+    }
+}
+
+#[derive(Debug, Component, Reflect)]
+pub struct SettingsWindow;
+
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SettingsWindowEguiContextPass;
+
+const DEFAULT_SIZE: (f32, f32) = (420., 560.);
+
+/// How long the settings must stay untouched before they are written, so dragging a slider saves once.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Edits made in the settings window that have not been written to the settings file yet.
+#[derive(Debug, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct SettingsWindowEdits {
+    pub unsaved_since: Option<Duration>,
+}
+
+fn handle_ipc_show_settings(
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    mut events: EventWriter<SettingsWindowEvent>,
+) {
+    for msg in messages.read() {
+        if let IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::ShowSettings) = msg {
+            events.write(SettingsWindowEvent::SpawnWindow);
+        }
+    }
+}
+
+/// Switches the microphone the same way picking it in the window would, so the choice is saved.
+fn handle_ipc_select_mic(
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    mut settings: ResMut<Settings>,
+    mut edits: ResMut<SettingsWindowEdits>,
+    mut changed: EventWriter<SettingsChanged>,
+    time: Res<Time>,
+) {
+    for msg in messages.read() {
+        if let IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::SelectMic(name)) =
+            msg
+        {
+            if settings.voice_activity.mic.as_ref() == Some(name) {
+                continue;
+            }
+            info!("Selecting microphone {name:?} by request");
+            settings.voice_activity.mic = Some(name.clone());
+            changed.write(SettingsChanged {
+                section: SettingsSection::VoiceActivity,
+            });
+            edits.unsaved_since = Some(time.elapsed());
+        }
+    }
+}
+
+fn spawn_window(commands: &mut Commands) {
+    commands.spawn((
+        Window {
+            title: "Settings".to_string(),
+            resolution: WindowResolution::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1),
+            ..default()
+        },
+        SettingsWindow,
+        Name::new("Settings Window"),
+        EguiMultipassSchedule::new(SettingsWindowEguiContextPass),
+    ));
+}
+
+fn handle_spawn_window_event(
+    mut events: EventReader<SettingsWindowEvent>,
+    mut commands: Commands,
+    mut query: Query<&mut Window, With<SettingsWindow>>,
+) {
+    for event in events.read() {
+        if let SettingsWindowEvent::SpawnWindow = event {
+            if let Some(mut window) = query.iter_mut().next() {
+                window.focused = true;
+                info!("Settings window already exists, focusing it (event)");
+            } else {
+                spawn_window(&mut commands);
+                info!("Settings window spawned (event)");
+            }
+        }
+    }
+}
+
+fn handle_despawn_window_event(
+    mut events: EventReader<SettingsWindowEvent>,
+    mut commands: Commands,
+    query: Query<Entity, With<SettingsWindow>>,
+) {
+    for event in events.read() {
+        if let SettingsWindowEvent::DespawnWindow = event
+            && let Some(entity) = query.iter().next()
+        {
+            commands.entity(entity).despawn();
+            info!("Settings window despawned (event)");
+        }
+    }
+}
+
+fn handle_toggle_window_event(
+    mut events: EventReader<SettingsWindowEvent>,
+    mut commands: Commands,
+    query: Query<Entity, With<SettingsWindow>>,
+) {
+    for event in events.read() {
+        if let SettingsWindowEvent::ToggleWindow = event {
+            if let Some(entity) = query.iter().next() {
+                commands.entity(entity).despawn();
+                info!("Settings window despawned (toggle event)");
+            } else {
+                spawn_window(&mut commands);
+                info!("Settings window spawned (toggle event)");
+            }
+        }
+    }
+}
+
+fn save_edited_settings(
+    mut edits: ResMut<SettingsWindowEdits>,
+    settings: Res<Settings>,
+    path: Res<SettingsPath>,
+    time: Res<Time>,
+) {
+    let Some(unsaved_since) = edits.unsaved_since else {
+        return;
+    };
+    if time.elapsed() < unsaved_since + SAVE_DELAY {
+        return;
+    }
+    edits.unsaved_since = None;
+    let Some(path) = &path.0 else {
+        warn!("No settings file to save to, edits only last until exit");
+        return;
+    };
+    match settings.save(path) {
+        Ok(()) => info!("Saved settings to {}", path.display()),
+        Err(e) => warn!("Failed to save settings to {}: {:?}", path.display(), e),
+    }
+}
+
+/// Things the sections asked for that are not edits to [`Settings`].
+#[derive(Default)]
+struct SectionRequests {
+    refresh_mics: bool,
+    test_alert: bool,
+}
+
+fn ui(world: &mut World) -> Result {
+    let mut ctx = world
+        .query_filtered::<&mut EguiContext, With<SettingsWindow>>()
+        .single_mut(world)?
+        .clone();
+    let mut mics = world
+        .query::<&Mic>()
+        .iter(world)
+        .map(|mic| (mic.name.clone(), mic.is_default))
+        .collect::<Vec<_>>();
+    mics.sort();
+    let voice_activity = world
+        .query::<&VoiceActivity>()
+        .iter(world)
+        .next()
+        .cloned()
+        .unwrap_or_default();
+    let sounds = world.resource::<AlertSoundLibrary>().entries.clone();
+    // Edit a copy so the sections can be diffed against what is in use
+    let mut draft = world.resource::<Settings>().clone();
+    let mut requests = SectionRequests::default();
+    egui::CentralPanel::default().show(ctx.get_mut(), |ui| {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::CollapsingHeader::new("Microphone")
+                .default_open(true)
+                .show(ui, |ui| {
+                    sections::microphone(
+                        ui,
+                        &mut draft.voice_activity,
+                        &mics,
+                        &mut requests.refresh_mics,
+                    );
+                });
+            egui::CollapsingHeader::new("Sensitivity")
+                .default_open(true)
+                .show(ui, |ui| {
+                    sections::sensitivity(ui, &mut draft.voice_activity.vad, &voice_activity);
+                });
+            egui::CollapsingHeader::new("Alerts")
+                .default_open(true)
+                .show(ui, |ui| {
+                    sections::alerts(ui, &mut draft.alerts, &sounds, &mut requests.test_alert);
+                });
+            egui::CollapsingHeader::new("Detection").show(ui, |ui| {
+                sections::detection(ui, &mut draft.detection);
+            });
+            egui::CollapsingHeader::new("Startup").show(ui, |ui| {
+                sections::startup(ui, &mut draft);
+            });
+        });
+    });
+
+    let changed = world.resource::<Settings>().changed_sections(&draft);
+    if !changed.is_empty() {
+        *world.resource_mut::<Settings>() = draft;
+        for section in changed {
+            world.send_event(SettingsChanged { section });
+        }
+        let now = world.resource::<Time>().elapsed();
+        world.resource_mut::<SettingsWindowEdits>().unsaved_since = Some(now);
+    }
+    if requests.refresh_mics {
+        world.send_event(MicDetectionThreadboundMessage::EnumerateMics);
+    }
+    if requests.test_alert {
+        world.send_event(RaiseAlert {
+            reason: "Testing alerts from the settings window".to_string(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use bevy_inspector_egui::egui;
+use ymb_alert_plugin::AlertAction;
+use ymb_alert_plugin::AlertConfig;
+use ymb_alert_plugin::AlertSoundEntry;
+use ymb_settings::DetectionSettings;
+use ymb_settings::Settings;
+use ymb_settings::VoiceActivitySettings;
+use ymb_voice_activity::VadConfig;
+use ymb_voice_activity_plugin::VoiceActivity;
+
+/// The quietest level shown on the meter, anything below reads as empty.
+const METER_FLOOR_DBFS: f32 = -80.0;
+
+pub(crate) fn microphone(
+    ui: &mut egui::Ui,
+    settings: &mut VoiceActivitySettings,
+    mics: &[(String, bool)],
+    refresh_mics: &mut bool,
+) {
+    let selected_text = settings
+        .mic
+        .clone()
+        .unwrap_or_else(|| "System default".to_string());
+    egui::ComboBox::from_label("Input device")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut settings.mic, None, "System default");
+            for (name, is_default) in mics {
+                let label = if *is_default {
+                    format!("{name} (default)")
+                } else {
+                    name.clone()
+                };
+                ui.selectable_value(&mut settings.mic, Some(name.clone()), label);
+            }
+        });
+    if ui.button("Refresh devices").clicked() {
+        *refresh_mics = true;
+    }
+}
+
+fn meter_fraction(level_dbfs: f32) -> f32 {
+    ((level_dbfs - METER_FLOOR_DBFS) / -METER_FLOOR_DBFS).clamp(0.0, 1.0)
+}
+
+pub(crate) fn sensitivity(ui: &mut egui::Ui, vad: &mut VadConfig, voice_activity: &VoiceActivity) {
+    let mut sensitivity = vad.sensitivity();
+    if ui
+        .add(egui::Slider::new(&mut sensitivity, 0.0..=1.0).text("Sensitivity"))
+        .changed()
+    {
+        vad.set_sensitivity(sensitivity);
+    }
+
+    // Live level with the speech threshold marked, so the slider can be tuned while talking
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 16.), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2., ui.visuals().extreme_bg_color);
+    let mut level = rect;
+    level.set_width(rect.width() * meter_fraction(voice_activity.level_dbfs));
+    let level_color = if voice_activity.is_speaking {
+        egui::Color32::GREEN
+    } else {
+        egui::Color32::GRAY
+    };
+    painter.rect_filled(level, 2., level_color);
+    let threshold_x = rect.left() + rect.width() * meter_fraction(vad.energy_threshold_dbfs);
+    painter.vline(
+        threshold_x,
+        rect.y_range(),
+        egui::Stroke::new(2., egui::Color32::YELLOW),
+    );
+    let state = if voice_activity.is_paused {
+        "paused"
+    } else if voice_activity.is_speaking {
+        "speaking"
+    } else {
+        "quiet"
+    };
+    ui.label(format!(
+        "{:.0} dBFS, threshold {:.0} dBFS, {}",
+        voice_activity.level_dbfs, vad.energy_threshold_dbfs, state
+    ));
+
+    ui.horizontal(|ui| {
+        ui.label("Attack");
+        ui.add(
+            egui::DragValue::new(&mut vad.attack_ms)
+                .range(0..=2_000)
+                .suffix(" ms"),
+        );
+        ui.label("Hangover");
+        ui.add(
+            egui::DragValue::new(&mut vad.hangover_ms)
+                .range(0..=5_000)
+                .suffix(" ms"),
+        );
+    });
+}
+
+fn action_label(action: &AlertAction) -> &'static str {
+    match action {
+        AlertAction::PlaySound { .. } => "Play sound",
+        AlertAction::Speak { .. } => "Speak",
+        AlertAction::FlashMuteStatusWindow { .. } => "Flash mute status window",
+        AlertAction::Notify { .. } => "Notification",
+    }
+}
+
+pub(crate) fn alerts(
+    ui: &mut egui::Ui,
+    alerts: &mut AlertConfig,
+    sounds: &[AlertSoundEntry],
+    test_alert: &mut bool,
+) {
+    for (i, action) in alerts.actions.iter_mut().enumerate() {
+        ui.push_id(i, |ui| {
+            ui.checkbox(&mut action.enabled, action_label(&action.action));
+            ui.add_enabled_ui(action.enabled, |ui| {
+                match &mut action.action {
+                    AlertAction::PlaySound { sound } => {
+                        let selected_text = sounds
+                            .iter()
+                            .find(|entry| entry.sound == *sound)
+                            .map(|entry| entry.name.clone())
+                            .unwrap_or_else(|| format!("{sound:?}"));
+                        egui::ComboBox::from_label("Sound")
+                            .selected_text(selected_text)
+                            .show_ui(ui, |ui| {
+                                for entry in sounds {
+                                    ui.selectable_value(
+                                        sound,
+                                        entry.sound.clone(),
+                                        entry.name.as_str(),
+                                    );
+                                }
+                            });
+                        ui.add(egui::Slider::new(&mut action.volume, 0.0..=1.0).text("Volume"));
+                    }
+                    AlertAction::Speak { text } => {
+                        ui.text_edit_singleline(text);
+                    }
+                    AlertAction::FlashMuteStatusWindow { duration_ms } => {
+                        ui.horizontal(|ui| {
+                            ui.label("Duration");
+                            ui.add(
+                                egui::DragValue::new(duration_ms)
+                                    .range(100..=10_000)
+                                    .suffix(" ms"),
+                            );
+                        });
+                    }
+                    AlertAction::Notify { title, body } => {
+                        ui.text_edit_singleline(title);
+                        ui.text_edit_multiline(body);
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("At most once every");
+                    ui.add(
+                        egui::DragValue::new(&mut action.throttle_ms)
+                            .range(0..=600_000)
+                            .suffix(" ms"),
+                    );
+                });
+            });
+        });
+        ui.separator();
+    }
+    if ui.button("Test alert").clicked() {
+        *test_alert = true;
+    }
+}
+
+pub(crate) fn detection(ui: &mut egui::Ui, detection: &mut DetectionSettings) {
+    egui::Grid::new("detection").show(ui, |ui| {
+        ui.label("Minimum speech");
+        ui.add(
+            egui::DragValue::new(&mut detection.min_speech_ms)
+                .range(0..=10_000)
+                .suffix(" ms"),
+        );
+        ui.end_row();
+        ui.label("Grace after muting");
+        ui.add(
+            egui::DragValue::new(&mut detection.grace_after_mute_ms)
+                .range(0..=30_000)
+                .suffix(" ms"),
+        );
+        ui.end_row();
+        ui.label("Cooldown between alerts");
+        ui.add(
+            egui::DragValue::new(&mut detection.cooldown_ms)
+                .range(0..=600_000)
+                .suffix(" ms"),
+        );
+        ui.end_row();
+    });
+    ui.checkbox(
+        &mut detection.ignore_when_push_to_talk,
+        "Ignore while push to talk is held",
+    );
+}
+
+pub(crate) fn startup(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.checkbox(
+        &mut settings.mute_status_window.show_on_startup,
+        "Show the mute status window on startup",
+    );
+    egui::Grid::new("startup").show(ui, |ui| {
+        ui.label("Mute status window size");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut settings.mute_status_window.width).range(100.0..=2_000.0),
+            );
+            ui.label("x");
+            ui.add(
+                egui::DragValue::new(&mut settings.mute_status_window.height).range(50.0..=2_000.0),
+            );
+        });
+        ui.end_row();
+        ui.label("Mute button refresh interval");
+        ui.add(
+            egui::DragValue::new(&mut settings.ui_automation.refresh_interval_ms)
+                .range(100..=60_000)
+                .suffix(" ms"),
+        );
+        ui.end_row();
+    });
+}
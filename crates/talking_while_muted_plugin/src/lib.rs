@@ -0,0 +1,370 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use ymb_alert_plugin::RaiseAlert;
+use ymb_settings::DetectionSettings;
+use ymb_settings::Settings;
+use ymb_ui_automation::MuteButtonState;
+use ymb_voice_activity::VoiceActivityEvent;
+use ymb_voice_activity_plugin::VoiceActivityGameboundMessage;
+
+pub struct TalkingWhileMutedPlugin;
+
+impl Plugin for TalkingWhileMutedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<VoiceActivityGameboundMessage>();
+        app.add_event::<TalkingWhileMuted>();
+        app.add_event::<RaiseAlert>();
+        app.init_resource::<TalkingWhileMutedConfig>();
+        app.init_resource::<TalkingWhileMutedState>();
+        app.init_resource::<PushToTalk>();
+        app.register_type::<TalkingWhileMutedConfig>();
+        app.register_type::<TalkingWhileMutedState>();
+        app.register_type::<PushToTalk>();
+        app.register_type::<TalkingWhileMuted>();
+        app.add_systems(
+            Update,
+            apply_detection_settings.run_if(resource_exists_and_changed::<Settings>),
+        );
+        app.add_systems(
+            Update,
+            (track_mute_state, track_voice_activity, evaluate_rule).chain(),
+        );
+        app.add_systems(Update, log_talking_while_muted);
+        app.add_systems(Update, raise_alert);
+    }
+}
+
+/// Tuning for when speech while muted is worth an alert, kept in sync with [`Settings::detection`].
+#[derive(Debug, Clone, PartialEq, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TalkingWhileMutedConfig {
+    /// How long speech must continue while muted before alerting.
+    pub min_speech_ms: u64,
+    /// Speech is not counted until this long after muting, so finishing a sentence is fine.
+    pub grace_after_mute_ms: u64,
+    /// Minimum time between two alerts.
+    pub cooldown_ms: u64,
+    /// Skip alerts while [`PushToTalk::is_held`] is set.
+    pub ignore_when_push_to_talk: bool,
+}
+impl Default for TalkingWhileMutedConfig {
+    fn default() -> Self {
+        Self::from(&DetectionSettings::default())
+    }
+}
+impl TalkingWhileMutedConfig {
+    pub fn min_speech(&self) -> Duration {
+        Duration::from_millis(self.min_speech_ms)
+    }
+    pub fn grace_after_mute(&self) -> Duration {
+        Duration::from_millis(self.grace_after_mute_ms)
+    }
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.cooldown_ms)
+    }
+}
+
+impl From<&DetectionSettings> for TalkingWhileMutedConfig {
+    fn from(settings: &DetectionSettings) -> Self {
+        Self {
+            min_speech_ms: settings.min_speech_ms,
+            grace_after_mute_ms: settings.grace_after_mute_ms,
+            cooldown_ms: settings.cooldown_ms,
+            ignore_when_push_to_talk: settings.ignore_when_push_to_talk,
+        }
+    }
+}
+
+/// Whether the push-to-talk key is currently held, set by whatever observes it.
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct PushToTalk {
+    pub is_held: bool,
+}
+
+/// Inputs to the rule, timestamped with [`Time::elapsed`].
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TalkingWhileMutedState {
+    pub muted_since: Option<Duration>,
+    pub speech_started_at: Option<Duration>,
+    pub last_alert_at: Option<Duration>,
+    /// Set once an utterance has been alerted on so it only alerts once.
+    pub alerted_this_utterance: bool,
+}
+
+/// The user has been speaking while muted, all timestamps are [`Time::elapsed`].
+#[derive(Debug, Clone, Copy, PartialEq, Event, Reflect)]
+pub struct TalkingWhileMuted {
+    pub muted_since: Duration,
+    pub speech_started_at: Duration,
+    pub detected_at: Duration,
+    /// How long the user had been speaking when the alert fired.
+    pub speech_duration: Duration,
+}
+
+fn apply_detection_settings(settings: Res<Settings>, mut config: ResMut<TalkingWhileMutedConfig>) {
+    *config = TalkingWhileMutedConfig::from(&settings.detection);
+}
+
+fn track_mute_state(
+    time: Res<Time>,
+    mute_buttons: Query<&MuteButtonState>,
+    mut state: ResMut<TalkingWhileMutedState>,
+) {
+    let is_muted = mute_buttons
+        .iter()
+        .any(|mute_button| *mute_button == MuteButtonState::Muted);
+    match (is_muted, state.muted_since) {
+        (true, None) => state.muted_since = Some(time.elapsed()),
+        (false, Some(_)) => state.muted_since = None,
+        _ => {}
+    }
+}
+
+fn track_voice_activity(
+    time: Res<Time>,
+    mut messages: EventReader<VoiceActivityGameboundMessage>,
+    mut state: ResMut<TalkingWhileMutedState>,
+) {
+    for msg in messages.read() {
+        match msg {
+            VoiceActivityGameboundMessage::Activity(VoiceActivityEvent::VoiceActivityStarted {
+                ..
+            }) => {
+                state.speech_started_at = Some(time.elapsed());
+                state.alerted_this_utterance = false;
+            }
+            VoiceActivityGameboundMessage::Activity(VoiceActivityEvent::VoiceActivityEnded {
+                ..
+            })
+            | VoiceActivityGameboundMessage::SourceChanged { .. }
+            | VoiceActivityGameboundMessage::Paused
+            | VoiceActivityGameboundMessage::Error(_) => {
+                state.speech_started_at = None;
+            }
+            VoiceActivityGameboundMessage::Level { .. }
+            | VoiceActivityGameboundMessage::Resumed => {}
+        }
+    }
+}
+
+fn evaluate_rule(
+    time: Res<Time>,
+    config: Res<TalkingWhileMutedConfig>,
+    push_to_talk: Res<PushToTalk>,
+    mut state: ResMut<TalkingWhileMutedState>,
+    mut alerts: EventWriter<TalkingWhileMuted>,
+) {
+    let (Some(muted_since), Some(speech_started_at)) = (state.muted_since, state.speech_started_at)
+    else {
+        return;
+    };
+    if state.alerted_this_utterance {
+        return;
+    }
+    if config.ignore_when_push_to_talk && push_to_talk.is_held {
+        return;
+    }
+    let now = time.elapsed();
+    let counted_from = speech_started_at.max(muted_since + config.grace_after_mute());
+    if now < counted_from + config.min_speech() {
+        return;
+    }
+    if let Some(last_alert_at) = state.last_alert_at
+        && now < last_alert_at + config.cooldown()
+    {
+        return;
+    }
+    state.alerted_this_utterance = true;
+    state.last_alert_at = Some(now);
+    alerts.write(TalkingWhileMuted {
+        muted_since,
+        speech_started_at,
+        detected_at: now,
+        speech_duration: now - speech_started_at,
+    });
+}
+
+fn log_talking_while_muted(mut alerts: EventReader<TalkingWhileMuted>) {
+    for alert in alerts.read() {
+        info!(
+            "Talking while muted for {:?} (muted for {:?})",
+            alert.speech_duration,
+            alert.detected_at - alert.muted_since
+        );
+    }
+}
+
+fn raise_alert(mut alerts: EventReader<TalkingWhileMuted>, mut raise: EventWriter<RaiseAlert>) {
+    for alert in alerts.read() {
+        raise.write(RaiseAlert {
+            reason: format!("Talking while muted for {:?}", alert.speech_duration),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::PushToTalk;
+    use crate::TalkingWhileMuted;
+    use crate::TalkingWhileMutedConfig;
+    use crate::TalkingWhileMutedPlugin;
+    use bevy::ecs::event::EventCursor;
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+    use ymb_ui_automation::MuteButtonState;
+    use ymb_voice_activity::VoiceActivityEvent;
+    use ymb_voice_activity_plugin::VoiceActivityGameboundMessage;
+
+    const STEP: Duration = Duration::from_millis(100);
+
+    struct Harness {
+        app: App,
+        mute_button: Entity,
+        cursor: EventCursor<TalkingWhileMuted>,
+    }
+    impl Harness {
+        fn new(config: TalkingWhileMutedConfig) -> Self {
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins);
+            app.add_plugins(TalkingWhileMutedPlugin);
+            app.insert_resource(config);
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(STEP));
+            let mute_button = app.world_mut().spawn(MuteButtonState::NotMuted).id();
+            // The first update only initializes the clock
+            app.update();
+            Self {
+                app,
+                mute_button,
+                cursor: EventCursor::default(),
+            }
+        }
+        fn set_muted(&mut self, muted: bool) {
+            let state = if muted {
+                MuteButtonState::Muted
+            } else {
+                MuteButtonState::NotMuted
+            };
+            self.app
+                .world_mut()
+                .entity_mut(self.mute_button)
+                .insert(state);
+        }
+        fn send(&mut self, event: VoiceActivityEvent) {
+            self.app
+                .world_mut()
+                .send_event(VoiceActivityGameboundMessage::Activity(event));
+        }
+        fn start_speaking(&mut self) {
+            self.send(VoiceActivityEvent::VoiceActivityStarted { at: Duration::ZERO });
+        }
+        fn stop_speaking(&mut self) {
+            self.send(VoiceActivityEvent::VoiceActivityEnded {
+                at: Duration::ZERO,
+                duration: Duration::ZERO,
+            });
+        }
+        /// Advances by `millis` and returns the alerts raised meanwhile.
+        fn run(&mut self, millis: u64) -> Vec<TalkingWhileMuted> {
+            let mut alerts = Vec::new();
+            for _ in 0..millis / STEP.as_millis() as u64 {
+                self.app.update();
+                let events = self.app.world().resource::<Events<TalkingWhileMuted>>();
+                alerts.extend(self.cursor.read(events).copied());
+            }
+            alerts
+        }
+    }
+
+    fn config() -> TalkingWhileMutedConfig {
+        TalkingWhileMutedConfig {
+            min_speech_ms: 500,
+            grace_after_mute_ms: 1_000,
+            cooldown_ms: 5_000,
+            ignore_when_push_to_talk: true,
+        }
+    }
+
+    #[test]
+    fn speaking_unmuted_never_alerts() {
+        let mut harness = Harness::new(config());
+        harness.start_speaking();
+        assert!(harness.run(3_000).is_empty());
+    }
+
+    #[test]
+    fn speaking_while_muted_alerts_once_per_utterance() {
+        let mut harness = Harness::new(config());
+        harness.set_muted(true);
+        assert!(harness.run(2_000).is_empty());
+        harness.start_speaking();
+        assert!(harness.run(400).is_empty());
+        let alerts = harness.run(3_000);
+        assert_eq!(alerts.len(), 1, "{alerts:?}");
+        let alert = alerts[0];
+        assert!(alert.speech_duration >= Duration::from_millis(500));
+        assert!(alert.speech_started_at >= alert.muted_since);
+    }
+
+    #[test]
+    fn short_speech_is_ignored() {
+        let mut harness = Harness::new(config());
+        harness.set_muted(true);
+        harness.run(2_000);
+        harness.start_speaking();
+        harness.run(300);
+        harness.stop_speaking();
+        assert!(harness.run(2_000).is_empty());
+    }
+
+    #[test]
+    fn grace_period_after_muting() {
+        let mut harness = Harness::new(config());
+        harness.start_speaking();
+        harness.run(1_000);
+        harness.set_muted(true);
+        // Speech only counts from the end of the grace period
+        assert!(harness.run(1_400).is_empty());
+        assert_eq!(harness.run(300).len(), 1);
+    }
+
+    #[test]
+    fn cooldown_between_alerts() {
+        let mut harness = Harness::new(config());
+        harness.set_muted(true);
+        harness.run(2_000);
+        harness.start_speaking();
+        assert_eq!(harness.run(1_000).len(), 1);
+        harness.stop_speaking();
+        harness.run(500);
+        harness.start_speaking();
+        // Still within the cooldown of the first alert
+        assert!(harness.run(3_000).is_empty());
+        assert_eq!(harness.run(2_000).len(), 1);
+    }
+
+    #[test]
+    fn push_to_talk_suppresses_alerts() {
+        let mut harness = Harness::new(config());
+        harness.app.world_mut().resource_mut::<PushToTalk>().is_held = true;
+        harness.set_muted(true);
+        harness.run(2_000);
+        harness.start_speaking();
+        assert!(harness.run(2_000).is_empty());
+        harness.app.world_mut().resource_mut::<PushToTalk>().is_held = false;
+        assert_eq!(harness.run(200).len(), 1);
+    }
+
+    #[test]
+    fn unmuting_resets_the_rule() {
+        let mut harness = Harness::new(config());
+        harness.set_muted(true);
+        harness.run(2_000);
+        harness.start_speaking();
+        harness.run(300);
+        harness.set_muted(false);
+        assert!(harness.run(2_000).is_empty());
+    }
+}
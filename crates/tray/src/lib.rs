@@ -1,9 +1,11 @@
-use bincode;
 use chrono;
-use interprocess::os::windows::named_pipe::DuplexPipeStream;
-use interprocess::os::windows::named_pipe::pipe_mode;
-use std::io::Write;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -13,11 +15,25 @@ use windows::Win32::System::Console::*;
 use windows::Win32::System::LibraryLoader::*;
 use windows::Win32::UI::Shell::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
+use windows::core::HSTRING;
+use windows::core::PCWSTR;
 use windows::core::w;
 use ymb_args::GlobalArgs;
 use ymb_console::attach_console_window;
 use ymb_console::ctrl_handler;
 use ymb_console::hide_console_window;
+use ymb_ipc::Frame;
+use ymb_ipc::FrameError;
+use ymb_ipc::FrameWriter;
+use ymb_ipc::InstanceRecord;
+use ymb_ipc::IpcMessageHandler;
+use ymb_ipc::IpcStream;
+use ymb_ipc::IpcTransport;
+use ymb_ipc::TrayIconKind;
+use ymb_ipc::TrayStatus;
+use ymb_ipc::TrayboundIPCMessage;
+use ymb_ipc::connect;
+use ymb_ipc::spawn_reader;
 use ymb_ipc_plugin::BevyboundIPCMessage;
 use ymb_lifecycle::OUR_HWND;
 use ymb_lifecycle::SHOULD_SHOW_HIDE_LOGS_TRAY_ACTION;
@@ -26,6 +42,8 @@ use ymb_logs::LogBuffer;
 use ymb_windy::error::WindyResult;
 
 const WM_TRAYICON: u32 = WM_USER + 1;
+/// Posted by the IPC reader thread whenever [`TRAY_STATUS`] changes.
+const WM_TRAY_STATUS: u32 = WM_USER + 2;
 const ID_TRAYICON: u32 = 1;
 const ID_HELLO: u32 = 2;
 const ID_SHOW_LOGS: u32 = 3;
@@ -33,16 +51,61 @@ const ID_HIDE_LOGS: u32 = 5;
 const ID_QUIT: u32 = 4;
 const ID_DEBUG_MSG: u32 = 7;
 const ID_WORLD_INSPECTOR: u32 = 9;
+const ID_SETTINGS: u32 = 10;
+const ID_CURRENTLY: u32 = 11;
+
+/// How often the IPC thread tries to connect while the GUI is not reachable.
+const IPC_CONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What the GUI last told us, written by the IPC reader thread and shown by the window.
+static TRAY_STATUS: LazyLock<Mutex<TrayStatus>> = LazyLock::new(Default::default);
+
+fn tray_status() -> TrayStatus {
+    TRAY_STATUS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
 
 struct TrayWindow {
     hwnd: HWND,
     nid: NOTIFYICONDATAW,
     log_buffer: LogBuffer,
+    /// The app icon, shown while nothing needs attention.
+    normal_icon: HICON,
 }
 
 impl TrayWindow {
+    /// Points the tooltip and icon at the latest [`TRAY_STATUS`].
+    fn update_from_status(&mut self) {
+        let status = tray_status();
+        set_tooltip(&mut self.nid, &status.tooltip());
+        let stock_icon = match status.icon() {
+            TrayIconKind::Normal => None,
+            TrayIconKind::Muted => Some(IDI_WARNING),
+            TrayIconKind::Error => Some(IDI_ERROR),
+        };
+        self.nid.hIcon = match stock_icon {
+            None => self.normal_icon,
+            Some(name) => match unsafe { LoadIconW(None, name) } {
+                Ok(icon) => icon,
+                Err(e) => {
+                    error!("Failed to load {:?} tray icon: {}", status.icon(), e);
+                    self.normal_icon
+                }
+            },
+        };
+        if let Err(e) = unsafe { Shell_NotifyIconW(NIM_MODIFY, &self.nid) }.ok() {
+            error!("Failed to update tray icon: {}", e);
+        }
+    }
+
     fn handle(&mut self, message: u32, wparam: WPARAM, lparam: LPARAM) -> bool {
         match message {
+            WM_TRAY_STATUS => {
+                self.update_from_status();
+                true
+            }
             WM_TRAYICON => {
                 if lparam.0 as u32 == WM_RBUTTONUP {
                     unsafe {
@@ -52,8 +115,19 @@ impl TrayWindow {
                         let hide_logs_text = w!("Hide logs");
                         let debug_msg_text = w!("Debug Msg");
                         let world_inspector_text = w!("World inspector");
+                        let settings_text = w!("Settings");
                         let quit_text = w!("Quit");
+                        let currently_text = HSTRING::from(tray_status().menu_label());
+                        AppendMenuW(
+                            hmenu,
+                            MF_STRING | MF_GRAYED,
+                            ID_CURRENTLY as usize,
+                            &currently_text,
+                        )
+                        .unwrap();
+                        AppendMenuW(hmenu, MF_SEPARATOR, 0, PCWSTR::null()).unwrap();
                         AppendMenuW(hmenu, MF_STRING, ID_HELLO as usize, hello_text).unwrap();
+                        AppendMenuW(hmenu, MF_STRING, ID_SETTINGS as usize, settings_text).unwrap();
                         AppendMenuW(hmenu, MF_STRING, ID_SHOW_LOGS as usize, show_logs_text)
                             .unwrap();
                         if SHOULD_SHOW_HIDE_LOGS_TRAY_ACTION.load(Ordering::SeqCst) {
@@ -91,10 +165,10 @@ impl TrayWindow {
                 } else if lparam.0 as u32 == WM_LBUTTONUP {
                     // Send ToggleWindowVisibility message to Bevy app
                     info!("Tray icon left-clicked: sending ToggleWindowVisibility");
-                    let pipe_name = ymb_welcome_gui::spawn::get_pipe_name_for_tray();
-                    match pipe_name {
-                        Some(pipe_name) => {
-                            send_ipc_message(pipe_name, BevyboundIPCMessage::TrayIconClicked);
+                    let gui = ymb_welcome_gui::spawn::gui_instance();
+                    match gui {
+                        Some(gui) => {
+                            send_ipc_message(gui, BevyboundIPCMessage::TrayIconClicked);
                         }
                         None => {
                             warn!("Tray: IPC pipe name not set. Is GUI running?");
@@ -125,12 +199,12 @@ impl TrayWindow {
                 }
                 ID_DEBUG_MSG => {
                     info!("Debug Msg menu item clicked");
-                    // Get the pipe name from the welcome_gui spawn module
-                    let pipe_name = ymb_welcome_gui::spawn::get_pipe_name_for_tray();
-                    match pipe_name {
-                        Some(pipe_name) => {
+                    // Get the GUI endpoint and secret from the welcome_gui spawn module
+                    let gui = ymb_welcome_gui::spawn::gui_instance();
+                    match gui {
+                        Some(gui) => {
                             send_ipc_message(
-                                pipe_name,
+                                gui,
                                 BevyboundIPCMessage::DebugMessageReceived(format!(
                                     "Debug message from tray at {}!",
                                     chrono::Local::now().format("%H:%M:%S")
@@ -155,10 +229,22 @@ impl TrayWindow {
                     }
                 }
                 ID_WORLD_INSPECTOR => {
-                    let pipe_name = ymb_welcome_gui::spawn::get_pipe_name_for_tray();
-                    match pipe_name {
-                        Some(pipe_name) => {
-                            send_ipc_message(pipe_name, BevyboundIPCMessage::ShowWorldInspector);
+                    let gui = ymb_welcome_gui::spawn::gui_instance();
+                    match gui {
+                        Some(gui) => {
+                            send_ipc_message(gui, BevyboundIPCMessage::ShowWorldInspector);
+                        }
+                        None => {
+                            warn!("Tray: IPC pipe name not set. Is GUI running?");
+                        }
+                    }
+                    true
+                }
+                ID_SETTINGS => {
+                    let gui = ymb_welcome_gui::spawn::gui_instance();
+                    match gui {
+                        Some(gui) => {
+                            send_ipc_message(gui, BevyboundIPCMessage::ShowSettings);
                         }
                         None => {
                             warn!("Tray: IPC pipe name not set. Is GUI running?");
@@ -203,26 +289,156 @@ impl TrayWindow {
     }
 }
 
-fn send_ipc_message(pipe_name: String, message: BevyboundIPCMessage) {
-    let message_to_send = bincode::serialize(&message).expect("serialize");
-    std::thread::spawn(move || {
-        match DuplexPipeStream::<pipe_mode::Bytes>::connect_by_path(pipe_name) {
-            Ok(mut stream) => {
-                info!("Tray (IPC Thread): Connected to IPC pipe.");
-                if let Err(e) = stream.write_all(&message_to_send) {
-                    error!("Tray (IPC Thread): Failed to send IPC message: {}", e);
-                } else {
-                    info!("Tray (IPC Thread): Sent IPC message {message:?}");
+/// Copies as much of `tooltip` as fits, always leaving room for the terminating nul.
+fn set_tooltip(nid: &mut NOTIFYICONDATAW, tooltip: &str) {
+    let wide = tooltip
+        .encode_utf16()
+        .take(nid.szTip.len() - 1)
+        .collect::<Vec<_>>();
+    nid.szTip = [0; 128];
+    nid.szTip[..wide.len()].copy_from_slice(&wide);
+}
+
+/// Queues a message for the IPC thread, which keeps one connection to the GUI open across messages.
+fn send_ipc_message(gui: InstanceRecord, message: BevyboundIPCMessage) {
+    if ipc_sender().send((gui, message)).is_err() {
+        error!("Tray: IPC thread is gone, message dropped");
+    }
+}
+
+/// Starts the IPC thread on first use.
+fn ipc_sender() -> &'static mpsc::Sender<(InstanceRecord, BevyboundIPCMessage)> {
+    static IPC_TX: OnceLock<mpsc::Sender<(InstanceRecord, BevyboundIPCMessage)>> = OnceLock::new();
+    IPC_TX.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("TrayIpc".to_string())
+            .spawn(move || ipc_thread(rx))
+            .expect("spawn tray IPC thread");
+        tx
+    })
+}
+
+/// Applies everything the GUI pushes to [`TRAY_STATUS`] and wakes the window to show it.
+struct UpdateTrayStatus;
+
+impl UpdateTrayStatus {
+    fn notify_window() {
+        let hwnd = HWND(OUR_HWND.load(Ordering::SeqCst) as _);
+        if let Err(e) = unsafe { PostMessageW(Some(hwnd), WM_TRAY_STATUS, WPARAM(0), LPARAM(0)) } {
+            warn!("Tray (IPC Thread): Failed to notify the tray window: {}", e);
+        }
+    }
+}
+
+impl IpcMessageHandler<TrayboundIPCMessage> for UpdateTrayStatus {
+    fn handle(&mut self, frame: Frame<TrayboundIPCMessage>) {
+        debug!("Tray (IPC Thread): Received {:?}", frame.message);
+        TRAY_STATUS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .apply(frame.message);
+        Self::notify_window();
+    }
+
+    fn disconnected(&mut self, reason: &FrameError) {
+        info!("Tray (IPC Thread): GUI connection ended: {}", reason);
+        // Whatever the GUI said last is no longer true
+        *TRAY_STATUS.lock().unwrap_or_else(|e| e.into_inner()) = TrayStatus::default();
+        Self::notify_window();
+    }
+}
+
+struct GuiConnection {
+    pipe_name: String,
+    writer: FrameWriter<<IpcStream as IpcTransport>::Writer>,
+    reader: JoinHandle<FrameError>,
+}
+
+impl GuiConnection {
+    fn connect(gui: &InstanceRecord) -> std::io::Result<Self> {
+        let (writer, reader) = spawn_reader(
+            connect(&gui.endpoint)?,
+            gui.secret.clone(),
+            UpdateTrayStatus,
+        )?;
+        Ok(Self {
+            pipe_name: gui.endpoint.clone(),
+            writer,
+            reader,
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        !self.reader.is_finished()
+    }
+}
+
+fn ipc_thread(rx: mpsc::Receiver<(InstanceRecord, BevyboundIPCMessage)>) {
+    let mut connection: Option<GuiConnection> = None;
+    loop {
+        if connection.as_ref().is_some_and(|c| !c.is_alive()) {
+            connection = None;
+        }
+        let (gui, message) = match rx.recv_timeout(IPC_CONNECT_INTERVAL) {
+            Ok(request) => request,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // Connect without waiting for a message so the GUI can push its state to us
+                if connection.is_none()
+                    && let Some(gui) = ymb_welcome_gui::spawn::gui_instance()
+                {
+                    match GuiConnection::connect(&gui) {
+                        Ok(c) => {
+                            info!("Tray (IPC Thread): Connected to IPC pipe.");
+                            connection = Some(c);
+                        }
+                        Err(e) => debug!("Tray (IPC Thread): GUI not reachable yet: {}", e),
+                    }
+                }
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        };
+        // The GUI may have dropped the connection since the last message, so retry once on a fresh one
+        for _attempt in 0..2 {
+            if connection
+                .as_ref()
+                .is_none_or(|c| c.pipe_name != gui.endpoint)
+            {
+                match GuiConnection::connect(&gui) {
+                    Ok(c) => {
+                        info!("Tray (IPC Thread): Connected to IPC pipe.");
+                        connection = Some(c);
+                    }
+                    Err(e) => {
+                        error!(
+                            "Tray (IPC Thread): Failed to connect to IPC pipe: {}. Is GUI running and its IPC server ready?",
+                            e
+                        );
+                        break;
+                    }
                 }
             }
-            Err(e) => {
-                error!(
-                    "Tray (IPC Thread): Failed to connect to IPC pipe: {}. Is GUI running and its IPC server ready?",
-                    e
-                );
+            let Some(c) = connection.as_mut() else {
+                break;
+            };
+            match c.writer.send(&message) {
+                Ok(request_id) => {
+                    info!(
+                        "Tray (IPC Thread): Sent IPC message {message:?} as request {request_id}"
+                    );
+                    break;
+                }
+                Err(e) => {
+                    warn!(
+                        "Tray (IPC Thread): Failed to send IPC message, reconnecting: {}",
+                        e
+                    );
+                    connection = None;
+                }
             }
         }
-    });
+    }
 }
 
 unsafe extern "system" fn window_proc(
@@ -236,6 +452,7 @@ unsafe extern "system" fn window_proc(
             hwnd,
             nid: Default::default(),
             log_buffer: Default::default(),
+            normal_icon: Default::default(),
         });
         unsafe { SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(window) as _) };
         return LRESULT(0);
@@ -330,11 +547,11 @@ pub fn main(global_args: GlobalArgs, log_writer: DualLogWriter) -> WindyResult<(
             ..Default::default()
         };
 
-        // Set tooltip
-        let tooltip =
-            w!("You're Muted Btw - This app warns when you're trying to talk while muted");
-        let tooltip_bytes = tooltip.as_wide();
-        nid.szTip[..tooltip_bytes.len()].copy_from_slice(tooltip_bytes);
+        // Set tooltip, replaced by the GUI's status once it connects
+        set_tooltip(
+            &mut nid,
+            "You're Muted Btw - This app warns when you're trying to talk while muted",
+        );
 
         Shell_NotifyIconW(NIM_ADD, &nid).ok()?;
 
@@ -343,12 +560,16 @@ pub fn main(global_args: GlobalArgs, log_writer: DualLogWriter) -> WindyResult<(
             hwnd,
             nid,
             log_buffer: log_writer.buffer.clone(),
+            normal_icon: icon,
         });
         SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(window) as _);
 
         // Spawn Bevy app
         ymb_welcome_gui::spawn(global_args, log_writer)?;
 
+        // Connect to the GUI as soon as it is listening so its status shows before the first click
+        ipc_sender();
+
         // Message loop
         let mut msg = MSG::default();
         while GetMessageW(&mut msg, None, 0, 0).into() {
@@ -357,6 +578,7 @@ pub fn main(global_args: GlobalArgs, log_writer: DualLogWriter) -> WindyResult<(
         }
 
         // Final cleanup
+        ymb_welcome_gui::spawn::unregister_instance();
         if let Some(window) = (GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut TrayWindow).as_mut() {
             if let Err(e) = Shell_NotifyIconW(NIM_DELETE, &window.nid).ok() {
                 debug!("Failed to delete tray icon, this always happens :P {}", e);
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use ymb_alert_plugin::AlertPause;
+use ymb_ipc::InstanceStatus;
+use ymb_ipc::TrayMuteState;
+use ymb_ipc::TrayStatus;
+use ymb_ipc::TrayboundIPCMessage;
+use ymb_ipc_plugin::BevyboundIPCMessage;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_ipc_plugin::TrayClients;
+use ymb_ui_automation::MuteButtonState;
+use ymb_voice_activity_plugin::VoiceActivity;
+use ymb_voice_activity_plugin::VoiceActivityGameboundMessage;
+
+/// Keeps connected trays up to date with the mute button, voice activity, microphone and errors.
+pub struct TrayStatusPlugin;
+
+impl Plugin for TrayStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<VoiceActivityGameboundMessage>();
+        app.add_event::<IpcWorkerGameboundMessage>();
+        app.init_resource::<TrayClients>();
+        app.init_resource::<PublishedTrayStatus>();
+        app.add_systems(
+            Update,
+            (track_errors, publish_tray_status, answer_status_requests).chain(),
+        );
+    }
+}
+
+/// What the trays have been told so far, so only changes are sent.
+#[derive(Debug, Default, Resource)]
+pub struct PublishedTrayStatus {
+    pub status: TrayStatus,
+    /// The latest voice activity error, cleared once capture works again.
+    pub error: Option<String>,
+}
+
+fn track_errors(
+    mut messages: EventReader<VoiceActivityGameboundMessage>,
+    mut published: ResMut<PublishedTrayStatus>,
+) {
+    for msg in messages.read() {
+        match msg {
+            VoiceActivityGameboundMessage::Error(error) => published.error = Some(error.clone()),
+            VoiceActivityGameboundMessage::SourceChanged { .. }
+            | VoiceActivityGameboundMessage::Resumed => published.error = None,
+            _ => {}
+        }
+    }
+}
+
+fn publish_tray_status(
+    mute_buttons: Query<&MuteButtonState>,
+    voice_activity: Query<&VoiceActivity>,
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    clients: Res<TrayClients>,
+    mut published: ResMut<PublishedTrayStatus>,
+) {
+    let mute_state = match mute_buttons.iter().next() {
+        Some(MuteButtonState::Muted) => TrayMuteState::Muted,
+        Some(MuteButtonState::NotMuted) => TrayMuteState::NotMuted,
+        None => TrayMuteState::Unknown,
+    };
+    let voice_activity = voice_activity.iter().next();
+    let status = TrayStatus {
+        mute_state,
+        is_speaking: voice_activity.is_some_and(|voice_activity| voice_activity.is_speaking),
+        mic: voice_activity.and_then(|voice_activity| voice_activity.source.clone()),
+        error: published.error.clone(),
+    };
+    // New clients start from nothing, so everyone gets the whole status again
+    let client_connected = messages
+        .read()
+        .any(|msg| matches!(msg, IpcWorkerGameboundMessage::ClientConnected));
+    let outgoing = if client_connected {
+        status.messages()
+    } else {
+        published.status.diff(&status)
+    };
+    if outgoing.is_empty() {
+        return;
+    }
+    for message in &outgoing {
+        debug!("Sending {:?} to {} trays", message, clients.0.len());
+        clients.0.broadcast(message);
+    }
+    published.status = status;
+}
+
+/// Answers `ctl status` with what was just published, plus how long alerts stay paused.
+///
+/// Only the client that asked hears the answer.
+fn answer_status_requests(
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    clients: Res<TrayClients>,
+    published: Res<PublishedTrayStatus>,
+    alert_pause: Option<Res<AlertPause>>,
+    time: Res<Time>,
+) {
+    let requesters: Vec<_> = messages
+        .read()
+        .filter_map(|msg| match msg {
+            IpcWorkerGameboundMessage::RequestReceived {
+                from,
+                request: BevyboundIPCMessage::RequestStatus,
+            } => Some(*from),
+            _ => None,
+        })
+        .collect();
+    if requesters.is_empty() {
+        return;
+    }
+    let status = InstanceStatus {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        mute_state: published.status.mute_state,
+        is_speaking: published.status.is_speaking,
+        mic: published.status.mic.clone(),
+        error: published.status.error.clone(),
+        alerts_paused_for_ms: alert_pause
+            .and_then(|pause| pause.remaining(time.elapsed()))
+            .map(|remaining| remaining.as_millis() as u64),
+    };
+    debug!("Answering status request with {:?}", status);
+    for requester in &requesters {
+        clients
+            .0
+            .reply(requester, TrayboundIPCMessage::Status(status.clone()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TrayStatusPlugin;
+    use bevy::prelude::*;
+    use ymb_ipc::InstanceStatus;
+    use ymb_ipc::IpcRequester;
+    use ymb_ipc::IpcTransport;
+    use ymb_ipc::SessionSecret;
+    use ymb_ipc::TrayMuteState;
+    use ymb_ipc::TrayboundIPCMessage;
+    use ymb_ipc::memory_duplex;
+    use ymb_ipc::read_frame;
+    use ymb_ipc_plugin::BevyboundIPCMessage;
+    use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+    use ymb_ipc_plugin::TrayClients;
+    use ymb_ui_automation::MuteButtonState;
+    use ymb_voice_activity_plugin::VoiceActivityGameboundMessage;
+
+    #[test]
+    fn trays_hear_about_changes() -> eyre::Result<()> {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(TrayStatusPlugin);
+        let (gui_end, tray_end) = memory_duplex();
+        let (_, writer) = gui_end.split()?;
+        let secret = SessionSecret::from_bytes([7; 32]);
+        let client = app
+            .world()
+            .resource::<TrayClients>()
+            .0
+            .add(writer, secret.clone())?;
+        let (mut tray_reader, _tray_writer) = tray_end.split()?;
+        let mut next_frame = || read_frame::<TrayboundIPCMessage>(&mut tray_reader, &secret);
+        let mut next = || next_frame().map(|f| f.message);
+
+        let mute_button = app.world_mut().spawn(MuteButtonState::Muted).id();
+        app.update();
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::MuteState(TrayMuteState::Muted)
+        );
+
+        app.world_mut()
+            .send_event(VoiceActivityGameboundMessage::Error(
+                "No microphone".to_string(),
+            ));
+        app.world_mut()
+            .entity_mut(mute_button)
+            .insert(MuteButtonState::NotMuted);
+        app.update();
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::MuteState(TrayMuteState::NotMuted)
+        );
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::Error(Some("No microphone".to_string()))
+        );
+
+        // Nothing changed, so the next frame is the full status sent to a new client
+        app.update();
+        app.world_mut()
+            .send_event(IpcWorkerGameboundMessage::ClientConnected);
+        app.update();
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::MuteState(TrayMuteState::NotMuted)
+        );
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::VoiceActivity { is_speaking: false }
+        );
+        assert_eq!(next()?, TrayboundIPCMessage::CurrentMic(None));
+        assert_eq!(
+            next()?,
+            TrayboundIPCMessage::Error(Some("No microphone".to_string()))
+        );
+
+        app.world_mut()
+            .send_event(IpcWorkerGameboundMessage::RequestReceived {
+                from: IpcRequester {
+                    client,
+                    request_id: 42,
+                },
+                request: BevyboundIPCMessage::RequestStatus,
+            });
+        app.update();
+        let reply = next_frame()?;
+        assert_eq!(reply.request_id, 42);
+        assert_eq!(
+            reply.message,
+            TrayboundIPCMessage::Status(InstanceStatus {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                mute_state: TrayMuteState::NotMuted,
+                is_speaking: false,
+                mic: None,
+                error: Some("No microphone".to_string()),
+                alerts_paused_for_ms: None,
+            })
+        );
+        Ok(())
+    }
+}
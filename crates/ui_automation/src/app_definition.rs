@@ -0,0 +1,148 @@
+use crate::AspectRatio;
+use crate::ElementSelector;
+use crate::MuteButtonState;
+use crate::TextMatch;
+use crate::YMBControlType;
+use bevy::reflect::Reflect;
+use eyre::ensure;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Where an app keeps its mute button, so more apps can be read without recompiling.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppDefinition {
+    /// Shown in logs and readings, like "Discord".
+    pub app: String,
+    pub mute_button: ElementSelector,
+    /// Button names that mean the app is muted, for buttons without a toggle state.
+    #[serde(default)]
+    pub muted_names: Vec<String>,
+    /// Button names that mean the app is not muted, for buttons without a toggle state.
+    #[serde(default)]
+    pub not_muted_names: Vec<String>,
+}
+impl AppDefinition {
+    pub fn discord() -> Self {
+        Self {
+            app: "Discord".to_string(),
+            mute_button: ElementSelector {
+                name: Some(TextMatch::OneOf(vec![
+                    "Mute".to_string(),
+                    "Unmute".to_string(),
+                ])),
+                control_type: YMBControlType::from_name("Button"),
+                aspect_ratio: Some(AspectRatio { min: 0.8, max: 1.2 }),
+                ancestors: vec![Self::discord_window()],
+                ..Default::default()
+            },
+            // The button is named for what clicking it does
+            muted_names: vec!["Unmute".to_string()],
+            not_muted_names: vec!["Mute".to_string()],
+        }
+    }
+
+    /// The Discord desktop app's window.
+    pub fn discord_window() -> ElementSelector {
+        ElementSelector {
+            name: Some(TextMatch::Contains("Discord".to_string())),
+            class_name: Some(TextMatch::Equals("Chrome_WidgetWin_1".to_string())),
+            control_type: YMBControlType::from_name("Pane"),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the state from the mute button's name, `None` when the name means neither.
+    pub fn state_from_name(&self, name: &str) -> Option<MuteButtonState> {
+        if self.muted_names.iter().any(|muted| muted == name) {
+            Some(MuteButtonState::Muted)
+        } else if self
+            .not_muted_names
+            .iter()
+            .any(|not_muted| not_muted == name)
+        {
+            Some(MuteButtonState::NotMuted)
+        } else {
+            None
+        }
+    }
+
+    pub fn validate(&self) -> eyre::Result<()> {
+        ensure!(!self.app.is_empty(), "The app needs a name");
+        self.mute_button
+            .validate()
+            .map_err(|e| e.wrap_err(format!("Invalid mute button for {}", self.app)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AppDefinition;
+    use crate::ElementInfo;
+    use crate::MuteButtonState;
+    use crate::YMBControlType;
+    use bevy::math::IRect;
+
+    #[test]
+    fn discord_finds_its_mute_button() {
+        let mute_button = ElementInfo {
+            name: "Unmute".to_string(),
+            bounding_rect: IRect::new(4158, 1550, 4199, 1590),
+            control_type: YMBControlType::from_name("Button").unwrap(),
+            ..Default::default()
+        };
+        let window = ElementInfo {
+            name: "#general | Guh-Uh-Guys - Discord".to_string(),
+            class_name: "Chrome_WidgetWin_1".to_string(),
+            children: Some(vec![mute_button.clone()]),
+            ..Default::default()
+        };
+        let discord = AppDefinition::discord();
+        discord.validate().unwrap();
+        let found = discord.mute_button.find_first(&window).unwrap();
+        assert_eq!(found, &mute_button);
+        assert_eq!(
+            discord.state_from_name(&found.name),
+            Some(MuteButtonState::Muted)
+        );
+
+        // The same button outside of Discord is someone else's
+        assert!(discord.mute_button.find_first(&mute_button).is_none());
+    }
+
+    #[test]
+    fn definitions_round_trip_through_toml() {
+        let discord = AppDefinition::discord();
+        let text = toml::to_string(&discord).unwrap();
+        assert!(text.contains("control_type = \"Button\""), "{text}");
+        let read: AppDefinition = toml::from_str(&text).unwrap();
+        assert_eq!(read, discord);
+    }
+
+    #[test]
+    fn definitions_are_written_by_hand() {
+        let text = r#"
+            app = "Teams"
+            not_muted_names = ["Mute mic"]
+            muted_names = ["Unmute mic"]
+
+            [mute_button]
+            automation_id = { equals = "microphone-button" }
+            control_type = "button"
+            size = { max_width = 100 }
+        "#;
+        let teams: AppDefinition = toml::from_str(text).unwrap();
+        teams.validate().unwrap();
+        assert_eq!(
+            teams.mute_button.control_type,
+            YMBControlType::from_name("Button")
+        );
+        assert_eq!(
+            teams.state_from_name("Unmute mic"),
+            Some(MuteButtonState::Muted)
+        );
+
+        let typo = text.replace("\"button\"", "\"buton\"");
+        assert!(toml::from_str::<AppDefinition>(&typo).is_err());
+    }
+}
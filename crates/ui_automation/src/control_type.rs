@@ -1,37 +1,109 @@
 use bevy::reflect::Reflect;
+#[cfg(windows)]
 use bevy_inspector_egui::egui;
+#[cfg(windows)]
 use bevy_inspector_egui::inspector_egui_impls::InspectorPrimitive;
+#[cfg(windows)]
 use bevy_inspector_egui::reflect_inspector::InspectorUi;
 use serde::Deserialize;
 use serde::Serialize;
+#[cfg(windows)]
 use uiautomation::controls::ControlType;
 
 use serde::de::Error as DeError;
 
-#[derive(Reflect, Clone, PartialEq, Eq, Serialize)]
+/// UI automation numbers its control types from this ID up, in the order of [`CONTROL_TYPE_NAMES`].
+const FIRST_CONTROL_TYPE_ID: i32 = 50000;
+
+/// The names UI automation gives its control types, so they can be written down and read without it.
+pub const CONTROL_TYPE_NAMES: [&str; 41] = [
+    "Button",
+    "Calendar",
+    "CheckBox",
+    "ComboBox",
+    "Edit",
+    "Hyperlink",
+    "Image",
+    "ListItem",
+    "List",
+    "Menu",
+    "MenuBar",
+    "MenuItem",
+    "ProgressBar",
+    "RadioButton",
+    "ScrollBar",
+    "Slider",
+    "Spinner",
+    "StatusBar",
+    "Tab",
+    "TabItem",
+    "Text",
+    "ToolBar",
+    "ToolTip",
+    "Tree",
+    "TreeItem",
+    "Custom",
+    "Group",
+    "Thumb",
+    "DataGrid",
+    "DataItem",
+    "Document",
+    "SplitButton",
+    "Window",
+    "Pane",
+    "Header",
+    "HeaderItem",
+    "Table",
+    "TitleBar",
+    "Separator",
+    "SemanticZoom",
+    "AppBar",
+];
+
+#[derive(Reflect, Clone, PartialEq, Eq)]
 pub struct YMBControlType {
     inner: i32,
 }
 impl std::fmt::Display for YMBControlType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.as_uia_control_type())
+        std::fmt::Debug::fmt(self, f)
     }
 }
 impl std::fmt::Debug for YMBControlType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.as_uia_control_type())
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "ControlType({})", self.inner),
+        }
     }
 }
 impl YMBControlType {
+    /// Looks up a control type by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        CONTROL_TYPE_NAMES
+            .iter()
+            .position(|known| known.eq_ignore_ascii_case(name))
+            .map(|index| Self {
+                inner: FIRST_CONTROL_TYPE_ID + index as i32,
+            })
+    }
+    pub fn name(&self) -> Option<&'static str> {
+        usize::try_from(self.inner - FIRST_CONTROL_TYPE_ID)
+            .ok()
+            .and_then(|index| CONTROL_TYPE_NAMES.get(index))
+            .copied()
+    }
+    #[cfg(windows)]
     pub fn as_uia_control_type(&self) -> ControlType {
         ControlType::try_from(self.inner).unwrap()
     }
 }
 impl Default for YMBControlType {
     fn default() -> Self {
-        ControlType::Pane.into()
+        Self::from_name("Pane").unwrap()
     }
 }
+#[cfg(windows)]
 impl From<ControlType> for YMBControlType {
     fn from(control_type: ControlType) -> Self {
         Self {
@@ -39,24 +111,40 @@ impl From<ControlType> for YMBControlType {
         }
     }
 }
+#[cfg(windows)]
 impl TryFrom<YMBControlType> for ControlType {
     type Error = uiautomation::Error;
     fn try_from(value: YMBControlType) -> Result<Self, Self::Error> {
         ControlType::try_from(value.inner)
     }
 }
+/// Written as the bare ID, the same way it is read.
+impl Serialize for YMBControlType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_i32(self.inner)
+    }
+}
 impl<'de> Deserialize<'de> for YMBControlType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         let inner = i32::deserialize(deserializer)?;
-        let control_type = ControlType::try_from(inner)
-            .map_err(|_| D::Error::custom(format!("Invalid ControlType value: {}", inner)))?;
-        Ok(control_type.into())
+        let control_type = Self { inner };
+        if control_type.name().is_none() {
+            return Err(D::Error::custom(format!(
+                "Invalid ControlType value: {}",
+                inner
+            )));
+        }
+        Ok(control_type)
     }
 }
 
+#[cfg(windows)]
 impl InspectorPrimitive for YMBControlType {
     fn ui(
         &mut self,
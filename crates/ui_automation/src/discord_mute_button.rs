@@ -1,12 +1,18 @@
+#[cfg(windows)]
+use crate::AppDefinition;
 use crate::ElementInfo;
+use crate::YMBControlType;
+#[cfg(windows)]
+use crate::selector_matcher;
 use bevy::ecs::component::Component;
 use bevy::math::IRect;
 use bevy::math::IVec2;
 use bevy::reflect::Reflect;
 use eyre::ensure;
+#[cfg(windows)]
 use uiautomation::UIAutomation;
+#[cfg(windows)]
 use uiautomation::UIElement;
-use uiautomation::controls::ControlType::Button;
 
 #[derive(Component, Reflect, Debug)]
 pub struct DiscordMuteButton;
@@ -18,7 +24,7 @@ impl DiscordMuteButton {
                 min: IVec2::new(4158, 1550),
                 max: IVec2::new(4199, 1590),
             },
-            control_type: Button.into(),
+            control_type: YMBControlType::from_name("Button").unwrap(),
             localized_control_type: "button".to_string(),
             class_name: "".to_string(),
             automation_id: "".to_string(),
@@ -27,28 +33,15 @@ impl DiscordMuteButton {
             children: None,
         }
     }
+    #[cfg(windows)]
     pub fn try_find(automation: &UIAutomation) -> eyre::Result<UIElement> {
-        let matcher = automation
-            .create_matcher()
-            .name("Mute")
-            .control_type(Button);
-        let first = matcher.find_first();
-        if let Ok(element_info) = first {
-            return Ok(element_info);
-        }
-
-        let matcher = automation
-            .create_matcher()
-            .name("Unmute")
-            .control_type(Button);
-        let second = matcher.find_first();
-        if let Ok(element_info) = second {
-            return Ok(element_info);
-        }
-        eyre::bail!("Discord mute button not found ({first:?}, {second:?})");
+        let matcher = selector_matcher(automation, &AppDefinition::discord().mute_button)?;
+        matcher
+            .find_first()
+            .map_err(|e| eyre::eyre!("Discord mute button not found ({e:?})"))
     }
     pub fn try_eq(mute_button_element_info: &ElementInfo) -> eyre::Result<()> {
-        ensure!(mute_button_element_info.control_type == Button.into());
+        ensure!(mute_button_element_info.control_type.name() == Some("Button"));
         ensure!(mute_button_element_info.name == "Mute");
         let rect = mute_button_element_info.bounding_rect;
         let width = rect.width() as f64;
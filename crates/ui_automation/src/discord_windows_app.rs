@@ -1,11 +1,17 @@
+#[cfg(windows)]
+use crate::AppDefinition;
 use crate::DrillId::Unknown;
 use crate::ElementInfo;
+use crate::YMBControlType;
+#[cfg(windows)]
+use crate::selector_matcher;
 use bevy::math::IRect;
 use bevy::math::IVec2;
 use eyre::ensure;
+#[cfg(windows)]
 use uiautomation::UIAutomation;
+#[cfg(windows)]
 use uiautomation::UIMatcher;
-use uiautomation::controls::ControlType::Pane;
 
 pub struct DiscordWindowsApp;
 impl DiscordWindowsApp {
@@ -16,7 +22,7 @@ impl DiscordWindowsApp {
                 min: IVec2::new(3832, 568),
                 max: IVec2::new(5768, 1624),
             },
-            control_type: Pane.into(),
+            control_type: YMBControlType::from_name("Pane").unwrap(),
             localized_control_type: "pane".to_string(),
             class_name: "Chrome_WidgetWin_1".to_string(),
             automation_id: "".to_string(),
@@ -25,15 +31,12 @@ impl DiscordWindowsApp {
             children: None,
         }
     }
-    pub fn get_matcher(automation: &UIAutomation) -> UIMatcher {
-        automation
-            .create_matcher()
-            .contains_name("Discord")
-            .control_type(Pane)
-            .classname("Chrome_WidgetWin_1")
+    #[cfg(windows)]
+    pub fn get_matcher(automation: &UIAutomation) -> eyre::Result<UIMatcher> {
+        selector_matcher(automation, &AppDefinition::discord_window())
     }
     pub fn try_eq(mute_button_element_info: &ElementInfo) -> eyre::Result<()> {
-        ensure!(mute_button_element_info.control_type == Pane.into());
+        ensure!(mute_button_element_info.control_type.name() == Some("Pane"));
         ensure!(mute_button_element_info.name.contains("Discord"));
         Ok(())
     }
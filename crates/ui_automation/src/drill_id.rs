@@ -5,11 +5,16 @@ use eyre::bail;
 use itertools::Itertools;
 use serde::Deserialize;
 use serde::Serialize;
+#[cfg(windows)]
 use uiautomation::UIAutomation;
+#[cfg(windows)]
 use uiautomation::UIElement;
 
+#[cfg(windows)]
 use crate::Drillable;
+#[cfg(windows)]
 use crate::ElementInfo;
+#[cfg(windows)]
 use crate::gather_single_element_info;
 #[derive(Debug, Eq, PartialEq, Clone, Reflect, Default, Hash, Serialize, Deserialize)]
 pub enum DrillId {
@@ -85,6 +90,7 @@ impl DrillId {
             DrillId::Unknown => "DrillId::Unknown".to_string(),
         }
     }
+    #[cfg(windows)]
     pub fn resolve(self) -> eyre::Result<VecDeque<(UIElement, ElementInfo)>> {
         let automation = UIAutomation::new()?;
         let walker = automation.create_tree_walker()?;
@@ -1,7 +1,9 @@
 use crate::DrillId;
+#[cfg(windows)]
 use crate::IntoBevyIRect;
 use crate::RuntimeId;
 use crate::control_type::YMBControlType;
+#[cfg(windows)]
 use crate::update_drill_ids;
 use bevy::ecs::component::Component;
 use bevy::log::trace;
@@ -10,8 +12,8 @@ use bevy::reflect::Reflect;
 use itertools::Itertools;
 use serde::Deserialize;
 use serde::Serialize;
+#[cfg(windows)]
 use uiautomation::UIElement;
-use uiautomation::controls::ControlType;
 
 #[derive(Debug, Clone, Reflect, PartialEq, Eq, Serialize, Deserialize, Component)]
 #[reflect(no_field_bounds)]
@@ -37,7 +39,7 @@ impl Default for ElementInfo {
         ElementInfo {
             name: "UNKNOWN ELEMENT INFO".to_string(),
             bounding_rect: IRect::new(0, 0, 0, 0),
-            control_type: ControlType::Pane.into(),
+            control_type: YMBControlType::default(),
             localized_control_type: "".to_string(),
             class_name: "".to_string(),
             automation_id: "".to_string(),
@@ -47,6 +49,7 @@ impl Default for ElementInfo {
         }
     }
 }
+#[cfg(windows)]
 impl TryFrom<UIElement> for ElementInfo {
     type Error = uiautomation::Error;
     fn try_from(value: UIElement) -> Result<Self, Self::Error> {
@@ -200,6 +203,7 @@ impl ElementInfo {
             })
             .join("")
     }
+    #[cfg(windows)]
     pub fn try_update_drill_ids(&mut self) -> eyre::Result<()> {
         update_drill_ids(self)?;
         Ok(())
@@ -0,0 +1,53 @@
+use crate::ScreenInfo;
+use bevy::math::IRect;
+use windows::Win32::Foundation::LPARAM;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::Graphics::Gdi::EnumDisplayMonitors;
+use windows::Win32::Graphics::Gdi::GetMonitorInfoW;
+use windows::Win32::Graphics::Gdi::HDC;
+use windows::Win32::Graphics::Gdi::HMONITOR;
+use windows::Win32::Graphics::Gdi::MONITORINFO;
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+use windows::core::BOOL;
+
+/// Every monitor attached to the desktop, in the order Windows enumerates them.
+pub fn gather_screens() -> eyre::Result<Vec<ScreenInfo>> {
+    let mut screens: Vec<ScreenInfo> = Vec::new();
+    let found = unsafe {
+        EnumDisplayMonitors(
+            None,
+            None,
+            Some(push_screen),
+            LPARAM(&mut screens as *mut Vec<ScreenInfo> as isize),
+        )
+    };
+    eyre::ensure!(found.as_bool(), "Failed to enumerate the display monitors");
+    Ok(screens)
+}
+
+unsafe extern "system" fn push_screen(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _clip: *mut RECT,
+    screens: LPARAM,
+) -> BOOL {
+    // Only ever called with the vec passed to EnumDisplayMonitors above
+    let screens = unsafe { &mut *(screens.0 as *mut Vec<ScreenInfo>) };
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+        screens.push(ScreenInfo {
+            bounding_rect: to_irect(info.rcMonitor),
+            work_area: to_irect(info.rcWork),
+            primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+        });
+    }
+    // Keep enumerating
+    true.into()
+}
+
+fn to_irect(rect: RECT) -> IRect {
+    IRect::new(rect.left, rect.top, rect.right, rect.bottom)
+}
@@ -1,41 +1,81 @@
+mod app_definition;
 mod control_type;
+#[cfg(windows)]
 mod conversion_traits;
 mod discord_mute_button;
 mod discord_windows_app;
+#[cfg(windows)]
 mod drill;
 mod drill_id;
 mod element_info;
+#[cfg(windows)]
 mod find_element_at;
+#[cfg(windows)]
 mod gather_children;
+#[cfg(windows)]
 mod gather_element_info;
+#[cfg(windows)]
 mod gather_elements_at;
+#[cfg(windows)]
 mod gather_info_tree_ancestry_filtered;
+#[cfg(windows)]
 mod gather_info_tree_filtered;
+#[cfg(windows)]
 mod gather_root;
+#[cfg(windows)]
+mod gather_screens;
+#[cfg(windows)]
 mod gather_tree_from_position;
+#[cfg(windows)]
 mod gather_ui_ancestors_including_start;
 mod runtime_id;
+mod selector;
+#[cfg(windows)]
+mod selector_matcher;
+mod snapshot;
+#[cfg(windows)]
 mod stop_behaviour;
 mod toggle_state;
+#[cfg(windows)]
 mod update_drill_ids;
 
+pub use app_definition::*;
 pub use control_type::*;
+#[cfg(windows)]
 pub use conversion_traits::*;
 pub use discord_mute_button::*;
 pub use discord_windows_app::*;
+#[cfg(windows)]
 pub use drill::*;
 pub use drill_id::*;
 pub use element_info::*;
+#[cfg(windows)]
 pub use find_element_at::*;
+#[cfg(windows)]
 pub use gather_children::*;
+#[cfg(windows)]
 pub use gather_element_info::*;
+#[cfg(windows)]
 pub use gather_elements_at::*;
+#[cfg(windows)]
 pub use gather_info_tree_ancestry_filtered::*;
+#[cfg(windows)]
 pub use gather_info_tree_filtered::*;
+#[cfg(windows)]
 pub use gather_root::*;
+#[cfg(windows)]
+pub use gather_screens::*;
+#[cfg(windows)]
 pub use gather_tree_from_position::*;
+#[cfg(windows)]
 pub use gather_ui_ancestors_including_start::*;
 pub use runtime_id::*;
+pub use selector::*;
+#[cfg(windows)]
+pub use selector_matcher::*;
+pub use snapshot::*;
+#[cfg(windows)]
 pub use stop_behaviour::*;
 pub use toggle_state::*;
+#[cfg(windows)]
 pub use update_drill_ids::*;
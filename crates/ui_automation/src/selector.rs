@@ -0,0 +1,381 @@
+use crate::ElementInfo;
+use crate::YMBControlType;
+use bevy::reflect::Reflect;
+use eyre::ensure;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How a text field of an element is compared, case sensitive.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMatch {
+    Equals(String),
+    Contains(String),
+    StartsWith(String),
+    EndsWith(String),
+    OneOf(Vec<String>),
+}
+impl TextMatch {
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            TextMatch::Equals(expected) => text == expected,
+            TextMatch::Contains(expected) => text.contains(expected.as_str()),
+            TextMatch::StartsWith(expected) => text.starts_with(expected.as_str()),
+            TextMatch::EndsWith(expected) => text.ends_with(expected.as_str()),
+            TextMatch::OneOf(expected) => expected.iter().any(|expected| text == expected),
+        }
+    }
+}
+
+/// Limits on the size of an element's bounding rect, in pixels, inclusive.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SizeConstraint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_height: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<i32>,
+}
+impl SizeConstraint {
+    pub fn matches(&self, width: i32, height: i32) -> bool {
+        self.min_width.is_none_or(|min| width >= min)
+            && self.max_width.is_none_or(|max| width <= max)
+            && self.min_height.is_none_or(|min| height >= min)
+            && self.max_height.is_none_or(|max| height <= max)
+    }
+}
+
+/// Limits on width divided by height, exclusive, so square buttons can be told apart from wide ones.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AspectRatio {
+    pub min: f64,
+    pub max: f64,
+}
+impl AspectRatio {
+    pub fn matches(&self, width: i32, height: i32) -> bool {
+        let ratio = width as f64 / height as f64;
+        ratio > self.min && ratio < self.max
+    }
+}
+
+/// Describes an element by its [`ElementInfo`] fields, so apps can be defined in config.
+///
+/// Every field that is set has to match, an empty selector matches anything.
+#[derive(Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(no_field_bounds)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElementSelector {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<TextMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<TextMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub automation_id: Option<TextMatch>,
+    /// By name, like "Button".
+    #[serde(skip_serializing_if = "Option::is_none", with = "control_type_by_name")]
+    pub control_type: Option<YMBControlType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<SizeConstraint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<AspectRatio>,
+    /// Outermost first, each has to match an ancestor inside the one matched before it.
+    /// They don't have to be direct parents of each other.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<ElementSelector>,
+}
+impl ElementSelector {
+    /// Checks the element's own fields, ignoring [`Self::ancestors`].
+    pub fn matches_element(&self, element: &ElementInfo) -> bool {
+        let width = element.bounding_rect.width();
+        let height = element.bounding_rect.height();
+        self.name
+            .as_ref()
+            .is_none_or(|name| name.matches(&element.name))
+            && self
+                .class_name
+                .as_ref()
+                .is_none_or(|class_name| class_name.matches(&element.class_name))
+            && self
+                .automation_id
+                .as_ref()
+                .is_none_or(|automation_id| automation_id.matches(&element.automation_id))
+            && self
+                .control_type
+                .as_ref()
+                .is_none_or(|control_type| *control_type == element.control_type)
+            && self
+                .size
+                .as_ref()
+                .is_none_or(|size| size.matches(width, height))
+            && self
+                .aspect_ratio
+                .as_ref()
+                .is_none_or(|aspect_ratio| aspect_ratio.matches(width, height))
+    }
+
+    /// Checks the element and its ancestors, which are given outermost first.
+    pub fn matches(&self, element: &ElementInfo, ancestors: &[&ElementInfo]) -> bool {
+        if !self.matches_element(element) {
+            return false;
+        }
+        // Matching the innermost selector to the innermost ancestor that fits leaves the most room for the rest
+        let mut ancestors = ancestors.iter().rev();
+        self.ancestors.iter().rev().all(|selector| {
+            ancestors
+                .by_ref()
+                .any(|ancestor| selector.matches_element(ancestor))
+        })
+    }
+
+    /// Every element in the tree that matches, in depth first order, starting with the root.
+    pub fn find_all<'a>(&self, root: &'a ElementInfo) -> Vec<&'a ElementInfo> {
+        let mut found = Vec::new();
+        let mut path = Vec::new();
+        self.find_into(root, &mut path, &mut found, false);
+        found
+    }
+
+    pub fn find_first<'a>(&self, root: &'a ElementInfo) -> Option<&'a ElementInfo> {
+        let mut found = Vec::new();
+        let mut path = Vec::new();
+        self.find_into(root, &mut path, &mut found, true);
+        found.into_iter().next()
+    }
+
+    fn find_into<'a>(
+        &self,
+        element: &'a ElementInfo,
+        path: &mut Vec<&'a ElementInfo>,
+        found: &mut Vec<&'a ElementInfo>,
+        first_only: bool,
+    ) {
+        if self.matches(element, path) {
+            found.push(element);
+            if first_only {
+                return;
+            }
+        }
+        let Some(children) = &element.children else {
+            return;
+        };
+        path.push(element);
+        for child in children {
+            self.find_into(child, path, found, first_only);
+            if first_only && !found.is_empty() {
+                break;
+            }
+        }
+        path.pop();
+    }
+
+    /// Catches selectors that can never match, like an empty list of names.
+    pub fn validate(&self) -> eyre::Result<()> {
+        for (field, text_match) in [
+            ("name", &self.name),
+            ("class_name", &self.class_name),
+            ("automation_id", &self.automation_id),
+        ] {
+            if let Some(TextMatch::OneOf(options)) = text_match {
+                ensure!(!options.is_empty(), "{field} has to be one of no options");
+            }
+        }
+        if let Some(size) = &self.size {
+            ensure!(
+                size.min_width
+                    .zip(size.max_width)
+                    .is_none_or(|(min, max)| min <= max),
+                "min_width is more than max_width"
+            );
+            ensure!(
+                size.min_height
+                    .zip(size.max_height)
+                    .is_none_or(|(min, max)| min <= max),
+                "min_height is more than max_height"
+            );
+        }
+        if let Some(aspect_ratio) = &self.aspect_ratio {
+            ensure!(
+                aspect_ratio.min < aspect_ratio.max,
+                "The aspect ratio has to be more than {} and less than {}, which nothing is",
+                aspect_ratio.min,
+                aspect_ratio.max
+            );
+        }
+        for ancestor in &self.ancestors {
+            ancestor.validate()?;
+        }
+        Ok(())
+    }
+}
+
+mod control_type_by_name {
+    use crate::YMBControlType;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(
+        control_type: &Option<YMBControlType>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match control_type {
+            Some(control_type) => serializer.serialize_str(&control_type.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<YMBControlType>, D::Error> {
+        let Some(name) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        YMBControlType::from_name(&name)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("Unknown control type {name:?}")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AspectRatio;
+    use crate::ElementInfo;
+    use crate::ElementSelector;
+    use crate::SizeConstraint;
+    use crate::TextMatch;
+    use crate::YMBControlType;
+    use bevy::math::IRect;
+
+    fn element(name: &str, control_type: &str, children: Vec<ElementInfo>) -> ElementInfo {
+        ElementInfo {
+            name: name.to_string(),
+            bounding_rect: IRect::new(0, 0, 40, 40),
+            control_type: YMBControlType::from_name(control_type).unwrap(),
+            children: Some(children),
+            ..Default::default()
+        }
+    }
+
+    fn sample_tree() -> ElementInfo {
+        element(
+            "Desktop",
+            "Pane",
+            vec![
+                element("Notepad", "Window", vec![element("Mute", "Button", vec![])]),
+                element(
+                    "#general - Discord",
+                    "Pane",
+                    vec![element(
+                        "User area",
+                        "Group",
+                        vec![
+                            element("Mute", "Button", vec![]),
+                            element("Deafen", "Button", vec![]),
+                        ],
+                    )],
+                ),
+            ],
+        )
+    }
+
+    fn mute_button_in_discord() -> ElementSelector {
+        ElementSelector {
+            name: Some(TextMatch::OneOf(vec!["Mute".into(), "Unmute".into()])),
+            control_type: YMBControlType::from_name("Button"),
+            ancestors: vec![ElementSelector {
+                name: Some(TextMatch::Contains("Discord".into())),
+                control_type: YMBControlType::from_name("Pane"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ancestors_tell_apart_same_named_elements() {
+        let tree = sample_tree();
+        let found = mute_button_in_discord().find_all(&tree);
+        assert_eq!(found.len(), 1);
+        let user_area = &tree.children.as_ref().unwrap()[1]
+            .children
+            .as_ref()
+            .unwrap()[0];
+        assert!(std::ptr::eq(
+            found[0],
+            &user_area.children.as_ref().unwrap()[0]
+        ));
+
+        let without_ancestors = ElementSelector {
+            ancestors: vec![],
+            ..mute_button_in_discord()
+        };
+        assert_eq!(without_ancestors.find_all(&tree).len(), 2);
+    }
+
+    #[test]
+    fn ancestors_match_in_order() {
+        let tree = sample_tree();
+        let group = ElementSelector {
+            control_type: YMBControlType::from_name("Group"),
+            ..Default::default()
+        };
+        let discord = mute_button_in_discord().ancestors.remove(0);
+        let outer_to_inner = ElementSelector {
+            ancestors: vec![discord.clone(), group.clone()],
+            ..mute_button_in_discord()
+        };
+        assert!(outer_to_inner.find_first(&tree).is_some());
+        let inner_to_outer = ElementSelector {
+            ancestors: vec![group, discord],
+            ..mute_button_in_discord()
+        };
+        assert!(inner_to_outer.find_first(&tree).is_none());
+    }
+
+    #[test]
+    fn size_and_aspect_ratio() {
+        let mut wide = element("Mute", "Button", vec![]);
+        wide.bounding_rect = IRect::new(0, 0, 120, 40);
+        let square = element("Mute", "Button", vec![]);
+        let selector = ElementSelector {
+            aspect_ratio: Some(AspectRatio { min: 0.8, max: 1.2 }),
+            ..Default::default()
+        };
+        assert!(selector.matches_element(&square));
+        assert!(!selector.matches_element(&wide));
+
+        let selector = ElementSelector {
+            size: Some(SizeConstraint {
+                min_width: Some(100),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(!selector.matches_element(&square));
+        assert!(selector.matches_element(&wide));
+    }
+
+    #[test]
+    fn validate_rejects_impossible_selectors() {
+        assert!(mute_button_in_discord().validate().is_ok());
+        let no_names = ElementSelector {
+            ancestors: vec![ElementSelector {
+                name: Some(TextMatch::OneOf(vec![])),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(no_names.validate().is_err());
+        let backwards = ElementSelector {
+            aspect_ratio: Some(AspectRatio { min: 1.2, max: 0.8 }),
+            ..Default::default()
+        };
+        assert!(backwards.validate().is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::ElementSelector;
+use crate::IntoBevyIRect;
+use crate::YMBControlType;
+use uiautomation::UIAutomation;
+use uiautomation::UIElement;
+use uiautomation::UIMatcher;
+use uiautomation::UITreeWalker;
+
+/// Finds live elements with an [`ElementSelector`].
+///
+/// Every property read is a cross process call made for each element walked,
+/// so only the properties the selector names are read, cheapest first,
+/// and ancestors are only walked for elements that match themselves.
+pub fn selector_matcher(
+    automation: &UIAutomation,
+    selector: &ElementSelector,
+) -> eyre::Result<UIMatcher> {
+    let walker = automation.create_tree_walker()?;
+    let selector = selector.clone();
+    Ok(automation
+        .create_matcher()
+        .filter_fn(Box::new(move |element: &UIElement| {
+            // An element that vanished or can't be read is not the one we're looking for
+            Ok(element_matches(&selector, &walker, element).unwrap_or(false))
+        })))
+}
+
+/// Same as [`ElementSelector::matches`], walking up from the element only as far as the ancestors need.
+fn element_matches(
+    selector: &ElementSelector,
+    walker: &UITreeWalker,
+    element: &UIElement,
+) -> eyre::Result<bool> {
+    if !matches_live_element(selector, element)? {
+        return Ok(false);
+    }
+    let mut current = element.clone();
+    for ancestor in selector.ancestors.iter().rev() {
+        loop {
+            // Walking past the desktop fails, leaving this ancestor unmatched
+            let Ok(parent) = walker.get_parent(&current) else {
+                return Ok(false);
+            };
+            current = parent;
+            if matches_live_element(ancestor, &current)? {
+                break;
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Same as [`ElementSelector::matches_element`], reading only what the selector checks.
+fn matches_live_element(selector: &ElementSelector, element: &UIElement) -> eyre::Result<bool> {
+    if let Some(control_type) = &selector.control_type
+        && *control_type != YMBControlType::from(element.get_control_type()?)
+    {
+        return Ok(false);
+    }
+    if let Some(name) = &selector.name
+        && !name.matches(&element.get_name()?)
+    {
+        return Ok(false);
+    }
+    if let Some(class_name) = &selector.class_name
+        && !class_name.matches(&element.get_classname()?)
+    {
+        return Ok(false);
+    }
+    if let Some(automation_id) = &selector.automation_id
+        && !automation_id.matches(&element.get_automation_id()?)
+    {
+        return Ok(false);
+    }
+    if selector.size.is_some() || selector.aspect_ratio.is_some() {
+        let rect = element.get_bounding_rectangle()?.to_bevy_irect();
+        let (width, height) = (rect.width(), rect.height());
+        if selector
+            .size
+            .as_ref()
+            .is_some_and(|size| !size.matches(width, height))
+            || selector
+                .aspect_ratio
+                .as_ref()
+                .is_some_and(|aspect_ratio| !aspect_ratio.matches(width, height))
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
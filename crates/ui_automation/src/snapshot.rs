@@ -0,0 +1,281 @@
+use crate::AppDefinition;
+use crate::DiscordMuteButton;
+use crate::DiscordWindowsApp;
+use crate::ElementInfo;
+use bevy::math::IRect;
+use bevy::reflect::Reflect;
+use chrono::DateTime;
+use chrono::Utc;
+use eyre::Context;
+use eyre::bail;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+/// One monitor, in virtual desktop coordinates like the element bounding rects.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub struct ScreenInfo {
+    pub bounding_rect: IRect,
+    /// The part not covered by the taskbar and docked toolbars.
+    pub work_area: IRect,
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotMetadata {
+    pub taken_at: DateTime<Utc>,
+    /// The version of the build that took the snapshot.
+    pub app_version: String,
+    pub screens: Vec<ScreenInfo>,
+}
+
+/// A gathered element tree saved with what's needed to make sense of it later,
+/// so trees from bug reports can be queried without the machine they came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UISnapshot {
+    pub metadata: SnapshotMetadata,
+    pub root: ElementInfo,
+}
+
+/// How a snapshot is written before being gzipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Ron,
+}
+impl SnapshotFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Json => "json.gz",
+            SnapshotFormat::Ron => "ron.gz",
+        }
+    }
+
+    /// Goes by the file name ending in `.json.gz` or `.ron.gz`.
+    pub fn from_path(path: &Path) -> eyre::Result<Self> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        for format in [SnapshotFormat::Json, SnapshotFormat::Ron] {
+            if file_name.ends_with(&format!(".{}", format.extension())) {
+                return Ok(format);
+            }
+        }
+        bail!(
+            "Unknown snapshot format for {}, expected a .json.gz or .ron.gz file",
+            path.display()
+        )
+    }
+}
+
+impl UISnapshot {
+    /// Stamps the tree with the current time and this build's version.
+    pub fn new(root: ElementInfo, screens: Vec<ScreenInfo>) -> Self {
+        Self {
+            metadata: SnapshotMetadata {
+                taken_at: Utc::now(),
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                screens,
+            },
+            root,
+        }
+    }
+
+    /// Snapshots a tree gathered on this machine, like from [`crate::gather_root`].
+    #[cfg(windows)]
+    pub fn capture(root: ElementInfo) -> eyre::Result<Self> {
+        Ok(Self::new(root, crate::gather_screens()?))
+    }
+
+    /// Snapshots every element on the desktop, which takes a while with many windows open.
+    #[cfg(windows)]
+    pub fn capture_desktop() -> eyre::Result<Self> {
+        let automation = uiautomation::UIAutomation::new()?;
+        let walker = automation.create_tree_walker()?;
+        let desktop = automation.get_root_element()?;
+        let mut root = crate::gather_tree_filtered(&desktop, &walker, &|_| true, 0)?;
+        root.drill_id = crate::DrillId::Root;
+        root.try_update_drill_ids()?;
+        Self::capture(root)
+    }
+
+    pub fn write(&self, writer: impl Write, format: SnapshotFormat) -> eyre::Result<()> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        match format {
+            SnapshotFormat::Json => serde_json::to_writer(&mut encoder, self)?,
+            SnapshotFormat::Ron => ron::ser::to_writer(&mut encoder, self)?,
+        }
+        encoder.finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn read(reader: impl Read, format: SnapshotFormat) -> eyre::Result<Self> {
+        let decoder = GzDecoder::new(reader);
+        Ok(match format {
+            SnapshotFormat::Json => serde_json::from_reader(decoder)?,
+            SnapshotFormat::Ron => ron::de::from_reader(decoder)?,
+        })
+    }
+
+    /// Writes the snapshot in the format its file name asks for, see [`SnapshotFormat::from_path`].
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        let format = SnapshotFormat::from_path(path)?;
+        let file = File::create(path)
+            .wrap_err_with(|| format!("Failed to create snapshot {}", path.display()))?;
+        self.write(BufWriter::new(file), format)
+            .wrap_err_with(|| format!("Failed to write snapshot {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let format = SnapshotFormat::from_path(path)?;
+        let file = File::open(path)
+            .wrap_err_with(|| format!("Failed to open snapshot {}", path.display()))?;
+        Self::read(BufReader::new(file), format)
+            .wrap_err_with(|| format!("Failed to read snapshot {}", path.display()))
+    }
+
+    /// Looks for the app's mute button the way it would be found on the live tree.
+    pub fn find_mute_button(&self, definition: &AppDefinition) -> Option<&ElementInfo> {
+        definition.mute_button.find_first(&self.root)
+    }
+
+    /// Looks for Discord's mute button with the [`DiscordWindowsApp`] and [`DiscordMuteButton`] heuristics
+    /// instead of the selectors. The heuristics only recognize it while it reads "Mute".
+    pub fn find_discord_mute_button(&self) -> Option<&ElementInfo> {
+        self.root
+            .get_descendents()
+            .into_iter()
+            .filter(|window| DiscordWindowsApp::try_eq(window).is_ok())
+            .flat_map(|window| window.get_descendents())
+            .find(|element| DiscordMuteButton::try_eq(element).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AppDefinition;
+    use crate::DrillId;
+    use crate::ElementInfo;
+    use crate::MuteButtonState;
+    use crate::ScreenInfo;
+    use crate::SnapshotFormat;
+    use crate::UISnapshot;
+    use crate::YMBControlType;
+    use bevy::math::IRect;
+    use std::path::Path;
+
+    fn sample_snapshot() -> UISnapshot {
+        let mute_button = ElementInfo {
+            name: "Unmute".to_string(),
+            bounding_rect: IRect::new(4158, 1550, 4199, 1590),
+            control_type: YMBControlType::from_name("Button").unwrap(),
+            localized_control_type: "button".to_string(),
+            runtime_id: vec![42, 788570, 4, 4294966141u32].into(),
+            drill_id: [0, 0].into(),
+            ..Default::default()
+        };
+        let discord = ElementInfo {
+            name: "#general | Guh-Uh-Guys - Discord".to_string(),
+            bounding_rect: IRect::new(3832, 568, 5768, 1624),
+            class_name: "Chrome_WidgetWin_1".to_string(),
+            runtime_id: vec![42, 133266].into(),
+            drill_id: [0].into(),
+            children: Some(vec![mute_button]),
+            ..Default::default()
+        };
+        let desktop = ElementInfo {
+            name: "Desktop 1".to_string(),
+            bounding_rect: IRect::new(0, 0, 5760, 2160),
+            drill_id: DrillId::Root,
+            children: Some(vec![discord]),
+            ..Default::default()
+        };
+        UISnapshot::new(
+            desktop,
+            vec![
+                ScreenInfo {
+                    bounding_rect: IRect::new(0, 0, 3840, 2160),
+                    work_area: IRect::new(0, 0, 3840, 2100),
+                    primary: true,
+                },
+                ScreenInfo {
+                    bounding_rect: IRect::new(3840, 0, 5760, 1080),
+                    work_area: IRect::new(3840, 0, 5760, 1080),
+                    primary: false,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn round_trips_in_both_formats() -> eyre::Result<()> {
+        let snapshot = sample_snapshot();
+        for format in [SnapshotFormat::Json, SnapshotFormat::Ron] {
+            let mut bytes = Vec::new();
+            snapshot.write(&mut bytes, format)?;
+            assert_eq!(bytes[..2], [0x1f, 0x8b], "{format:?} should be gzipped");
+            let read = UISnapshot::read(bytes.as_slice(), format)?;
+            assert_eq!(read, snapshot, "{format:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn loaded_trees_can_be_queried() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("bug-report.ron.gz");
+        sample_snapshot().save(&path)?;
+        let snapshot = UISnapshot::load(&path)?;
+
+        let by_drill_id = snapshot.root.lookup_drill_id([0, 0].into()).unwrap();
+        let discord = AppDefinition::discord();
+        let mute_button = snapshot.find_mute_button(&discord).unwrap();
+        assert_eq!(mute_button, by_drill_id);
+        assert_eq!(
+            discord.state_from_name(&mute_button.name),
+            Some(MuteButtonState::Muted)
+        );
+        assert_eq!(snapshot.metadata.screens.len(), 2);
+        assert_eq!(snapshot.metadata.app_version, env!("CARGO_PKG_VERSION"));
+        Ok(())
+    }
+
+    #[test]
+    fn discord_heuristics_run_on_snapshots() {
+        let mut snapshot = sample_snapshot();
+        // Muted, the button reads "Unmute"
+        assert_eq!(snapshot.find_discord_mute_button(), None);
+
+        let drill_id: DrillId = [0, 0].into();
+        snapshot
+            .root
+            .lookup_drill_id_mut(drill_id.clone())
+            .unwrap()
+            .name = "Mute".to_string();
+        let by_drill_id = snapshot.root.lookup_drill_id(drill_id).unwrap();
+        assert_eq!(snapshot.find_discord_mute_button(), Some(by_drill_id));
+    }
+
+    #[test]
+    fn format_comes_from_the_file_name() {
+        assert_eq!(
+            SnapshotFormat::from_path(Path::new("tree.json.gz")).unwrap(),
+            SnapshotFormat::Json
+        );
+        assert_eq!(
+            SnapshotFormat::from_path(Path::new("reports/tree.ron.gz")).unwrap(),
+            SnapshotFormat::Ron
+        );
+        assert!(SnapshotFormat::from_path(Path::new("tree.json")).is_err());
+        assert!(sample_snapshot().save(Path::new("tree.txt")).is_err());
+    }
+}
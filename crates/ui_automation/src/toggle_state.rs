@@ -1,7 +1,10 @@
 use bevy::ecs::component::Component;
 use bevy::prelude::*;
+#[cfg(windows)]
 use eyre::bail;
+#[cfg(windows)]
 use uiautomation::UIElement;
+#[cfg(windows)]
 use uiautomation::patterns::UITogglePattern;
 
 #[derive(Debug, Reflect, Component, Clone, Hash, Eq, PartialEq)]
@@ -9,6 +12,7 @@ pub enum MuteButtonState {
     Muted,
     NotMuted,
 }
+#[cfg(windows)]
 impl TryFrom<uiautomation::types::ToggleState> for MuteButtonState {
     type Error = eyre::Error;
 
@@ -23,6 +27,7 @@ impl TryFrom<uiautomation::types::ToggleState> for MuteButtonState {
     }
 }
 
+#[cfg(windows)]
 impl TryFrom<&UIElement> for MuteButtonState {
     type Error = eyre::Error;
 
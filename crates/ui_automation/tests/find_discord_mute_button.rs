@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 use uiautomation::UIAutomation;
 use ymb_ui_automation::DiscordMuteButton;
 use ymb_ui_automation::MuteButtonState;
@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 use uiautomation::UIAutomation;
 use ymb_ui_automation::DiscordMuteButton;
 use ymb_ui_automation::DiscordWindowsApp;
@@ -6,7 +8,7 @@ use ymb_ui_automation::Drillable;
 #[test]
 fn it_works() -> eyre::Result<()> {
     let automation = UIAutomation::new()?;
-    let matcher = DiscordWindowsApp::get_matcher(&automation);
+    let matcher = DiscordWindowsApp::get_matcher(&automation)?;
     let elem = matcher.find_first()?;
     println!("Located Discord window: {elem:#?}");
     let walker = automation.create_tree_walker()?;
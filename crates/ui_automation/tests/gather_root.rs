@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 #[cfg(test)]
 mod test {
     use ymb_ui_automation::gather_root;
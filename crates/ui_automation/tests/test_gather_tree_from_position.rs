@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 use bevy::math::IVec2;
 use ymb_ui_automation::gather_tree_from_position;
 
@@ -0,0 +1,85 @@
+use crate::MuteConfidence;
+use crate::MuteReading;
+use crate::MuteSource;
+use bevy::prelude::*;
+use eyre::eyre;
+use uiautomation::UIAutomation;
+use uiautomation::UIElement;
+use uiautomation::patterns::UITogglePattern;
+use ymb_ui_automation::AppDefinition;
+use ymb_ui_automation::MuteButtonState;
+use ymb_ui_automation::selector_matcher;
+
+/// Reads the mute button of a desktop app through UI automation, found by its [`AppDefinition`].
+pub struct AppMuteSource {
+    definition: AppDefinition,
+    automation: UIAutomation,
+    mute_button: Option<UIElement>,
+}
+impl AppMuteSource {
+    pub fn new(definition: AppDefinition) -> eyre::Result<Self> {
+        Ok(Self {
+            definition,
+            automation: UIAutomation::new()?,
+            mute_button: None,
+        })
+    }
+
+    fn reading(&self, state: Option<MuteButtonState>, confidence: MuteConfidence) -> MuteReading {
+        MuteReading {
+            app: self.app().to_string(),
+            state,
+            confidence,
+        }
+    }
+
+    fn read_state(
+        &self,
+        mute_button: &UIElement,
+    ) -> eyre::Result<(MuteButtonState, MuteConfidence)> {
+        // Only the toggle pattern reads the state itself, the fallback goes by the button's label
+        if let Ok(pattern) = mute_button.get_pattern::<UITogglePattern>() {
+            let state = MuteButtonState::try_from(pattern.get_toggle_state()?)?;
+            return Ok((state, MuteConfidence::High));
+        }
+        let name = mute_button.get_name()?;
+        match self.definition.state_from_name(&name) {
+            Some(state) => Ok((state, MuteConfidence::Medium)),
+            None => Err(eyre!(
+                "Found an unexpected name {name:?} for the mute button element"
+            )),
+        }
+    }
+}
+
+impl MuteSource for AppMuteSource {
+    fn app(&self) -> &str {
+        &self.definition.app
+    }
+
+    fn probe(&mut self) -> eyre::Result<()> {
+        if self.mute_button.is_none() {
+            let matcher = selector_matcher(&self.automation, &self.definition.mute_button)?;
+            let element = matcher
+                .find_first()
+                .map_err(|e| eyre!("{} mute button not found ({e:?})", self.definition.app))?;
+            info!("Found mute button element: {:?}", element);
+            self.mute_button = Some(element);
+        }
+        Ok(())
+    }
+
+    fn current_state(&mut self) -> MuteReading {
+        let Some(mute_button) = &self.mute_button else {
+            return self.reading(None, MuteConfidence::Low);
+        };
+        match self.read_state(mute_button) {
+            Ok((state, confidence)) => self.reading(Some(state), confidence),
+            Err(x) => {
+                warn!("Failed to get toggle state from mute button: {:?}", x);
+                self.mute_button = None; // Reset the mute button if an error occurs
+                self.reading(Some(MuteButtonState::NotMuted), MuteConfidence::Low) // Default to Off if error occurs
+            }
+        }
+    }
+}
@@ -1,125 +1,154 @@
+#[cfg(windows)]
+mod app_mute_source;
+mod mute_source;
+mod scripted_mute_source;
+
+#[cfg(windows)]
+pub use app_mute_source::*;
+pub use mute_source::*;
+pub use scripted_mute_source::*;
+
 use bevy::prelude::*;
+#[cfg(windows)]
 use bevy_inspector_egui::inspector_egui_impls::InspectorEguiImpl;
-use std::any::type_name;
 use std::time::Duration;
-use uiautomation::UIAutomation;
-use uiautomation::UIElement;
+use ymb_ipc_plugin::BevyboundIPCMessage;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsSection;
+#[cfg(windows)]
 use ymb_ui_automation::AncestryTree;
+use ymb_ui_automation::AppDefinition;
+#[cfg(windows)]
 use ymb_ui_automation::DiscordMuteButton;
+#[cfg(windows)]
 use ymb_ui_automation::ElementInfo;
 use ymb_ui_automation::MuteButtonState;
+#[cfg(windows)]
 use ymb_ui_automation::YMBControlType;
+use ymb_worker_plugin::Backpressure;
 use ymb_worker_plugin::Sender;
-use ymb_worker_plugin::WorkerConfig;
+use ymb_worker_plugin::TickSchedule;
 use ymb_worker_plugin::WorkerPlugin;
 use ymb_worker_plugin::WorkerStateTrait;
+use ymb_worker_plugin::WorkerTickControl;
 
-pub struct UIAutomationPlugin;
+/// Spreads out mute button checks a little, the settings choose the interval itself.
+const REFRESH_JITTER: Duration = Duration::from_millis(100);
+
+/// Reads the apps defined in the settings, on Windows, after any [`Self::sources`] given here.
+#[derive(Default)]
+pub struct UIAutomationPlugin {
+    pub sources: MuteSourceRegistry,
+}
 
 impl Plugin for UIAutomationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(WorkerPlugin {
-            config: WorkerConfig::<
+        let sources = self.sources.clone();
+        app.add_plugins(
+            WorkerPlugin::<
                 UIWorkerThreadboundMessage,
                 UIWorkerGameboundMessage,
                 UIWorkerState,
-            > {
-                name: "ElementInfoPluginWorker".to_string(),
-                handle_threadbound_message,
-                threadbound_message_receiver: |thread_rx, _state| {
-                    if thread_rx.is_empty() {
-                        thread_rx.recv().map_err(BevyError::from)
-                    } else {
-                        Ok(thread_rx.try_iter().last().unwrap())
-                    }
-                },
-                ..default()
-            },
-        });
+            >::builder("ElementInfoPluginWorker")
+            .on_message(move |msg, reply_tx, state| {
+                handle_threadbound_message(msg, reply_tx, state, &sources)
+            })
+            // The Discord source reads the UI through COM, which the worker sets up and tears down
+            .ui_automation_thread()
+            // Polls on the worker thread, so it keeps up while the window is hidden and the app updates slowly
+            .tick_every(
+                TickSchedule::every(Duration::from_secs(2)).with_jitter(REFRESH_JITTER),
+                |_| UIWorkerThreadboundMessage::DetectMuteButtonState,
+            )
+            // Detecting more than once in a row only repeats the same answer
+            .threadbound_backpressure(Backpressure::coalesce_variants())
+            .build(),
+        );
         app.add_systems(Update, handle_gamebound_messages);
-        app.add_systems(Update, tick_worker);
-        app.add_systems(Startup, startup_fetch);
-        app.init_resource::<UIAutomationPluginConfig>();
-        app.register_type::<UIAutomationPluginConfig>();
-        app.register_type::<ElementInfo>();
-        app.register_type::<AncestryTree>();
-        app.register_type::<YMBControlType>();
+        app.add_systems(Update, handle_ipc_simulate_mute_toggle);
+        // The first detection already reads the configured apps
+        app.add_systems(Startup, (apply_settings, startup_fetch).chain());
+        app.add_systems(Update, apply_changed_settings);
         app.register_type::<MuteButtonState>();
-        app.register_type::<DiscordMuteButton>();
-        app.register_type_data::<YMBControlType, InspectorEguiImpl>();
-    }
-}
-
-#[derive(Resource, Reflect)]
-#[reflect(Resource)]
-pub struct UIAutomationPluginConfig {
-    pub refresh_interval: Timer,
-}
-
-impl Default for UIAutomationPluginConfig {
-    fn default() -> Self {
-        Self {
-            refresh_interval: Timer::new(Duration::from_secs(2), TimerMode::Repeating),
+        app.register_type::<MuteReading>();
+        app.register_type::<MuteConfidence>();
+        app.register_type::<AppDefinition>();
+        #[cfg(windows)]
+        {
+            app.register_type::<ElementInfo>();
+            app.register_type::<AncestryTree>();
+            app.register_type::<YMBControlType>();
+            app.register_type::<DiscordMuteButton>();
+            app.register_type_data::<YMBControlType, InspectorEguiImpl>();
         }
     }
 }
 
+/// What the mute button entity is called, whichever source it was read from.
+const MUTE_BUTTON_NAME: &str = "Mute Button";
+
 pub struct UIWorkerState {
-    automation: UIAutomation,
-    mute_button: Option<UIElement>,
+    /// The apps from the settings, read after the plugin's own sources.
+    apps: Vec<AppDefinition>,
+    /// Created from the registry on the first message, so they live on the worker thread.
+    sources: Option<MuteSources>,
 }
 impl WorkerStateTrait for UIWorkerState {
     type Error = BevyError;
 
     fn try_default() -> std::result::Result<Self, Self::Error> {
-        let automation = UIAutomation::new()?;
         Ok(Self {
-            automation,
-            mute_button: None,
+            apps: Vec::new(),
+            sources: None,
         })
     }
+
+    fn cleanup(&mut self) {
+        // Release the sources while COM is still initialized on this thread
+        self.sources = None;
+        debug!("Released mute sources");
+    }
 }
 
 #[derive(Debug, Reflect, Clone, Event)]
 pub enum UIWorkerThreadboundMessage {
     DetectMuteButtonState,
+    UseApps { apps: Vec<AppDefinition> },
 }
 
 #[derive(Debug, Reflect, Clone, Event)]
 #[reflect(from_reflect = false)]
 pub enum UIWorkerGameboundMessage {
-    MuteButtonObserved { state: MuteButtonState },
+    MuteButtonObserved { reading: MuteReading },
 }
 
 fn handle_threadbound_message(
     msg: &UIWorkerThreadboundMessage,
     reply_tx: &Sender<UIWorkerGameboundMessage>,
     state: &mut UIWorkerState,
+    registry: &MuteSourceRegistry,
 ) -> Result<()> {
     debug!("Handling threadbound message: {:?}", msg);
     match msg {
         UIWorkerThreadboundMessage::DetectMuteButtonState => {
-            if state.mute_button.is_none() {
-                if let Ok(element) = DiscordMuteButton::try_find(&state.automation) {
-                    info!("Found mute button element: {:?}", element);
-                    state.mute_button = Some(element);
+            let sources = state
+                .sources
+                .get_or_insert_with(|| with_apps(registry, &state.apps).create());
+            match sources.read() {
+                Some(reading) => {
+                    reply_tx.send(UIWorkerGameboundMessage::MuteButtonObserved { reading })?;
                 }
+                None => warn!("Mute button not found."),
             }
-            if let Some(mute_button) = &state.mute_button {
-                match MuteButtonState::try_from(mute_button) {
-                    Ok(state) => {
-                        reply_tx.send(UIWorkerGameboundMessage::MuteButtonObserved { state })?;
-                    }
-                    Err(x) => {
-                        warn!("Failed to get toggle state from mute button: {:?}", x);
-                        state.mute_button = None; // Reset the mute button if an error occurs
-                        reply_tx.send(UIWorkerGameboundMessage::MuteButtonObserved {
-                            state: MuteButtonState::NotMuted, // Default to Off if error occurs
-                        })?;
-                    }
-                }
-            } else {
-                warn!("Mute button not found.");
+        }
+        UIWorkerThreadboundMessage::UseApps { apps } => {
+            if state.apps != *apps {
+                info!("Reading mute buttons of {} configured apps", apps.len());
+                state.apps = apps.clone();
+                // Recreated on the next detection, probing the new apps from scratch
+                state.sources = None;
             }
         }
     }
@@ -127,31 +156,63 @@ fn handle_threadbound_message(
     Ok(())
 }
 
+/// Adds a source for every valid app definition, after the plugin's own sources.
+fn with_apps(registry: &MuteSourceRegistry, apps: &[AppDefinition]) -> MuteSourceRegistry {
+    let apps = apps
+        .iter()
+        .filter(|definition| match definition.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Skipping app definition for {}: {:?}", definition.app, e);
+                false
+            }
+        });
+    #[cfg(windows)]
+    let registry = apps
+        .cloned()
+        .fold(registry.clone(), |registry, definition| {
+            registry.with_source(move || AppMuteSource::new(definition.clone()))
+        });
+    #[cfg(not(windows))]
+    let registry = {
+        debug!(
+            "Reading app mute buttons needs Windows, skipping {} apps",
+            apps.count()
+        );
+        registry.clone()
+    };
+    registry
+}
+
 fn handle_gamebound_messages(
     mut messages: EventReader<UIWorkerGameboundMessage>,
-    mut mute_button: Query<&mut MuteButtonState>,
+    mut mute_button: Query<(Entity, &mut MuteButtonState)>,
     mut commands: Commands,
 ) -> Result {
     for msg in messages.read() {
         match msg {
-            UIWorkerGameboundMessage::MuteButtonObserved { state } => {
-                debug!("Received mute button state: {:?}", state);
-                let existing = mute_button.single_mut() ;
-                if let Ok(mut toggle_state) = existing {
+            UIWorkerGameboundMessage::MuteButtonObserved { reading } => {
+                debug!("Received mute reading: {:?}", reading);
+                let Some(state) = &reading.state else {
+                    continue;
+                };
+                let existing = mute_button.single_mut();
+                if let Ok((entity, mut toggle_state)) = existing {
                     if *toggle_state != *state {
                         info!(
-                            "Toggle state changed from {:?} to {:?}",
-                            toggle_state, state
+                            "Toggle state changed from {:?} to {:?} according to {}",
+                            toggle_state, state, reading.app
                         );
                         *toggle_state = state.clone();
                     }
+                    commands.entity(entity).insert(reading.clone());
                 } else {
-                    info!("No existing MuteButtonState found, spawning new one. Error was {:?}", existing.err().unwrap());
-                    info!("Spawning new DiscordMuteButton with state: {:?}", state);
-                    commands.spawn((
-                        state.clone(),
-                        Name::new(type_name::<DiscordMuteButton>()),
-                    ));
+                    info!(
+                        "No existing MuteButtonState found, spawning new one. Error was {:?}",
+                        existing.err().unwrap()
+                    );
+                    info!("Spawning new mute button with state: {:?}", state);
+                    commands.spawn((state.clone(), reading.clone(), Name::new(MUTE_BUTTON_NAME)));
                 }
             }
         }
@@ -160,16 +221,60 @@ fn handle_gamebound_messages(
     Ok(())
 }
 
-fn tick_worker(
+/// Flips the observed mute state without touching Discord, for exercising alerts and the tray.
+/// The next observation of the real button overwrites it.
+fn handle_ipc_simulate_mute_toggle(
+    mut messages: EventReader<IpcWorkerGameboundMessage>,
+    mut mute_button: Query<&mut MuteButtonState>,
+    mut commands: Commands,
+) {
+    for msg in messages.read() {
+        if let IpcWorkerGameboundMessage::MessageReceived(BevyboundIPCMessage::SimulateMuteToggle) =
+            msg
+        {
+            if let Ok(mut state) = mute_button.single_mut() {
+                let simulated = match *state {
+                    MuteButtonState::Muted => MuteButtonState::NotMuted,
+                    MuteButtonState::NotMuted => MuteButtonState::Muted,
+                };
+                info!(
+                    "Simulating mute toggle from {:?} to {:?}",
+                    *state, simulated
+                );
+                *state = simulated;
+            } else {
+                info!("Simulating mute toggle with no mute button observed yet, spawning Muted");
+                commands.spawn((MuteButtonState::Muted, Name::new(MUTE_BUTTON_NAME)));
+            }
+        }
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut tick_control: EventWriter<WorkerTickControl<UIWorkerThreadboundMessage>>,
     mut threadbound_messages: EventWriter<UIWorkerThreadboundMessage>,
-    mut config: ResMut<UIAutomationPluginConfig>,
-    time: Res<Time>,
 ) {
-    config.refresh_interval.tick(time.delta());
-    if !config.refresh_interval.just_finished() {
-        return;
+    tick_control.write(WorkerTickControl::set_interval(
+        settings.ui_automation.refresh_interval(),
+    ));
+    threadbound_messages.write(UIWorkerThreadboundMessage::UseApps {
+        apps: settings.ui_automation.apps.clone(),
+    });
+}
+
+fn apply_changed_settings(
+    mut events: EventReader<SettingsChanged>,
+    settings: Res<Settings>,
+    tick_control: EventWriter<WorkerTickControl<UIWorkerThreadboundMessage>>,
+    threadbound_messages: EventWriter<UIWorkerThreadboundMessage>,
+) {
+    if events
+        .read()
+        .any(|event| event.section == SettingsSection::UIAutomation)
+    {
+        apply_settings(settings, tick_control, threadbound_messages);
     }
-    threadbound_messages.write(UIWorkerThreadboundMessage::DetectMuteButtonState);
 }
 
 fn startup_fetch(mut threadbound_messages: EventWriter<UIWorkerThreadboundMessage>) {
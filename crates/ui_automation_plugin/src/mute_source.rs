@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use std::sync::Arc;
+use ymb_ui_automation::MuteButtonState;
+
+/// How much a [`MuteReading`] can be trusted, readings with more confidence win.
+#[derive(Debug, Reflect, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MuteConfidence {
+    /// A fallback, like assuming unmuted after failing to read the control.
+    Low,
+    /// Inferred, like from the label of a button.
+    Medium,
+    /// Read straight from the control's state.
+    High,
+}
+
+/// Also kept on the mute button entity, as the reading its state came from.
+#[derive(Debug, Component, Reflect, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct MuteReading {
+    /// The app the reading came from, like "Discord".
+    pub app: String,
+    /// `None` when the source could not tell.
+    pub state: Option<MuteButtonState>,
+    pub confidence: MuteConfidence,
+}
+
+/// Somewhere the mute state can be read from, like one app's mute button.
+///
+/// Sources are created and used on the UI automation worker thread.
+pub trait MuteSource {
+    /// The app this source reads.
+    fn app(&self) -> &str;
+
+    /// Looks for the app's mute control, succeeding once [`Self::current_state`] can read it.
+    fn probe(&mut self) -> eyre::Result<()>;
+
+    /// Reads the mute state, only called after a successful [`Self::probe`].
+    fn current_state(&mut self) -> MuteReading;
+
+    /// Called with the new reading whenever it differs from the one before.
+    fn on_change(&mut self, reading: &MuteReading) {
+        debug!("[{}] Mute reading changed to {:?}", self.app(), reading);
+    }
+}
+
+/// Creates a [`MuteSource`] on the worker thread, again after every worker restart.
+pub type MuteSourceFactory = Arc<dyn Fn() -> eyre::Result<Box<dyn MuteSource>> + Send + Sync>;
+
+/// The sources [`crate::UIAutomationPlugin`] reads, in order of preference when they are equally confident.
+#[derive(Clone, Default)]
+pub struct MuteSourceRegistry {
+    factories: Vec<MuteSourceFactory>,
+}
+impl MuteSourceRegistry {
+    pub fn with_source<M: MuteSource + 'static>(
+        mut self,
+        create: impl Fn() -> eyre::Result<M> + Send + Sync + 'static,
+    ) -> Self {
+        self.factories.push(Arc::new(move || {
+            create().map(|source| Box::new(source) as Box<dyn MuteSource>)
+        }));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.factories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factories.is_empty()
+    }
+
+    /// Creates every source, leaving out the ones that fail.
+    pub fn create(&self) -> MuteSources {
+        let sources = self
+            .factories
+            .iter()
+            .filter_map(|create| match create() {
+                Ok(source) => Some(source),
+                Err(e) => {
+                    warn!("Failed to create mute source: {:?}", e);
+                    None
+                }
+            })
+            .map(|source| (source, None))
+            .collect();
+        MuteSources { sources }
+    }
+}
+impl std::fmt::Debug for MuteSourceRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MuteSourceRegistry")
+            .field("sources", &self.factories.len())
+            .finish()
+    }
+}
+
+/// The created sources with their last readings.
+pub struct MuteSources {
+    sources: Vec<(Box<dyn MuteSource>, Option<MuteReading>)>,
+}
+impl MuteSources {
+    /// Reads every source that probes successfully, returning the most confident reading that knows the state.
+    pub fn read(&mut self) -> Option<MuteReading> {
+        let mut best: Option<MuteReading> = None;
+        for (source, last) in &mut self.sources {
+            if let Err(e) = source.probe() {
+                debug!("[{}] Mute source not available: {:?}", source.app(), e);
+                continue;
+            }
+            let reading = source.current_state();
+            if last.as_ref() != Some(&reading) {
+                source.on_change(&reading);
+                *last = Some(reading.clone());
+            }
+            if reading.state.is_some()
+                && best
+                    .as_ref()
+                    .is_none_or(|best| reading.confidence > best.confidence)
+            {
+                best = Some(reading);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::MuteConfidence;
+    use crate::MuteScript;
+    use crate::MuteSourceRegistry;
+    use ymb_ui_automation::MuteButtonState;
+
+    #[test]
+    fn the_most_confident_source_wins() {
+        let guess = MuteScript::new("Guess");
+        guess.push(Some(MuteButtonState::Muted), MuteConfidence::Low);
+        let sure = MuteScript::new("Sure");
+        sure.push(Some(MuteButtonState::NotMuted), MuteConfidence::High);
+        let clueless = MuteScript::new("Clueless");
+        clueless.push(None, MuteConfidence::High);
+        let mut sources = MuteSourceRegistry::default()
+            .with_source(guess.factory())
+            .with_source(sure.factory())
+            .with_source(clueless.factory())
+            .create();
+
+        let best = sources.read().unwrap();
+        assert_eq!(best.app, "Sure");
+        assert_eq!(best.state, Some(MuteButtonState::NotMuted));
+
+        sure.set_available(false);
+        assert_eq!(sources.read().unwrap().app, "Guess");
+    }
+
+    #[test]
+    fn changes_are_reported_once() {
+        let script = MuteScript::new("Scripted");
+        script.push(Some(MuteButtonState::Muted), MuteConfidence::High);
+        let mut sources = MuteSourceRegistry::default()
+            .with_source(script.factory())
+            .create();
+        sources.read();
+        sources.read();
+        script.push(Some(MuteButtonState::NotMuted), MuteConfidence::High);
+        sources.read();
+        let changes: Vec<_> = script
+            .changes()
+            .into_iter()
+            .map(|reading| reading.state)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                Some(MuteButtonState::Muted),
+                Some(MuteButtonState::NotMuted)
+            ]
+        );
+    }
+}
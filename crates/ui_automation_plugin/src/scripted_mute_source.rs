@@ -0,0 +1,100 @@
+use crate::MuteConfidence;
+use crate::MuteReading;
+use crate::MuteSource;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use ymb_ui_automation::MuteButtonState;
+
+/// Drives a fake [`MuteSource`] from a test, so the plugin can be exercised without any real app.
+///
+/// Clones share the script, so it can be changed while the worker reads from it.
+#[derive(Clone)]
+pub struct MuteScript {
+    app: String,
+    script: Arc<Mutex<Script>>,
+}
+
+struct Script {
+    available: bool,
+    /// Read front to back, one per read.
+    readings: VecDeque<MuteReading>,
+    /// Read again once the queue runs out.
+    last: Option<MuteReading>,
+    changes: Vec<MuteReading>,
+}
+
+impl MuteScript {
+    pub fn new(app: impl Into<String>) -> Self {
+        Self {
+            app: app.into(),
+            script: Arc::new(Mutex::new(Script {
+                available: true,
+                readings: VecDeque::new(),
+                last: None,
+                changes: Vec::new(),
+            })),
+        }
+    }
+
+    /// Queues a reading, after the ones before it.
+    ///
+    /// Each read takes the next queued reading, repeating the last one once there are none left.
+    pub fn push(&self, state: Option<MuteButtonState>, confidence: MuteConfidence) {
+        let reading = MuteReading {
+            app: self.app.clone(),
+            state,
+            confidence,
+        };
+        self.lock().readings.push_back(reading);
+    }
+
+    /// Whether probing succeeds, like the app being open.
+    pub fn set_available(&self, available: bool) {
+        self.lock().available = available;
+    }
+
+    /// Every reading the source was told changed, oldest first.
+    pub fn changes(&self) -> Vec<MuteReading> {
+        self.lock().changes.clone()
+    }
+
+    /// For [`crate::MuteSourceRegistry::with_source`].
+    pub fn factory(&self) -> impl Fn() -> eyre::Result<ScriptedMuteSource> + Send + Sync + 'static {
+        let script = self.clone();
+        move || Ok(ScriptedMuteSource(script.clone()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub struct ScriptedMuteSource(MuteScript);
+
+impl MuteSource for ScriptedMuteSource {
+    fn app(&self) -> &str {
+        &self.0.app
+    }
+
+    fn probe(&mut self) -> eyre::Result<()> {
+        eyre::ensure!(self.0.lock().available, "{} is not available", self.0.app);
+        Ok(())
+    }
+
+    fn current_state(&mut self) -> MuteReading {
+        let mut script = self.0.lock();
+        if let Some(next) = script.readings.pop_front() {
+            script.last = Some(next);
+        }
+        script.last.clone().unwrap_or(MuteReading {
+            app: self.0.app.clone(),
+            state: None,
+            confidence: MuteConfidence::Low,
+        })
+    }
+
+    fn on_change(&mut self, reading: &MuteReading) {
+        self.0.lock().changes.push(reading.clone());
+    }
+}
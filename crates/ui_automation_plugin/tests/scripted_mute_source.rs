@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use std::time::Instant;
+use ymb_ipc_plugin::IpcWorkerGameboundMessage;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_ui_automation::MuteButtonState;
+use ymb_ui_automation_plugin::MuteConfidence;
+use ymb_ui_automation_plugin::MuteReading;
+use ymb_ui_automation_plugin::MuteScript;
+use ymb_ui_automation_plugin::MuteSourceRegistry;
+use ymb_ui_automation_plugin::UIAutomationPlugin;
+use ymb_ui_automation_plugin::UIWorkerThreadboundMessage;
+
+fn app(sources: MuteSourceRegistry) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(Settings::default());
+    app.add_event::<SettingsChanged>();
+    app.add_event::<IpcWorkerGameboundMessage>();
+    app.add_plugins(UIAutomationPlugin { sources });
+    app
+}
+
+/// Updates until the mute button entity matches, its state changes on the worker thread.
+fn wait_for_mute_button(app: &mut App, state: MuteButtonState) -> MuteReading {
+    let started = Instant::now();
+    while started.elapsed() < Duration::from_secs(5) {
+        app.update();
+        let mut query = app.world_mut().query::<(&MuteButtonState, &MuteReading)>();
+        if let Ok((current, reading)) = query.single(app.world())
+            && *current == state
+        {
+            return reading.clone();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("the mute button never became {state:?}");
+}
+
+#[test]
+fn scripted_sources_drive_the_mute_button() {
+    let fallback = MuteScript::new("Fallback");
+    fallback.push(Some(MuteButtonState::NotMuted), MuteConfidence::Low);
+    let fake = MuteScript::new("Fake");
+    fake.push(Some(MuteButtonState::Muted), MuteConfidence::High);
+    let mut app = app(MuteSourceRegistry::default()
+        .with_source(fallback.factory())
+        .with_source(fake.factory()));
+
+    let reading = wait_for_mute_button(&mut app, MuteButtonState::Muted);
+    assert_eq!(reading.app, "Fake");
+    assert_eq!(reading.confidence, MuteConfidence::High);
+
+    // Once the fake goes away, the fallback is all there is
+    fake.set_available(false);
+    app.world_mut()
+        .send_event(UIWorkerThreadboundMessage::DetectMuteButtonState);
+    let reading = wait_for_mute_button(&mut app, MuteButtonState::NotMuted);
+    assert_eq!(reading.app, "Fallback");
+}
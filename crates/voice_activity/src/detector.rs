@@ -0,0 +1,334 @@
+use crate::AudioFrame;
+use crate::AudioSource;
+use bevy_reflect::Reflect;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Thresholds for the energy plus zero-crossing detector.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    /// Frames quieter than this are never speech.
+    pub energy_threshold_dbfs: f32,
+    /// Frames crossing zero less often than this are treated as hum.
+    pub min_zero_crossing_rate_hz: f32,
+    /// Frames crossing zero more often than this are treated as hiss.
+    pub max_zero_crossing_rate_hz: f32,
+    /// How long speech-like frames must persist before activity starts.
+    pub attack_ms: u64,
+    /// How long activity is held after the last speech-like frame.
+    pub hangover_ms: u64,
+}
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            energy_threshold_dbfs: -45.0,
+            min_zero_crossing_rate_hz: 150.0,
+            max_zero_crossing_rate_hz: 5_000.0,
+            attack_ms: 60,
+            hangover_ms: 300,
+        }
+    }
+}
+impl VadConfig {
+    const LOUDEST_THRESHOLD_DBFS: f32 = -20.0;
+    const SENSITIVITY_RANGE_DB: f32 = 50.0;
+
+    pub fn attack(&self) -> Duration {
+        Duration::from_millis(self.attack_ms)
+    }
+    pub fn hangover(&self) -> Duration {
+        Duration::from_millis(self.hangover_ms)
+    }
+    /// Sensitivity in `[0.0, 1.0]`, where higher values trigger on quieter speech.
+    pub fn sensitivity(&self) -> f32 {
+        ((Self::LOUDEST_THRESHOLD_DBFS - self.energy_threshold_dbfs) / Self::SENSITIVITY_RANGE_DB)
+            .clamp(0.0, 1.0)
+    }
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.energy_threshold_dbfs =
+            Self::LOUDEST_THRESHOLD_DBFS - sensitivity.clamp(0.0, 1.0) * Self::SENSITIVITY_RANGE_DB;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum VoiceActivityEvent {
+    VoiceActivityStarted {
+        /// Source time of the first speech-like frame.
+        at: Duration,
+    },
+    VoiceActivityEnded {
+        /// Source time of the end of the last speech-like frame.
+        at: Duration,
+        duration: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameAnalysis {
+    pub level_dbfs: f32,
+    pub zero_crossing_rate_hz: f32,
+    pub is_speech_like: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameOutcome {
+    pub analysis: FrameAnalysis,
+    pub event: Option<VoiceActivityEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DetectorState {
+    Idle {
+        candidate_since: Option<Duration>,
+    },
+    Active {
+        started_at: Duration,
+        last_speech_at: Duration,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct VoiceActivityDetector {
+    config: VadConfig,
+    state: DetectorState,
+}
+impl VoiceActivityDetector {
+    pub fn new(config: VadConfig) -> Self {
+        Self {
+            config,
+            state: DetectorState::Idle {
+                candidate_since: None,
+            },
+        }
+    }
+    pub fn config(&self) -> &VadConfig {
+        &self.config
+    }
+    pub fn config_mut(&mut self) -> &mut VadConfig {
+        &mut self.config
+    }
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, DetectorState::Active { .. })
+    }
+    /// Forgets any in-progress activity without emitting an end event.
+    pub fn reset(&mut self) {
+        self.state = DetectorState::Idle {
+            candidate_since: None,
+        };
+    }
+    pub fn analyze(&self, frame: &AudioFrame) -> FrameAnalysis {
+        let level_dbfs = frame.level_dbfs();
+        let zero_crossing_rate_hz = frame.zero_crossing_rate_hz();
+        let is_speech_like = level_dbfs >= self.config.energy_threshold_dbfs
+            && zero_crossing_rate_hz >= self.config.min_zero_crossing_rate_hz
+            && zero_crossing_rate_hz <= self.config.max_zero_crossing_rate_hz;
+        FrameAnalysis {
+            level_dbfs,
+            zero_crossing_rate_hz,
+            is_speech_like,
+        }
+    }
+    pub fn process(&mut self, frame: &AudioFrame) -> FrameOutcome {
+        let analysis = self.analyze(frame);
+        let mut event = None;
+        match &mut self.state {
+            DetectorState::Idle { candidate_since } => {
+                if analysis.is_speech_like {
+                    let since = *candidate_since.get_or_insert(frame.start);
+                    if frame.end().saturating_sub(since) >= self.config.attack() {
+                        self.state = DetectorState::Active {
+                            started_at: since,
+                            last_speech_at: frame.end(),
+                        };
+                        event = Some(VoiceActivityEvent::VoiceActivityStarted { at: since });
+                    }
+                } else {
+                    *candidate_since = None;
+                }
+            }
+            DetectorState::Active {
+                started_at,
+                last_speech_at,
+            } => {
+                if analysis.is_speech_like {
+                    *last_speech_at = frame.end();
+                } else if frame.end().saturating_sub(*last_speech_at) >= self.config.hangover() {
+                    event = Some(VoiceActivityEvent::VoiceActivityEnded {
+                        at: *last_speech_at,
+                        duration: last_speech_at.saturating_sub(*started_at),
+                    });
+                    self.state = DetectorState::Idle {
+                        candidate_since: None,
+                    };
+                }
+            }
+        }
+        FrameOutcome { analysis, event }
+    }
+}
+
+/// Runs a finite source through the detector, returning every event produced.
+pub fn detect_all(
+    source: &mut impl AudioSource,
+    detector: &mut VoiceActivityDetector,
+) -> eyre::Result<Vec<VoiceActivityEvent>> {
+    let mut events = Vec::new();
+    while !source.is_finished() {
+        let Some(frame) = source.next_frame()? else {
+            break;
+        };
+        if let Some(event) = detector.process(&frame).event {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Signal;
+    use crate::SyntheticSource;
+    use crate::VadConfig;
+    use crate::VoiceActivityDetector;
+    use crate::VoiceActivityEvent;
+    use crate::WavFileSource;
+    use crate::detect_all;
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    const SAMPLE_RATE: u32 = 16_000;
+    const FRAME: Duration = Duration::from_millis(20);
+    const VOICE: Signal = Signal::Sine {
+        frequency: 220.0,
+        amplitude: 0.3,
+    };
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn detect(source: SyntheticSource) -> Vec<VoiceActivityEvent> {
+        let mut source = source;
+        let mut detector = VoiceActivityDetector::new(VadConfig::default());
+        detect_all(&mut source, &mut detector).unwrap()
+    }
+
+    #[test]
+    fn silence_is_not_speech() {
+        let events =
+            detect(SyntheticSource::new(SAMPLE_RATE, FRAME).then(Signal::Silence, ms(2000)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn tone_between_silence_starts_and_ends() {
+        let events = detect(
+            SyntheticSource::new(SAMPLE_RATE, FRAME)
+                .then(Signal::Silence, ms(500))
+                .then(VOICE, ms(1000))
+                .then(Signal::Silence, ms(1000)),
+        );
+        assert_eq!(
+            events,
+            vec![
+                VoiceActivityEvent::VoiceActivityStarted { at: ms(500) },
+                VoiceActivityEvent::VoiceActivityEnded {
+                    at: ms(1500),
+                    duration: ms(1000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn blip_shorter_than_attack_is_ignored() {
+        let events = detect(
+            SyntheticSource::new(SAMPLE_RATE, FRAME)
+                .then(Signal::Silence, ms(200))
+                .then(VOICE, ms(40))
+                .then(Signal::Silence, ms(500)),
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn pause_within_hangover_is_one_utterance() {
+        let events = detect(
+            SyntheticSource::new(SAMPLE_RATE, FRAME)
+                .then(VOICE, ms(400))
+                .then(Signal::Silence, ms(200))
+                .then(VOICE, ms(400))
+                .then(Signal::Silence, ms(500)),
+        );
+        assert_eq!(events.len(), 2, "{events:?}");
+    }
+
+    #[test]
+    fn hiss_and_quiet_tone_are_rejected() {
+        let events = detect(
+            SyntheticSource::new(SAMPLE_RATE, FRAME)
+                .then(Signal::WhiteNoise { amplitude: 0.5 }, ms(1000))
+                .then(
+                    Signal::Sine {
+                        frequency: 220.0,
+                        amplitude: 0.001,
+                    },
+                    ms(1000),
+                ),
+        );
+        assert!(events.is_empty(), "{events:?}");
+    }
+
+    #[test]
+    fn sensitivity_round_trips_through_threshold() {
+        let mut config = VadConfig::default();
+        assert!((config.sensitivity() - 0.5).abs() < 0.001);
+        config.set_sensitivity(1.0);
+        assert_eq!(config.energy_threshold_dbfs, -70.0);
+        config.set_sensitivity(0.0);
+        assert_eq!(config.energy_threshold_dbfs, -20.0);
+    }
+
+    #[test]
+    fn wav_file_source() -> eyre::Result<()> {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut bytes = Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut bytes, spec)?;
+            let mut tone = SyntheticSource::new(SAMPLE_RATE, FRAME)
+                .then(Signal::Silence, ms(300))
+                .then(VOICE, ms(600))
+                .then(Signal::Silence, ms(600));
+            while let Some(frame) = crate::AudioSource::next_frame(&mut tone)? {
+                for sample in frame.samples {
+                    let sample = (sample * i16::MAX as f32) as i16;
+                    writer.write_sample(sample)?;
+                    writer.write_sample(sample)?;
+                }
+            }
+            writer.finalize()?;
+        }
+        bytes.set_position(0);
+        let mut source = WavFileSource::from_reader(bytes, FRAME)?;
+        let mut detector = VoiceActivityDetector::new(VadConfig::default());
+        let events = detect_all(&mut source, &mut detector)?;
+        assert_eq!(
+            events,
+            vec![
+                VoiceActivityEvent::VoiceActivityStarted { at: ms(300) },
+                VoiceActivityEvent::VoiceActivityEnded {
+                    at: ms(900),
+                    duration: ms(600),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+/// Quietest level reported for a frame, used instead of negative infinity for digital silence.
+pub const SILENCE_DBFS: f32 = -100.0;
+
+/// A block of mono PCM samples normalised to `[-1.0, 1.0]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    /// Offset of the first sample from the start of the source.
+    pub start: Duration,
+}
+impl AudioFrame {
+    pub fn duration(&self) -> Duration {
+        samples_to_duration(self.samples.len() as u64, self.sample_rate)
+    }
+    pub fn end(&self) -> Duration {
+        self.start + self.duration()
+    }
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let sum_of_squares: f32 = self.samples.iter().map(|x| x * x).sum();
+        (sum_of_squares / self.samples.len() as f32).sqrt()
+    }
+    /// Root mean square level relative to full scale, clamped to [`SILENCE_DBFS`].
+    pub fn level_dbfs(&self) -> f32 {
+        let rms = self.rms();
+        if rms <= 0.0 {
+            return SILENCE_DBFS;
+        }
+        (20.0 * rms.log10()).max(SILENCE_DBFS)
+    }
+    /// Sign changes per second.
+    ///
+    /// Expressed in hertz rather than per-sample so thresholds hold across sample rates.
+    pub fn zero_crossing_rate_hz(&self) -> f32 {
+        let duration = self.duration().as_secs_f32();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        let crossings = self
+            .samples
+            .windows(2)
+            .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+            .count();
+        crossings as f32 / duration
+    }
+}
+
+pub fn samples_to_duration(samples: u64, sample_rate: u32) -> Duration {
+    if sample_rate == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(samples as f64 / sample_rate as f64)
+}
+
+pub fn duration_to_samples(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
+}
+
+/// Averages interleaved channels into a single mono channel.
+pub fn downmix_interleaved(samples: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(channels as usize)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect()
+}
+
+/// Slices an arbitrary stream of mono samples into fixed length [`AudioFrame`]s.
+#[derive(Debug, Clone)]
+pub struct Framer {
+    sample_rate: u32,
+    frame_len: usize,
+    pending: Vec<f32>,
+    emitted_samples: u64,
+}
+impl Framer {
+    pub fn new(sample_rate: u32, frame_duration: Duration) -> Self {
+        Self {
+            sample_rate,
+            frame_len: duration_to_samples(frame_duration, sample_rate).max(1),
+            pending: Vec::new(),
+            emitted_samples: 0,
+        }
+    }
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+    pub fn push(&mut self, samples: &[f32]) {
+        self.pending.extend_from_slice(samples);
+    }
+    pub fn next_frame(&mut self) -> Option<AudioFrame> {
+        if self.pending.len() < self.frame_len {
+            return None;
+        }
+        let rest = self.pending.split_off(self.frame_len);
+        let samples = std::mem::replace(&mut self.pending, rest);
+        Some(self.emit(samples))
+    }
+    /// Emits whatever is left as a short final frame.
+    pub fn flush(&mut self) -> Option<AudioFrame> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let samples = std::mem::take(&mut self.pending);
+        Some(self.emit(samples))
+    }
+    /// Discards pending samples and restarts the timeline at zero.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.emitted_samples = 0;
+    }
+    fn emit(&mut self, samples: Vec<f32>) -> AudioFrame {
+        let start = samples_to_duration(self.emitted_samples, self.sample_rate);
+        self.emitted_samples += samples.len() as u64;
+        AudioFrame {
+            samples,
+            sample_rate: self.sample_rate,
+            start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::AudioFrame;
+    use crate::Framer;
+    use crate::SILENCE_DBFS;
+    use crate::downmix_interleaved;
+    use std::time::Duration;
+
+    #[test]
+    fn framer_emits_fixed_frames_with_timestamps() {
+        let mut framer = Framer::new(1000, Duration::from_millis(10));
+        framer.push(&[0.0; 25]);
+        let first = framer.next_frame().unwrap();
+        let second = framer.next_frame().unwrap();
+        assert_eq!(first.samples.len(), 10);
+        assert_eq!(first.start, Duration::ZERO);
+        assert_eq!(second.start, Duration::from_millis(10));
+        assert!(framer.next_frame().is_none());
+        let last = framer.flush().unwrap();
+        assert_eq!(last.samples.len(), 5);
+        assert_eq!(last.start, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn silence_is_clamped() {
+        let frame = AudioFrame {
+            samples: vec![0.0; 100],
+            sample_rate: 1000,
+            start: Duration::ZERO,
+        };
+        assert_eq!(frame.level_dbfs(), SILENCE_DBFS);
+        assert_eq!(frame.zero_crossing_rate_hz(), 0.0);
+    }
+
+    #[test]
+    fn full_scale_square_wave() {
+        // 4 samples per period at 1 kHz is a 250 Hz square wave, which crosses zero 500 times a second
+        let samples = [1.0, 1.0, -1.0, -1.0].repeat(250);
+        let frame = AudioFrame {
+            samples,
+            sample_rate: 1000,
+            start: Duration::ZERO,
+        };
+        assert!(frame.level_dbfs().abs() < 0.01);
+        assert!((frame.zero_crossing_rate_hz() - 500.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn downmix_averages_channels() {
+        assert_eq!(
+            downmix_interleaved(&[1.0, 0.0, 0.5, 0.5], 2),
+            vec![0.5, 0.5]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+mod detector;
+mod frame;
+mod source;
+mod synthetic_source;
+mod wav_source;
+
+pub use detector::*;
+pub use frame::*;
+pub use source::*;
+pub use synthetic_source::*;
+pub use wav_source::*;
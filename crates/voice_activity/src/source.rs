@@ -0,0 +1,29 @@
+use crate::AudioFrame;
+
+/// Something that produces mono [`AudioFrame`]s, such as a microphone, a file or a test signal.
+pub trait AudioSource {
+    /// Human readable name, used for logging and mic selection.
+    fn name(&self) -> &str;
+    fn sample_rate(&self) -> u32;
+    /// Returns the next frame, or `None` when no complete frame is available yet.
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>>;
+    /// Finite sources return `true` once every frame has been produced.
+    fn is_finished(&self) -> bool {
+        false
+    }
+}
+
+impl<T: AudioSource + ?Sized> AudioSource for Box<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+    fn sample_rate(&self) -> u32 {
+        (**self).sample_rate()
+    }
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>> {
+        (**self).next_frame()
+    }
+    fn is_finished(&self) -> bool {
+        (**self).is_finished()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::AudioFrame;
+use crate::AudioSource;
+use crate::Framer;
+use crate::duration_to_samples;
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    Silence,
+    Sine { frequency: f32, amplitude: f32 },
+    WhiteNoise { amplitude: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalSegment {
+    pub signal: Signal,
+    pub duration: Duration,
+}
+
+/// Generates a scripted sequence of signals, useful for exercising detection thresholds without a capture device.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use ymb_voice_activity::Signal;
+/// # use ymb_voice_activity::SyntheticSource;
+/// let source = SyntheticSource::new(16_000, Duration::from_millis(20))
+///     .then(Signal::Silence, Duration::from_millis(500))
+///     .then(Signal::Sine { frequency: 220.0, amplitude: 0.3 }, Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SyntheticSource {
+    name: String,
+    framer: Framer,
+    segments: VecDeque<(Signal, usize)>,
+    phase: f32,
+    noise_state: u32,
+}
+impl SyntheticSource {
+    pub fn new(sample_rate: u32, frame_duration: Duration) -> Self {
+        Self {
+            name: "Synthetic".to_string(),
+            framer: Framer::new(sample_rate, frame_duration),
+            segments: VecDeque::new(),
+            phase: 0.0,
+            noise_state: 0x9E37_79B9,
+        }
+    }
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+    pub fn then(mut self, signal: Signal, duration: Duration) -> Self {
+        self.push(SignalSegment { signal, duration });
+        self
+    }
+    pub fn push(&mut self, segment: SignalSegment) {
+        let samples = duration_to_samples(segment.duration, self.framer.sample_rate());
+        self.segments.push_back((segment.signal, samples));
+    }
+    fn generate(&mut self, mut count: usize) {
+        let sample_rate = self.framer.sample_rate() as f32;
+        let mut buffer = Vec::with_capacity(count);
+        while count > 0 {
+            let Some((signal, remaining)) = self.segments.front_mut() else {
+                break;
+            };
+            let take = count.min(*remaining);
+            for _ in 0..take {
+                let sample = match signal {
+                    Signal::Silence => 0.0,
+                    Signal::Sine {
+                        frequency,
+                        amplitude,
+                    } => {
+                        self.phase = (self.phase + TAU * *frequency / sample_rate) % TAU;
+                        self.phase.sin() * *amplitude
+                    }
+                    Signal::WhiteNoise { amplitude } => {
+                        // xorshift32, deterministic so tests are repeatable
+                        self.noise_state ^= self.noise_state << 13;
+                        self.noise_state ^= self.noise_state >> 17;
+                        self.noise_state ^= self.noise_state << 5;
+                        let unit = self.noise_state as f32 / u32::MAX as f32;
+                        (unit * 2.0 - 1.0) * *amplitude
+                    }
+                };
+                buffer.push(sample);
+            }
+            *remaining -= take;
+            count -= take;
+            if *remaining == 0 {
+                self.segments.pop_front();
+            }
+        }
+        self.framer.push(&buffer);
+    }
+}
+impl AudioSource for SyntheticSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sample_rate(&self) -> u32 {
+        self.framer.sample_rate()
+    }
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>> {
+        let needed = self
+            .framer
+            .frame_len()
+            .saturating_sub(self.framer.pending_len());
+        self.generate(needed);
+        if let Some(frame) = self.framer.next_frame() {
+            return Ok(Some(frame));
+        }
+        Ok(self.framer.flush())
+    }
+    fn is_finished(&self) -> bool {
+        self.segments.is_empty() && self.framer.pending_len() == 0
+    }
+}
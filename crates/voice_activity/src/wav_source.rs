@@ -0,0 +1,69 @@
+use crate::AudioFrame;
+use crate::AudioSource;
+use crate::Framer;
+use crate::downmix_interleaved;
+use eyre::Context;
+use hound::SampleFormat;
+use hound::WavReader;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+/// Plays back a WAV file as a finite [`AudioSource`], downmixed to mono.
+#[derive(Debug, Clone)]
+pub struct WavFileSource {
+    name: String,
+    framer: Framer,
+}
+impl WavFileSource {
+    pub fn open(path: impl AsRef<Path>, frame_duration: Duration) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let reader = WavReader::open(path)
+            .wrap_err_with(|| format!("Failed to open WAV file {}", path.display()))?;
+        let mut source = Self::from_wav_reader(reader, frame_duration)?;
+        source.name = path.display().to_string();
+        Ok(source)
+    }
+    pub fn from_reader(reader: impl Read, frame_duration: Duration) -> eyre::Result<Self> {
+        Self::from_wav_reader(WavReader::new(reader)?, frame_duration)
+    }
+    fn from_wav_reader<R: Read>(
+        reader: WavReader<R>,
+        frame_duration: Duration,
+    ) -> eyre::Result<Self> {
+        let spec = reader.spec();
+        let interleaved: Vec<f32> = match spec.sample_format {
+            SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+            SampleFormat::Int => {
+                let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.map(|x| x as f32 / scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+        let mut framer = Framer::new(spec.sample_rate, frame_duration);
+        framer.push(&downmix_interleaved(&interleaved, spec.channels));
+        Ok(Self {
+            name: "WAV".to_string(),
+            framer,
+        })
+    }
+}
+impl AudioSource for WavFileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sample_rate(&self) -> u32 {
+        self.framer.sample_rate()
+    }
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>> {
+        if let Some(frame) = self.framer.next_frame() {
+            return Ok(Some(frame));
+        }
+        Ok(self.framer.flush())
+    }
+    fn is_finished(&self) -> bool {
+        self.framer.pending_len() == 0
+    }
+}
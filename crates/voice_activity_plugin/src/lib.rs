@@ -0,0 +1,318 @@
+mod microphone_source;
+
+pub use microphone_source::*;
+
+use bevy::prelude::*;
+use crossbeam_channel::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::VoiceActivitySettings;
+use ymb_voice_activity::AudioSource;
+use ymb_voice_activity::SILENCE_DBFS;
+use ymb_voice_activity::VadConfig;
+use ymb_voice_activity::VoiceActivityDetector;
+use ymb_voice_activity::VoiceActivityEvent;
+use ymb_worker_plugin::Backpressure;
+use ymb_worker_plugin::Sender;
+use ymb_worker_plugin::WorkerPlugin;
+use ymb_worker_plugin::WorkerStateTrait;
+
+/// How much audio the detector looks at at once, sources are opened with it.
+pub const FRAME_DURATION: Duration = Duration::from_millis(20);
+const LEVEL_PUBLISH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Opens the source for a microphone name, or for the default microphone when `None`.
+///
+/// Called on the worker thread whenever the microphone is selected or capture resumes after an error.
+pub type AudioSourceFactory =
+    Arc<dyn Fn(Option<&str>) -> eyre::Result<Box<dyn AudioSource>> + Send + Sync>;
+
+/// Captures from the microphone in the settings, through [`Self::open_source`].
+pub struct VoiceActivityPlugin {
+    pub open_source: AudioSourceFactory,
+}
+impl Default for VoiceActivityPlugin {
+    fn default() -> Self {
+        Self {
+            open_source: Arc::new(|name: Option<&str>| -> eyre::Result<Box<dyn AudioSource>> {
+                Ok(Box::new(MicrophoneSource::open(name, FRAME_DURATION)?))
+            }),
+        }
+    }
+}
+
+impl Plugin for VoiceActivityPlugin {
+    fn build(&self, app: &mut App) {
+        let open_source = self.open_source.clone();
+        app.add_plugins(
+            WorkerPlugin::<
+                VoiceActivityThreadboundMessage,
+                VoiceActivityGameboundMessage,
+                VoiceActivityWorkerState,
+            >::builder("VoiceActivityWorker")
+            .on_message(move |msg, reply_tx, state| {
+                handle_threadbound_message(msg, reply_tx, state, &open_source)
+            })
+            .on_error(handle_threadbound_message_error_handler)
+            .receiver(|thread_rx, state| {
+                if !state.is_capturing() {
+                    return thread_rx.recv().map_err(BevyError::from);
+                }
+                // Wake up once per frame to drain the microphone when nothing else is queued
+                match thread_rx.recv_timeout(FRAME_DURATION) {
+                    Ok(msg) => Ok(msg),
+                    Err(RecvTimeoutError::Timeout) => {
+                        Ok(VoiceActivityThreadboundMessage::ProcessAudio)
+                    }
+                    Err(e) => Err(BevyError::from(e)),
+                }
+            })
+            .gamebound_channel_capacity(64)
+            // Dragging the sensitivity slider sends a burst, only the last one matters
+            .threadbound_backpressure(Backpressure::coalesce_by(
+                |msg: &VoiceActivityThreadboundMessage| {
+                    matches!(msg, VoiceActivityThreadboundMessage::SetSensitivity(_)).then_some(())
+                },
+            ))
+            // Levels are published constantly, so a slow app keeps only the newest instead of stalling capture
+            .gamebound_backpressure(Backpressure::coalesce_by(
+                |msg: &VoiceActivityGameboundMessage| {
+                    matches!(msg, VoiceActivityGameboundMessage::Level { .. }).then_some(())
+                },
+            ))
+            .build(),
+        );
+        app.register_type::<VoiceActivity>();
+        app.register_type::<VadConfig>();
+        app.add_systems(Startup, spawn_voice_activity);
+        app.add_systems(
+            Update,
+            apply_voice_activity_settings.run_if(run_once.or(on_event::<SettingsChanged>)),
+        );
+        app.add_systems(Update, handle_gamebound_messages);
+    }
+}
+
+/// Live speech state of the selected microphone.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct VoiceActivity {
+    pub is_speaking: bool,
+    pub is_paused: bool,
+    pub level_dbfs: f32,
+    pub source: Option<String>,
+}
+impl Default for VoiceActivity {
+    fn default() -> Self {
+        Self {
+            is_speaking: false,
+            is_paused: false,
+            level_dbfs: SILENCE_DBFS,
+            source: None,
+        }
+    }
+}
+
+#[derive(Debug, Reflect, Clone, Event)]
+pub enum VoiceActivityThreadboundMessage {
+    /// Switch to the input device with this friendly name, or the default device when `None`.
+    SelectMic {
+        name: Option<String>,
+    },
+    Pause,
+    Resume,
+    /// See [`VadConfig::set_sensitivity`].
+    SetSensitivity(f32),
+    Configure(VadConfig),
+    /// Sent by the worker to itself to drain captured audio.
+    ProcessAudio,
+}
+
+#[derive(Debug, Reflect, Clone, Event)]
+pub enum VoiceActivityGameboundMessage {
+    Activity(VoiceActivityEvent),
+    Level { level_dbfs: f32 },
+    SourceChanged { name: String },
+    Paused,
+    Resumed,
+    Error(String),
+}
+
+pub struct VoiceActivityWorkerState {
+    source: Option<Box<dyn AudioSource>>,
+    selected_mic: Option<String>,
+    detector: VoiceActivityDetector,
+    paused: bool,
+    last_level_published: Instant,
+}
+impl VoiceActivityWorkerState {
+    fn is_capturing(&self) -> bool {
+        self.source.is_some() && !self.paused
+    }
+    fn open_source(&mut self, open_source: &AudioSourceFactory) -> eyre::Result<String> {
+        // Drop the old stream first so devices that only allow one client can be reopened
+        self.source = None;
+        let source = open_source(self.selected_mic.as_deref())?;
+        let name = source.name().to_string();
+        self.source = Some(source);
+        self.detector.reset();
+        Ok(name)
+    }
+}
+impl WorkerStateTrait for VoiceActivityWorkerState {
+    type Error = BevyError;
+
+    fn try_default() -> std::result::Result<Self, Self::Error> {
+        Ok(Self {
+            source: None,
+            selected_mic: None,
+            detector: VoiceActivityDetector::new(VadConfig::default()),
+            paused: false,
+            last_level_published: Instant::now(),
+        })
+    }
+}
+
+fn handle_threadbound_message(
+    msg: &VoiceActivityThreadboundMessage,
+    reply_tx: &Sender<VoiceActivityGameboundMessage>,
+    state: &mut VoiceActivityWorkerState,
+    open_source: &AudioSourceFactory,
+) -> Result<()> {
+    match msg {
+        VoiceActivityThreadboundMessage::SelectMic { name } => {
+            state.selected_mic = name.clone();
+            let opened = state.open_source(open_source)?;
+            info!("Capturing voice activity from {:?}", opened);
+            reply_tx.send(VoiceActivityGameboundMessage::SourceChanged { name: opened })?;
+        }
+        VoiceActivityThreadboundMessage::Pause => {
+            state.paused = true;
+            state.detector.reset();
+            reply_tx.send(VoiceActivityGameboundMessage::Paused)?;
+        }
+        VoiceActivityThreadboundMessage::Resume => {
+            state.paused = false;
+            if state.source.is_none() {
+                let opened = state.open_source(open_source)?;
+                reply_tx.send(VoiceActivityGameboundMessage::SourceChanged { name: opened })?;
+            }
+            reply_tx.send(VoiceActivityGameboundMessage::Resumed)?;
+        }
+        VoiceActivityThreadboundMessage::Configure(config) => {
+            *state.detector.config_mut() = config.clone();
+        }
+        VoiceActivityThreadboundMessage::SetSensitivity(sensitivity) => {
+            state.detector.config_mut().set_sensitivity(*sensitivity);
+            debug!(
+                "Voice activity threshold is now {} dBFS",
+                state.detector.config().energy_threshold_dbfs
+            );
+        }
+        VoiceActivityThreadboundMessage::ProcessAudio => {
+            if state.paused {
+                return Ok(());
+            }
+            let Some(source) = state.source.as_mut() else {
+                return Ok(());
+            };
+            let mut level_dbfs = None;
+            while let Some(frame) = source.next_frame()? {
+                let outcome = state.detector.process(&frame);
+                level_dbfs = Some(outcome.analysis.level_dbfs);
+                if let Some(event) = outcome.event {
+                    reply_tx.send(VoiceActivityGameboundMessage::Activity(event))?;
+                }
+            }
+            if let Some(level_dbfs) = level_dbfs
+                && state.last_level_published.elapsed() >= LEVEL_PUBLISH_INTERVAL
+            {
+                state.last_level_published = Instant::now();
+                reply_tx.send(VoiceActivityGameboundMessage::Level { level_dbfs })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_threadbound_message_error_handler(
+    _msg: &VoiceActivityThreadboundMessage,
+    reply_tx: &Sender<VoiceActivityGameboundMessage>,
+    state: &mut VoiceActivityWorkerState,
+    error: &BevyError,
+) -> Result<()> {
+    // A failing device would otherwise error on every frame
+    state.source = None;
+    reply_tx.send(VoiceActivityGameboundMessage::Error(format!("{error}")))?;
+    Ok(())
+}
+
+fn spawn_voice_activity(mut commands: Commands) {
+    commands.spawn((VoiceActivity::default(), Name::new("Voice Activity")));
+}
+
+/// Sends only what differs from the last applied settings, so editing the detector does not reopen the mic.
+fn apply_voice_activity_settings(
+    settings: Res<Settings>,
+    mut applied: Local<Option<VoiceActivitySettings>>,
+    mut threadbound_messages: EventWriter<VoiceActivityThreadboundMessage>,
+) {
+    let wanted = &settings.voice_activity;
+    if applied
+        .as_ref()
+        .is_none_or(|applied| applied.vad != wanted.vad)
+    {
+        threadbound_messages.write(VoiceActivityThreadboundMessage::Configure(
+            wanted.vad.clone(),
+        ));
+    }
+    if applied
+        .as_ref()
+        .is_none_or(|applied| applied.mic != wanted.mic)
+    {
+        info!("Selecting microphone {:?}", wanted.mic);
+        threadbound_messages.write(VoiceActivityThreadboundMessage::SelectMic {
+            name: wanted.mic.clone(),
+        });
+    }
+    *applied = Some(wanted.clone());
+}
+
+fn handle_gamebound_messages(
+    mut messages: EventReader<VoiceActivityGameboundMessage>,
+    mut voice_activity: Query<&mut VoiceActivity>,
+) -> Result {
+    for msg in messages.read() {
+        let mut voice_activity = voice_activity.single_mut()?;
+        match msg {
+            VoiceActivityGameboundMessage::Activity(event) => {
+                debug!("Voice activity: {:?}", event);
+                voice_activity.is_speaking =
+                    matches!(event, VoiceActivityEvent::VoiceActivityStarted { .. });
+            }
+            VoiceActivityGameboundMessage::Level { level_dbfs } => {
+                voice_activity.level_dbfs = *level_dbfs;
+            }
+            VoiceActivityGameboundMessage::SourceChanged { name } => {
+                voice_activity.source = Some(name.clone());
+                voice_activity.is_speaking = false;
+            }
+            VoiceActivityGameboundMessage::Paused => {
+                voice_activity.is_paused = true;
+                voice_activity.is_speaking = false;
+                voice_activity.level_dbfs = SILENCE_DBFS;
+            }
+            VoiceActivityGameboundMessage::Resumed => {
+                voice_activity.is_paused = false;
+            }
+            VoiceActivityGameboundMessage::Error(message) => {
+                warn!("Voice activity worker error: {}", message);
+                voice_activity.source = None;
+                voice_activity.is_speaking = false;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use bevy::log::error;
+use cpal::FromSample;
+use cpal::SampleFormat;
+use cpal::SizedSample;
+use cpal::Stream;
+use cpal::StreamConfig;
+use cpal::traits::DeviceTrait;
+use cpal::traits::HostTrait;
+use cpal::traits::StreamTrait;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::TrySendError;
+use crossbeam_channel::bounded;
+use eyre::OptionExt;
+use eyre::bail;
+use std::time::Duration;
+use ymb_voice_activity::AudioFrame;
+use ymb_voice_activity::AudioSource;
+use ymb_voice_activity::Framer;
+use ymb_voice_activity::downmix_interleaved;
+
+/// Captures from an input device, keeping the stream alive for as long as this value lives.
+pub struct MicrophoneSource {
+    name: String,
+    framer: Framer,
+    samples_rx: Receiver<Vec<f32>>,
+    _stream: Stream,
+}
+impl MicrophoneSource {
+    /// Opens the input device with the given friendly name, or the default device when `None`.
+    pub fn open(device_name: Option<&str>, frame_duration: Duration) -> eyre::Result<Self> {
+        let host = cpal::default_host();
+        let device = match device_name {
+            Some(wanted) => host
+                .input_devices()?
+                .find(|device| device.name().is_ok_and(|name| name == wanted))
+                .ok_or_else(|| eyre::eyre!("No input device named {wanted:?}"))?,
+            None => host
+                .default_input_device()
+                .ok_or_eyre("No default input device")?,
+        };
+        let name = device.name()?;
+        let supported = device.default_input_config()?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+
+        let channels = config.channels;
+        let (stream, samples_rx) = match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, channels)?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, channels)?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, channels)?,
+            SampleFormat::I32 => build_stream::<i32>(&device, &config, channels)?,
+            other => bail!("Unsupported sample format {other:?} for input device {name:?}"),
+        };
+        stream.play()?;
+
+        Ok(Self {
+            name,
+            framer: Framer::new(config.sample_rate.0, frame_duration),
+            samples_rx,
+            _stream: stream,
+        })
+    }
+}
+
+/// Returns the stream and the mono samples it captures.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    channels: u16,
+) -> eyre::Result<(Stream, Receiver<Vec<f32>>)>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    // Roughly two seconds of callbacks, older audio is dropped if the worker falls behind
+    let (samples_tx, samples_rx) = bounded::<Vec<f32>>(200);
+    let oldest_rx = samples_rx.clone();
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let samples = data
+                .iter()
+                .map(|x| f32::from_sample_(*x))
+                .collect::<Vec<_>>();
+            let mut samples = downmix_interleaved(&samples, channels);
+            // The worker only ever takes from the front, so making room always lets this send through
+            while let Err(TrySendError::Full(rejected)) = samples_tx.try_send(samples) {
+                let _ = oldest_rx.try_recv();
+                samples = rejected;
+            }
+        },
+        |e| error!("Input stream error: {e}"),
+        None,
+    )?;
+    Ok((stream, samples_rx))
+}
+
+impl AudioSource for MicrophoneSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sample_rate(&self) -> u32 {
+        self.framer.sample_rate()
+    }
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>> {
+        if let Some(frame) = self.framer.next_frame() {
+            return Ok(Some(frame));
+        }
+        for samples in self.samples_rx.try_iter() {
+            self.framer.push(&samples);
+        }
+        Ok(self.framer.next_frame())
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use eyre::eyre;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use ymb_settings::Settings;
+use ymb_settings::SettingsChanged;
+use ymb_settings::SettingsSection;
+use ymb_settings::VoiceActivitySettings;
+use ymb_voice_activity::AudioFrame;
+use ymb_voice_activity::AudioSource;
+use ymb_voice_activity::SILENCE_DBFS;
+use ymb_voice_activity::Signal;
+use ymb_voice_activity::SignalSegment;
+use ymb_voice_activity::SyntheticSource;
+use ymb_voice_activity::VoiceActivityEvent;
+use ymb_voice_activity_plugin::FRAME_DURATION;
+use ymb_voice_activity_plugin::VoiceActivity;
+use ymb_voice_activity_plugin::VoiceActivityGameboundMessage;
+use ymb_voice_activity_plugin::VoiceActivityPlugin;
+use ymb_voice_activity_plugin::VoiceActivityThreadboundMessage;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const VOICE: Signal = Signal::Sine {
+    frequency: 220.0,
+    amplitude: 0.3,
+};
+/// Around -43 dBFS, speech at the default sensitivity but not at the lowest.
+const QUIET_VOICE: Signal = Signal::Sine {
+    frequency: 220.0,
+    amplitude: 0.01,
+};
+
+/// A synthetic microphone the test keeps feeding after the worker opened it.
+#[derive(Clone)]
+struct Feed {
+    name: String,
+    source: Arc<Mutex<SyntheticSource>>,
+}
+impl Feed {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            source: Arc::new(Mutex::new(
+                SyntheticSource::new(16_000, FRAME_DURATION).with_name(name),
+            )),
+        }
+    }
+
+    fn push(&self, signal: Signal, duration: Duration) {
+        self.source
+            .lock()
+            .unwrap()
+            .push(SignalSegment { signal, duration });
+    }
+
+    fn wait_until_drained(&self) {
+        let started = Instant::now();
+        while !self.source.lock().unwrap().is_finished() {
+            assert!(
+                started.elapsed() < TIMEOUT,
+                "{} was never drained",
+                self.name
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+impl AudioSource for Feed {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sample_rate(&self) -> u32 {
+        self.source.lock().unwrap().sample_rate()
+    }
+    fn next_frame(&mut self) -> eyre::Result<Option<AudioFrame>> {
+        self.source.lock().unwrap().next_frame()
+    }
+}
+
+#[derive(Resource, Default)]
+struct Activity(Vec<VoiceActivityEvent>);
+
+fn collect(
+    mut messages: EventReader<VoiceActivityGameboundMessage>,
+    mut activity: ResMut<Activity>,
+) {
+    for msg in messages.read() {
+        if let VoiceActivityGameboundMessage::Activity(event) = msg {
+            activity.0.push(*event);
+        }
+    }
+}
+
+/// `default` is opened when the settings name no microphone.
+fn app(default: &Feed, others: &[&Feed]) -> App {
+    let default = default.clone();
+    let others: Vec<Feed> = others.iter().map(|feed| (*feed).clone()).collect();
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(Settings::default());
+    app.add_event::<SettingsChanged>();
+    app.init_resource::<Activity>();
+    app.add_plugins(VoiceActivityPlugin {
+        open_source: Arc::new(
+            move |name: Option<&str>| -> eyre::Result<Box<dyn AudioSource>> {
+                let feed = match name {
+                    None => &default,
+                    Some(name) => others
+                        .iter()
+                        .find(|feed| feed.name == name)
+                        .ok_or_else(|| eyre!("No microphone named {name:?}"))?,
+                };
+                Ok(Box::new(feed.clone()))
+            },
+        ),
+    });
+    app.add_systems(Update, collect);
+    app
+}
+
+/// Updates until the component matches, it follows the worker thread.
+fn wait_for(app: &mut App, what: &str, done: impl Fn(&VoiceActivity) -> bool) {
+    let started = Instant::now();
+    while started.elapsed() < TIMEOUT {
+        app.update();
+        let mut query = app.world_mut().query::<&VoiceActivity>();
+        if query.single(app.world()).is_ok_and(&done) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let mut query = app.world_mut().query::<&VoiceActivity>();
+    panic!("never {what}, ended at {:?}", query.single(app.world()));
+}
+
+fn edit_settings(app: &mut App, edit: impl FnOnce(&mut VoiceActivitySettings)) {
+    edit(&mut app.world_mut().resource_mut::<Settings>().voice_activity);
+    app.world_mut().send_event(SettingsChanged {
+        section: SettingsSection::VoiceActivity,
+    });
+}
+
+/// Returns once the worker got through everything sent before, since it handles messages in order.
+fn pause_and_resume(app: &mut App) {
+    app.world_mut()
+        .send_event(VoiceActivityThreadboundMessage::Pause);
+    wait_for(app, "paused", |voice_activity| {
+        voice_activity.is_paused
+            && !voice_activity.is_speaking
+            && voice_activity.level_dbfs == SILENCE_DBFS
+    });
+    app.world_mut()
+        .send_event(VoiceActivityThreadboundMessage::Resume);
+    wait_for(app, "resumed", |voice_activity| !voice_activity.is_paused);
+}
+
+#[test]
+fn synthetic_microphones_drive_voice_activity() {
+    let default = Feed::new("Default");
+    let headset = Feed::new("Headset");
+    default.push(VOICE, Duration::from_millis(500));
+    let mut app = app(&default, &[&headset]);
+    wait_for(
+        &mut app,
+        "speaking into the default mic",
+        |voice_activity| {
+            voice_activity.source.as_deref() == Some("Default") && voice_activity.is_speaking
+        },
+    );
+
+    // Switching starts over on the new source
+    edit_settings(&mut app, |settings| {
+        settings.mic = Some("Headset".to_string());
+    });
+    wait_for(&mut app, "switched to the headset", |voice_activity| {
+        voice_activity.source.as_deref() == Some("Headset") && !voice_activity.is_speaking
+    });
+    headset.push(VOICE, Duration::from_millis(500));
+    wait_for(&mut app, "speaking into the headset", |voice_activity| {
+        voice_activity.is_speaking
+    });
+    pause_and_resume(&mut app);
+
+    // Quiet speech is ignored at the lowest sensitivity
+    edit_settings(&mut app, |settings| settings.vad.set_sensitivity(0.0));
+    pause_and_resume(&mut app);
+    app.world_mut().resource_mut::<Activity>().0.clear();
+    headset.push(QUIET_VOICE, Duration::from_millis(500));
+    headset.wait_until_drained();
+    pause_and_resume(&mut app);
+    assert_eq!(app.world().resource::<Activity>().0, vec![]);
+
+    // And heard at the highest
+    edit_settings(&mut app, |settings| settings.vad.set_sensitivity(1.0));
+    pause_and_resume(&mut app);
+    headset.push(QUIET_VOICE, Duration::from_millis(500));
+    wait_for(&mut app, "speaking quietly", |voice_activity| {
+        voice_activity.is_speaking
+    });
+}
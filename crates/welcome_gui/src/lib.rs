@@ -12,9 +12,19 @@ use ymb_window_icon_plugin::WindowIconPlugin;
 use ymb_world_inspector_plugin::YMBWorldInspectorPlugin;
 use ymb_mute_status_window_plugin::YMBMuteStatusWindowPlugin;
 use ymb_mic_detection_plugin::MicDetectionPlugin;
+use ymb_voice_activity_plugin::VoiceActivityPlugin;
+use ymb_talking_while_muted_plugin::TalkingWhileMutedPlugin;
+use ymb_alert_plugin::AlertPlugin;
+use ymb_settings::Settings;
+use ymb_settings::SettingsPlugin;
+use ymb_settings_window_plugin::YMBSettingsWindowPlugin;
+use ymb_tray_status_plugin::TrayStatusPlugin;
+use ymb_worker_plugin::WorkerChannelOverride;
+use ymb_worker_plugin::WorkerChannelOverrides;
 
 pub fn run(_global_args: &GlobalArgs) -> eyre::Result<()> {
-    App::new()
+    let mut app = App::new();
+    app
         // bevy
         .add_plugins(
             DefaultPlugins
@@ -27,14 +37,41 @@ pub fn run(_global_args: &GlobalArgs) -> eyre::Result<()> {
         )
         .insert_resource(ClearColor(Color::NONE))
         // ours
+        .add_plugins(SettingsPlugin);
+    // Worker plugins read their channel sizes while being added
+    let worker_channel_overrides = worker_channel_overrides(app.world().resource::<Settings>());
+    app.insert_resource(worker_channel_overrides)
         .add_plugins(ExitOnEscPlugin)
         .add_plugins(YMBEguiPlugin)
-        .add_plugins(UIAutomationPlugin)
+        .add_plugins(UIAutomationPlugin::default())
         .add_plugins(YMBWorldInspectorPlugin)
         .add_plugins(YMBMuteStatusWindowPlugin)
+        .add_plugins(YMBSettingsWindowPlugin)
         .add_plugins(MicDetectionPlugin)
+        .add_plugins(VoiceActivityPlugin::default())
+        .add_plugins(TalkingWhileMutedPlugin)
+        .add_plugins(AlertPlugin)
         .add_plugins(IpcPlugin)
+        .add_plugins(TrayStatusPlugin)
         .add_plugins(WindowIconPlugin)
         .run();
     Ok(())
+}
+
+fn worker_channel_overrides(settings: &Settings) -> WorkerChannelOverrides {
+    WorkerChannelOverrides(
+        settings
+            .workers
+            .iter()
+            .map(|(name, channels)| {
+                (
+                    name.clone(),
+                    WorkerChannelOverride {
+                        gamebound_channel_capacity: channels.gamebound_channel_capacity,
+                        threadbound_channel_capacity: channels.threadbound_channel_capacity,
+                    },
+                )
+            })
+            .collect(),
+    )
 }
\ No newline at end of file
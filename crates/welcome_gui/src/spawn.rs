@@ -7,7 +7,6 @@ use std::os::windows::process::CommandExt;
 use std::process::Child;
 use std::process::Command;
 use std::process::Stdio;
-use std::sync::OnceLock;
 use std::thread;
 use windows::Win32::Foundation::HANDLE;
 use windows::Win32::System::JobObjects::AssignProcessToJobObject;
@@ -18,6 +17,11 @@ use windows::Win32::System::JobObjects::JobObjectExtendedLimitInformation;
 use windows::Win32::System::JobObjects::SetInformationJobObject;
 use ymb_args::Args;
 use ymb_args::GlobalArgs;
+use ymb_ipc::InstanceRecord;
+use ymb_ipc::InstanceRegistry;
+use ymb_ipc::SECRET_ENV_VAR;
+use ymb_ipc::SessionSecret;
+use ymb_ipc::endpoint_path;
 use ymb_logs::DualLogWriter;
 
 const DETACHED_PROCESS: u32 = 0x00000008;
@@ -40,13 +44,14 @@ fn spawn_gui_with_job(global_args: GlobalArgs, log_writer: DualLogWriter) -> eyr
 
     // Generate a unique pipe name
     let pipe_guid = uuid::Uuid::new_v4();
-    // Use the same format as the Bevy IPC plugin expects (\\.\pipe\ymb-gui-ipc-<pid>-<guid>)
     let gui_pid = std::process::id();
-    let pipe_name = format!(
-        r"\\.\pipe\ymb-gui-ipc-{}-{}",
+    let pipe_name = endpoint_path(&format!(
+        "ymb-gui-ipc-{}-{}",
         gui_pid,
         pipe_guid.as_simple()
-    );
+    ));
+    // Only the GUI we spawn and whoever can read our registry learn this, so only they can drive each other
+    let secret = SessionSecret::generate()?;
 
     // Create a job object that kills processes when the handle is closed
     let job_handle = unsafe { CreateJobObjectW(None, None)? };
@@ -72,6 +77,7 @@ fn spawn_gui_with_job(global_args: GlobalArgs, log_writer: DualLogWriter) -> eyr
         )
         .creation_flags(DETACHED_PROCESS | CREATE_NO_WINDOW)
         .env("YMB_IPC_PIPE_NAME", &pipe_name)
+        .env(SECRET_ENV_VAR, secret.to_hex())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()?;
@@ -82,8 +88,13 @@ fn spawn_gui_with_job(global_args: GlobalArgs, log_writer: DualLogWriter) -> eyr
     // Print for debug: ensure the tray and GUI are using the same pipe name
     info!("[SPAWN] Pipe name for tray and GUI: {}", pipe_name);
 
-    // Store the pipe name somewhere accessible to the tray (e.g., in a static/global)
-    set_pipe_name_for_tray(pipe_name);
+    // Record the pipe name where the tray, `ctl` and later launches can find it
+    // The GUI works without the record, only finding it from other processes does not
+    if let Err(e) = register_instance(pipe_name, secret) {
+        warn!(
+            "Failed to register this instance, ctl and later launches will not find it: {e:?}"
+        );
+    }
 
     // Pipe child stdout/stderr to the tray's log buffer
     if let Some(stdout) = child.stdout.take() {
@@ -146,11 +157,38 @@ fn attach_to_job(job_handle: HANDLE, child: &mut Child) -> eyre::Result<()> {
     Ok(())
 }
 
-// Store the pipe name for the tray to use
-static PIPE_NAME_FOR_TRAY: OnceLock<String> = OnceLock::new();
-fn set_pipe_name_for_tray(name: String) {
-    let _ = PIPE_NAME_FOR_TRAY.set(name);
+fn register_instance(pipe_name: String, secret: SessionSecret) -> eyre::Result<()> {
+    let registry = InstanceRegistry::for_current_user()
+        .ok_or_else(|| eyre::eyre!("No directory to record running instances in"))?;
+    info!("Registering instance in {}", registry.path().display());
+    registry.register(InstanceRecord::current(pipe_name, secret))?;
+    Ok(())
+}
+
+/// Removes this process from the registry, called when the tray quits.
+pub fn unregister_instance() {
+    let Some(registry) = InstanceRegistry::for_current_user() else {
+        return;
+    };
+    if let Err(e) = registry.unregister(std::process::id()) {
+        warn!(
+            "Failed to unregister instance from {}: {e}",
+            registry.path().display()
+        );
+    }
 }
-pub fn get_pipe_name_for_tray() -> Option<String> {
-    PIPE_NAME_FOR_TRAY.get().cloned()
+
+/// Where the GUI spawned by this tray listens and the secret it expects, once it has been registered.
+pub fn gui_instance() -> Option<InstanceRecord> {
+    let registry = InstanceRegistry::for_current_user()?;
+    match registry.find(std::process::id()) {
+        Ok(instance) => instance,
+        Err(e) => {
+            warn!(
+                "Failed to read instance registry {}: {e}",
+                registry.path().display()
+            );
+            None
+        }
+    }
 }
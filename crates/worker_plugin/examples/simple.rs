@@ -5,7 +5,6 @@ use bevy::log::tracing_subscriber;
 use bevy::log::tracing_subscriber::EnvFilter;
 use bevy::prelude::*;
 use crossbeam_channel::Sender;
-use ymb_worker_plugin::WorkerConfig;
 use ymb_worker_plugin::WorkerPlugin;
 
 fn main() {
@@ -29,13 +28,11 @@ pub struct MyCounterPlugin;
 
 impl Plugin for MyCounterPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(WorkerPlugin {
-            config: WorkerConfig::<ThreadboundMessage, GameboundMessage, WorkerState> {
-                name: "MyWorker".to_string(),
-                handle_threadbound_message,
-                ..default()
-            },
-        });
+        app.add_plugins(
+            WorkerPlugin::<ThreadboundMessage, GameboundMessage, WorkerState>::builder("MyWorker")
+                .on_message(handle_threadbound_message)
+                .build(),
+        );
         app.init_resource::<CounterState>();
         app.add_systems(Update, handle_gamebound_messages);
         app.add_systems(Update, tick_worker);
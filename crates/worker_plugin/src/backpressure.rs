@@ -0,0 +1,390 @@
+use bevy::reflect::Reflect;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::SendTimeoutError;
+use crossbeam_channel::Sender;
+use crossbeam_channel::TrySendError;
+use serde::Deserialize;
+use serde::Serialize;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Which coalescing group a message belongs to, `None` for messages that never coalesce.
+pub type CoalesceKey<M> = Arc<dyn Fn(&M) -> Option<u64> + Send + Sync>;
+
+/// What happens to a message when its channel is full.
+pub enum Backpressure<M> {
+    /// Drop the message that did not fit, the default for threadbound messages.
+    DropNewest,
+    /// Drop the oldest queued message to make room.
+    DropOldest,
+    /// Replace any queued message with the same key, so only the latest of each group is kept.
+    ///
+    /// When the channel is still full, the oldest keyed message makes room for the new one,
+    /// so a flood of keyed messages cannot crowd out the rest.
+    CoalesceBy(CoalesceKey<M>),
+    /// Wait this long for room, then drop the message.
+    ///
+    /// Threadbound messages are sent from the app, which stalls while it waits.
+    BlockFor(Duration),
+    /// Wait as long as it takes, the default for gamebound messages.
+    Block,
+}
+impl<M> Backpressure<M> {
+    pub fn coalesce_by<K: Hash>(key: impl Fn(&M) -> Option<K> + Send + Sync + 'static) -> Self {
+        Self::CoalesceBy(Arc::new(move |msg| {
+            key(msg).map(|key| {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                hasher.finish()
+            })
+        }))
+    }
+
+    /// Coalesces messages of the same enum variant.
+    pub fn coalesce_variants() -> Self {
+        Self::coalesce_by(|msg| Some(std::mem::discriminant(msg)))
+    }
+}
+impl<M> Clone for Backpressure<M> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::DropNewest => Self::DropNewest,
+            Self::DropOldest => Self::DropOldest,
+            Self::CoalesceBy(key) => Self::CoalesceBy(key.clone()),
+            Self::BlockFor(timeout) => Self::BlockFor(*timeout),
+            Self::Block => Self::Block,
+        }
+    }
+}
+impl<M> std::fmt::Debug for Backpressure<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DropNewest => write!(f, "DropNewest"),
+            Self::DropOldest => write!(f, "DropOldest"),
+            Self::CoalesceBy(_) => write!(f, "CoalesceBy(..)"),
+            Self::BlockFor(timeout) => write!(f, "BlockFor({timeout:?})"),
+            Self::Block => write!(f, "Block"),
+        }
+    }
+}
+
+/// What the [`Backpressure`] policy of one direction had to do.
+#[derive(Reflect, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackpressureStats {
+    /// New messages dropped because there was no room, by [`Backpressure::DropNewest`] or when nothing else could go.
+    pub dropped_newest: u64,
+    /// Queued messages dropped to make room.
+    pub dropped_oldest: u64,
+    /// Queued messages replaced by a newer one with the same key.
+    pub coalesced: u64,
+    /// Messages dropped after waiting out [`Backpressure::BlockFor`].
+    pub timed_out: u64,
+}
+impl BackpressureStats {
+    /// Messages lost for good, coalesced ones were superseded so they do not count.
+    pub fn dropped(&self) -> u64 {
+        self.dropped_newest + self.dropped_oldest + self.timed_out
+    }
+}
+
+pub(crate) enum Pushed {
+    Sent,
+    /// The new message did not make it into the channel.
+    Dropped,
+    Disconnected,
+}
+
+/// Sends `msg` following `policy`, where `queued` is a receiver of the same channel.
+pub(crate) fn push_with_backpressure<M>(
+    policy: &Backpressure<M>,
+    tx: &Sender<M>,
+    queued: &Receiver<M>,
+    msg: M,
+    stats: &mut BackpressureStats,
+) -> Pushed {
+    if let Backpressure::CoalesceBy(key) = policy {
+        return push_coalescing(key, tx, queued, msg, stats);
+    }
+    let msg = match tx.try_send(msg) {
+        Ok(()) => return Pushed::Sent,
+        Err(TrySendError::Disconnected(_)) => return Pushed::Disconnected,
+        Err(TrySendError::Full(msg)) => msg,
+    };
+    match policy {
+        Backpressure::DropNewest | Backpressure::CoalesceBy(_) => {
+            stats.dropped_newest += 1;
+            Pushed::Dropped
+        }
+        Backpressure::DropOldest => {
+            let mut msg = msg;
+            // The thread may take messages meanwhile, so only the drops that were needed are counted
+            loop {
+                if queued.try_recv().is_ok() {
+                    stats.dropped_oldest += 1;
+                }
+                match tx.try_send(msg) {
+                    Ok(()) => return Pushed::Sent,
+                    Err(TrySendError::Disconnected(_)) => return Pushed::Disconnected,
+                    Err(TrySendError::Full(_)) if queued.is_empty() => {
+                        // Nothing left to drop, as with a zero capacity channel
+                        stats.dropped_newest += 1;
+                        return Pushed::Dropped;
+                    }
+                    Err(TrySendError::Full(returned)) => msg = returned,
+                }
+            }
+        }
+        Backpressure::BlockFor(timeout) => match tx.send_timeout(msg, *timeout) {
+            Ok(()) => Pushed::Sent,
+            Err(SendTimeoutError::Timeout(_)) => {
+                stats.timed_out += 1;
+                Pushed::Dropped
+            }
+            Err(SendTimeoutError::Disconnected(_)) => Pushed::Disconnected,
+        },
+        Backpressure::Block => match tx.send(msg) {
+            Ok(()) => Pushed::Sent,
+            Err(_) => Pushed::Disconnected,
+        },
+    }
+}
+
+fn push_coalescing<M>(
+    key: &CoalesceKey<M>,
+    tx: &Sender<M>,
+    queued: &Receiver<M>,
+    msg: M,
+    stats: &mut BackpressureStats,
+) -> Pushed {
+    let msg_key = key(&msg);
+    // Take everything queued and put back what survives, in order
+    let mut kept = Vec::with_capacity(queued.len());
+    for old in queued.try_iter() {
+        if msg_key.is_some() && key(&old) == msg_key {
+            stats.coalesced += 1;
+        } else {
+            kept.push(old);
+        }
+    }
+    if tx.capacity().is_some_and(|capacity| kept.len() >= capacity)
+        && let Some(index) = kept.iter().position(|old| key(old).is_some())
+    {
+        kept.remove(index);
+        stats.dropped_oldest += 1;
+    }
+    for old in kept {
+        if let Err(TrySendError::Disconnected(_)) = tx.try_send(old) {
+            return Pushed::Disconnected;
+        }
+    }
+    match tx.try_send(msg) {
+        Ok(()) => Pushed::Sent,
+        Err(TrySendError::Full(_)) => {
+            stats.dropped_newest += 1;
+            Pushed::Dropped
+        }
+        Err(TrySendError::Disconnected(_)) => Pushed::Disconnected,
+    }
+}
+
+/// Forwards what a handler sends to the gamebound channel, applying `policy` on the way.
+///
+/// Runs until every handler-side sender is gone.
+pub(crate) fn pump_gamebound<G>(
+    policy: &Backpressure<G>,
+    staged: &Receiver<G>,
+    game_tx: &Sender<G>,
+    queued: &Receiver<G>,
+    mut report: impl FnMut(&BackpressureStats),
+) {
+    let mut stats = BackpressureStats::default();
+    for msg in staged.iter() {
+        let before = stats.clone();
+        if let Pushed::Disconnected =
+            push_with_backpressure(policy, game_tx, queued, msg, &mut stats)
+        {
+            return;
+        }
+        if stats != before {
+            report(&stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Backpressure;
+    use crate::BackpressureStats;
+    use crate::Pushed;
+    use crate::WorkerPlugin;
+    use crate::WorkerTestApp;
+    use crate::push_with_backpressure;
+    use bevy::prelude::*;
+    use crossbeam_channel::bounded;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Msg {
+        Level(u32),
+        Control(u32),
+    }
+
+    /// Pushes every message into a channel of `capacity`, returning what ends up queued.
+    fn queue_up(
+        policy: Backpressure<Msg>,
+        capacity: usize,
+        msgs: impl IntoIterator<Item = Msg>,
+    ) -> (Vec<Msg>, BackpressureStats) {
+        let (tx, rx) = bounded(capacity);
+        let mut stats = BackpressureStats::default();
+        for msg in msgs {
+            push_with_backpressure(&policy, &tx, &rx, msg, &mut stats);
+        }
+        (rx.try_iter().collect(), stats)
+    }
+
+    #[test]
+    fn drop_newest_keeps_what_was_there() {
+        let (queued, stats) = queue_up(Backpressure::DropNewest, 2, (1..=4).map(Msg::Level));
+        assert_eq!(queued, [Msg::Level(1), Msg::Level(2)]);
+        assert_eq!(
+            stats,
+            BackpressureStats {
+                dropped_newest: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn drop_oldest_keeps_the_latest() {
+        let (queued, stats) = queue_up(Backpressure::DropOldest, 2, (1..=4).map(Msg::Level));
+        assert_eq!(queued, [Msg::Level(3), Msg::Level(4)]);
+        assert_eq!(
+            stats,
+            BackpressureStats {
+                dropped_oldest: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn coalescing_keeps_the_latest_per_key_and_spares_the_rest() {
+        let levels_only =
+            || Backpressure::coalesce_by(|msg: &Msg| matches!(msg, Msg::Level(_)).then_some(()));
+        let (queued, stats) = queue_up(
+            levels_only(),
+            3,
+            [Msg::Level(1), Msg::Control(1), Msg::Level(2), Msg::Level(3)],
+        );
+        assert_eq!(queued, [Msg::Control(1), Msg::Level(3)]);
+        assert_eq!(stats.coalesced, 2);
+
+        // Control messages push out queued levels, and are only dropped once nothing else can go
+        let (queued, stats) = queue_up(
+            levels_only(),
+            2,
+            [
+                Msg::Level(1),
+                Msg::Control(1),
+                Msg::Control(2),
+                Msg::Control(3),
+            ],
+        );
+        assert_eq!(queued, [Msg::Control(1), Msg::Control(2)]);
+        assert_eq!(
+            stats,
+            BackpressureStats {
+                dropped_oldest: 1,
+                dropped_newest: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn coalescing_variants_keeps_one_of_each() {
+        let (queued, stats) = queue_up(
+            Backpressure::coalesce_variants(),
+            10,
+            [
+                Msg::Level(1),
+                Msg::Control(1),
+                Msg::Level(2),
+                Msg::Control(2),
+            ],
+        );
+        assert_eq!(queued, [Msg::Level(2), Msg::Control(2)]);
+        assert_eq!(stats.coalesced, 2);
+    }
+
+    #[test]
+    fn blocking_gives_up_after_the_timeout() {
+        let started = Instant::now();
+        let (queued, stats) = queue_up(
+            Backpressure::BlockFor(Duration::from_millis(20)),
+            1,
+            (1..=2).map(Msg::Level),
+        );
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(queued, [Msg::Level(1)]);
+        assert_eq!(stats.timed_out, 1);
+    }
+
+    #[test]
+    fn blocking_waits_for_room() {
+        let (tx, rx) = bounded(1);
+        tx.send(Msg::Level(1)).unwrap();
+        let consumer = rx.clone();
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            consumer.recv().unwrap()
+        });
+        let mut stats = BackpressureStats::default();
+        let pushed = push_with_backpressure(
+            &Backpressure::BlockFor(Duration::from_secs(5)),
+            &tx,
+            &rx,
+            Msg::Level(2),
+            &mut stats,
+        );
+        assert!(matches!(pushed, Pushed::Sent));
+        assert_eq!(thread.join().unwrap(), Msg::Level(1));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [Msg::Level(2)]);
+        assert_eq!(stats, BackpressureStats::default());
+    }
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Burst(u32);
+
+    #[derive(Debug, Clone, PartialEq, Event, Reflect)]
+    struct Level(u32);
+
+    #[test]
+    fn the_gamebound_pump_applies_the_policy() {
+        let mut app = WorkerTestApp::new(
+            WorkerPlugin::<Burst, Level, ()>::builder("Burst")
+                .on_message(|msg, reply_tx, _| {
+                    for n in 1..=msg.0 {
+                        reply_tx.send(Level(n))?;
+                    }
+                    Ok(())
+                })
+                .gamebound_channel_capacity(2)
+                .gamebound_backpressure(Backpressure::DropOldest)
+                .build(),
+        );
+        app.send(Burst(10));
+        app.update();
+        // Without updates the app drains nothing, so the handler would block here without the policy
+        std::thread::sleep(Duration::from_millis(100));
+        app.update();
+        assert_eq!(app.received(), [Level(9), Level(10)]);
+        assert_eq!(app.stats().gamebound_backpressure.dropped_oldest, 8);
+    }
+}
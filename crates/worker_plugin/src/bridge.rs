@@ -1,6 +1,13 @@
+use crate::Pushed;
+use crate::WorkerCancellation;
 use crate::WorkerConfig;
+use crate::WorkerCounters;
 use crate::WorkerMessage;
+use crate::WorkerMetrics;
 use crate::WorkerStateTrait;
+use crate::WorkerStatus;
+use crate::push_with_backpressure;
+use bevy::ecs::entity::Entity;
 use bevy::ecs::event::EventReader;
 use bevy::ecs::event::EventWriter;
 use bevy::ecs::resource::Resource;
@@ -10,32 +17,52 @@ use bevy::log::error;
 use bevy::log::trace;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
+use std::thread::JoinHandle;
 
 pub fn bridge_requests<ThreadboundMessage, GameboundMessage, WorkerState>(
     config: Res<WorkerConfig<ThreadboundMessage, GameboundMessage, WorkerState>>,
     bridge: ResMut<Bridge<ThreadboundMessage, GameboundMessage>>,
     mut events: EventReader<ThreadboundMessage>,
+    mut metrics: ResMut<WorkerMetrics>,
 ) where
     ThreadboundMessage: WorkerMessage,
     GameboundMessage: WorkerMessage,
     WorkerState: WorkerStateTrait,
 {
+    let stats = metrics.workers.entry(config.name.clone()).or_default();
+    // The bridge keeps a receiver of its own, so the channel alone cannot tell that the thread is gone
+    let thread_gone = bridge
+        .thread
+        .as_ref()
+        .is_none_or(|thread| thread.is_finished());
     for event in events.read() {
         trace!("[{}] Bevy => Thread: {:?}", config.name, event);
-        if let Err(e) = bridge.sender.try_send(event.clone()) {
-            match e {
-                crossbeam_channel::TrySendError::Full(_) => {
-                    error!(
-                        "[{}] Threadbound channel is full, dropping message: {:?}",
-                        config.name, event
-                    );
-                }
-                crossbeam_channel::TrySendError::Disconnected(_) => {
-                    error!(
-                        "[{}] Threadbound channel is disconnected, dropping message: {:?}",
-                        config.name, event
-                    );
-                }
+        let pushed = if thread_gone {
+            Pushed::Disconnected
+        } else {
+            push_with_backpressure(
+                &config.threadbound_backpressure,
+                &bridge.sender,
+                &bridge.queued,
+                event.clone(),
+                &mut stats.threadbound_backpressure,
+            )
+        };
+        match pushed {
+            Pushed::Sent => stats.messages_in += 1,
+            Pushed::Dropped => {
+                stats.dropped_full += 1;
+                error!(
+                    "[{}] Threadbound channel is full, dropping message: {:?}",
+                    config.name, event
+                );
+            }
+            Pushed::Disconnected => {
+                stats.dropped_disconnected += 1;
+                error!(
+                    "[{}] Threadbound channel is disconnected, dropping message: {:?}",
+                    config.name, event
+                );
             }
         }
     }
@@ -45,13 +72,16 @@ pub fn bridge_responses<T, G, S>(
     config: Res<WorkerConfig<T, G, S>>,
     bridge: ResMut<Bridge<T, G>>,
     mut events: EventWriter<G>,
+    mut metrics: ResMut<WorkerMetrics>,
 ) where
     T: WorkerMessage,
     G: WorkerMessage,
     S: WorkerStateTrait,
 {
+    let stats = metrics.workers.entry(config.name.clone()).or_default();
     for msg in bridge.receiver.try_iter() {
         trace!("[{}] Thread => Bevy: {:?}", config.name, msg);
+        stats.messages_out += 1;
         events.write(msg);
     }
 }
@@ -63,5 +93,14 @@ where
     G: WorkerMessage,
 {
     pub sender: Sender<T>,
+    /// The thread's end of the threadbound channel, for policies that drop or coalesce queued messages.
+    pub queued: Receiver<T>,
     pub receiver: Receiver<G>,
+    pub status: Receiver<WorkerStatus>,
+    /// Carries the worker's [`WorkerStatus`].
+    pub entity: Entity,
+    /// Taken when the worker is shut down, `None` if it could not be spawned.
+    pub thread: Option<JoinHandle<()>>,
+    pub cancellation: WorkerCancellation,
+    pub counters: WorkerCounters,
 }
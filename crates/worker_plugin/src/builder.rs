@@ -0,0 +1,197 @@
+use crate::Backpressure;
+use crate::Sender;
+use crate::TickSchedule;
+use crate::WorkerConfig;
+use crate::WorkerMessage;
+use crate::WorkerPlugin;
+use crate::WorkerStateTrait;
+use crate::WorkerSupervision;
+use crate::receive_latest_only;
+use crate::ticking_receiver;
+use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::unbounded;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl<T, G, S> WorkerPlugin<T, G, S>
+where
+    T: WorkerMessage + GetTypeRegistration,
+    G: WorkerMessage + GetTypeRegistration,
+    S: WorkerStateTrait,
+{
+    /// Starts a worker named `name` that ignores its messages until given [`WorkerPluginBuilder::on_message`].
+    pub fn builder(name: impl Into<String>) -> WorkerPluginBuilder<T, G, S> {
+        WorkerPluginBuilder {
+            config: WorkerConfig {
+                name: name.into(),
+                ..default()
+            },
+        }
+    }
+}
+
+/// Builds a [`WorkerPlugin`] from closures, which unlike `fn` items can capture what they need.
+pub struct WorkerPluginBuilder<T, G, S>
+where
+    T: WorkerMessage + GetTypeRegistration,
+    G: WorkerMessage + GetTypeRegistration,
+    S: WorkerStateTrait,
+{
+    config: WorkerConfig<T, G, S>,
+}
+
+impl<T, G, S> WorkerPluginBuilder<T, G, S>
+where
+    T: WorkerMessage + GetTypeRegistration,
+    G: WorkerMessage + GetTypeRegistration,
+    S: WorkerStateTrait,
+{
+    pub fn on_message(
+        mut self,
+        handler: impl Fn(&T, &Sender<G>, &mut S) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.config.handle_threadbound_message = Arc::new(handler);
+        self
+    }
+
+    /// Called after [`Self::on_message`] fails, the error is already logged.
+    pub fn on_error(
+        mut self,
+        handler: impl Fn(&T, &Sender<G>, &mut S, &BevyError) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.config.handle_threadbound_message_error_handler = Arc::new(handler);
+        self
+    }
+
+    /// Replaces how the thread waits for its next message, which blocks on the channel by default.
+    pub fn receiver(
+        mut self,
+        receiver: impl Fn(&Receiver<T>, &mut S) -> Result<T> + Send + Sync + 'static,
+    ) -> Self {
+        self.config.threadbound_message_receiver = Arc::new(receiver);
+        self
+    }
+
+    /// Skips to the newest message when several are queued, see [`receive_latest_only`].
+    pub fn receive_latest_only(self) -> Self {
+        self.receiver(receive_latest_only)
+    }
+
+    /// Also hands the thread `tick(state)` whenever `schedule` is due, even while the app is not updating.
+    ///
+    /// Replaces the receiver, send [`crate::WorkerTickControl`] events to pause, resume or retime the ticks.
+    pub fn tick_every(
+        mut self,
+        schedule: TickSchedule,
+        tick: impl Fn(&mut S) -> T + Send + Sync + 'static,
+    ) -> Self {
+        let (control_tx, control_rx) = unbounded();
+        self.config.tick_control = Some(control_tx);
+        self.receiver(ticking_receiver(schedule, control_rx, tick))
+    }
+
+    /// Initializes COM on the thread so it can use UI automation.
+    pub fn ui_automation_thread(mut self) -> Self {
+        self.config.is_ui_automation_thread = true;
+        self
+    }
+
+    /// Sleeps this long after every message.
+    pub fn sleep_duration(mut self, sleep_duration: Duration) -> Self {
+        self.config.sleep_duration = sleep_duration;
+        self
+    }
+
+    pub fn gamebound_channel_capacity(mut self, capacity: usize) -> Self {
+        self.config.gamebound_channel_capacity = capacity;
+        self
+    }
+
+    pub fn threadbound_channel_capacity(mut self, capacity: usize) -> Self {
+        self.config.threadbound_channel_capacity = capacity;
+        self
+    }
+
+    /// What happens to messages from the app when the thread is not keeping up, drops the newest by default.
+    pub fn threadbound_backpressure(mut self, policy: Backpressure<T>) -> Self {
+        self.config.threadbound_backpressure = policy;
+        self
+    }
+
+    /// What happens to the handler's messages when the app is not keeping up, blocks the handler by default.
+    pub fn gamebound_backpressure(mut self, policy: Backpressure<G>) -> Self {
+        self.config.gamebound_backpressure = policy;
+        self
+    }
+
+    pub fn supervision(mut self, supervision: WorkerSupervision) -> Self {
+        self.config.supervision = supervision;
+        self
+    }
+
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.config.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    pub fn build(self) -> WorkerPlugin<T, G, S> {
+        WorkerPlugin {
+            config: self.config,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::WorkerPlugin;
+    use bevy::prelude::*;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Poll(u32);
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Polled(u32);
+
+    #[test]
+    fn closures_capture_their_configuration() {
+        let offset = 100;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(
+            WorkerPlugin::<Poll, Polled, ()>::builder("Poller")
+                .on_message(move |msg, reply_tx, _| {
+                    reply_tx.send(Polled(msg.0 + offset))?;
+                    Ok(())
+                })
+                .build(),
+        );
+        app.update();
+        app.world_mut().send_event(Poll(1));
+
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            app.update();
+            let polled = app.world().resource::<Events<Polled>>();
+            if let Some(polled) = polled.iter_current_update_events().next() {
+                assert_eq!(polled.0, 101);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("the worker never replied");
+    }
+
+    #[test]
+    fn latest_only_skips_to_the_newest_message() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for n in 0..3 {
+            tx.send(Poll(n)).unwrap();
+        }
+        assert_eq!(crate::receive_latest_only(&rx, &mut ()).unwrap().0, 2);
+        assert!(rx.is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::ecs::resource::Resource;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::ReflectResource;
+use bevy::reflect::Reflect;
+
+/// Channel capacities that replace the ones in [`crate::WorkerConfig`], keyed by worker name.
+///
+/// Must be inserted before the [`crate::WorkerPlugin`] it applies to is added.
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct WorkerChannelOverrides(pub HashMap<String, WorkerChannelOverride>);
+
+#[derive(Reflect, Debug, Clone, Default, PartialEq)]
+pub struct WorkerChannelOverride {
+    pub gamebound_channel_capacity: Option<usize>,
+    pub threadbound_channel_capacity: Option<usize>,
+}
@@ -1,13 +1,18 @@
+use crate::Backpressure;
 use crate::PhantomHolder;
 use crate::ThreadboundMessageErrorHandler;
 use crate::ThreadboundMessageHandler;
 use crate::ThreadboundMessageReceiver;
+use crate::TickCommand;
 use crate::WorkerMessage;
 use crate::WorkerStateTrait;
-use bevy::ecs::error::BevyError;
+use crate::WorkerSupervision;
+use crate::receive_blocking;
 use bevy::ecs::resource::Resource;
 use bevy::prelude::ReflectResource;
 use bevy::reflect::Reflect;
+use crossbeam_channel::Sender;
+use std::sync::Arc;
 
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
@@ -15,13 +20,30 @@ pub struct WorkerConfig<ThreadboundMessage, GameboundMessage, WorkerState> {
     pub name: String,
     pub sleep_duration: std::time::Duration,
     pub is_ui_automation_thread: bool,
+    #[reflect(ignore, default = "default_receiver")]
     pub threadbound_message_receiver: ThreadboundMessageReceiver<ThreadboundMessage, WorkerState>,
+    #[reflect(ignore, default = "ignore_messages")]
     pub handle_threadbound_message:
         ThreadboundMessageHandler<ThreadboundMessage, GameboundMessage, WorkerState>,
+    #[reflect(ignore, default = "ignore_errors")]
     pub handle_threadbound_message_error_handler:
         ThreadboundMessageErrorHandler<ThreadboundMessage, GameboundMessage, WorkerState>,
     pub gamebound_channel_capacity: usize,
     pub threadbound_channel_capacity: usize,
+    /// What [`crate::bridge_requests`] does when the threadbound channel is full.
+    #[reflect(ignore, default = "drop_newest")]
+    pub threadbound_backpressure: Backpressure<ThreadboundMessage>,
+    /// What the worker does when the gamebound channel is full.
+    ///
+    /// Anything but [`Backpressure::Block`] forwards the handler's messages through a helper thread.
+    #[reflect(ignore, default = "block")]
+    pub gamebound_backpressure: Backpressure<GameboundMessage>,
+    pub supervision: WorkerSupervision,
+    /// How long exiting the app waits for the thread to finish before leaving it behind.
+    pub shutdown_timeout: std::time::Duration,
+    /// Set by [`crate::WorkerPluginBuilder::tick_every`], feeds the ticking receiver its [`crate::WorkerTickControl`]s.
+    #[reflect(ignore)]
+    pub tick_control: Option<Sender<TickCommand>>,
     pub type_holder: PhantomHolder<ThreadboundMessage, GameboundMessage, WorkerState>,
 }
 impl<ThreadboundMessage, GameboundMessage, WorkerState> Default
@@ -36,13 +58,16 @@ where
             name: "Unknown Worker".to_string(),
             is_ui_automation_thread: false,
             sleep_duration: std::time::Duration::ZERO,
-            handle_threadbound_message: |_, _, _| Ok(()),
-            handle_threadbound_message_error_handler: |_, _, _, _| Ok(()),
-            threadbound_message_receiver: |thread_rx, _state| {
-                thread_rx.recv().map_err(BevyError::from)
-            },
+            handle_threadbound_message: ignore_messages(),
+            handle_threadbound_message_error_handler: ignore_errors(),
+            threadbound_message_receiver: default_receiver(),
             gamebound_channel_capacity: 10,
             threadbound_channel_capacity: 10,
+            threadbound_backpressure: drop_newest(),
+            gamebound_backpressure: block(),
+            supervision: WorkerSupervision::default(),
+            shutdown_timeout: std::time::Duration::from_secs(2),
+            tick_control: None,
             type_holder:
                 PhantomHolder::<ThreadboundMessage, GameboundMessage, WorkerState>::default(),
         }
@@ -56,12 +81,39 @@ impl<ThreadboundMessage, GameboundMessage, WorkerState> Clone
             name: self.name.clone(),
             sleep_duration: self.sleep_duration,
             is_ui_automation_thread: self.is_ui_automation_thread,
-            threadbound_message_receiver: self.threadbound_message_receiver,
-            handle_threadbound_message: self.handle_threadbound_message,
-            handle_threadbound_message_error_handler: self.handle_threadbound_message_error_handler,
+            threadbound_message_receiver: self.threadbound_message_receiver.clone(),
+            handle_threadbound_message: self.handle_threadbound_message.clone(),
+            handle_threadbound_message_error_handler: self
+                .handle_threadbound_message_error_handler
+                .clone(),
             gamebound_channel_capacity: self.gamebound_channel_capacity,
             threadbound_channel_capacity: self.threadbound_channel_capacity,
+            threadbound_backpressure: self.threadbound_backpressure.clone(),
+            gamebound_backpressure: self.gamebound_backpressure.clone(),
+            supervision: self.supervision.clone(),
+            shutdown_timeout: self.shutdown_timeout,
+            tick_control: self.tick_control.clone(),
             type_holder: self.type_holder.clone(),
         }
     }
 }
+
+fn ignore_messages<T, G, S>() -> ThreadboundMessageHandler<T, G, S> {
+    Arc::new(|_, _, _| Ok(()))
+}
+
+fn ignore_errors<T, G, S>() -> ThreadboundMessageErrorHandler<T, G, S> {
+    Arc::new(|_, _, _, _| Ok(()))
+}
+
+fn drop_newest<M>() -> Backpressure<M> {
+    Backpressure::DropNewest
+}
+
+fn block<M>() -> Backpressure<M> {
+    Backpressure::Block
+}
+
+fn default_receiver<T: 'static, S: 'static>() -> ThreadboundMessageReceiver<T, S> {
+    Arc::new(receive_blocking)
+}
@@ -1,14 +1,30 @@
+use crate::Backpressure;
 use crate::Bridge;
+use crate::WorkerCancellation;
 use crate::WorkerConfig;
+use crate::WorkerCounters;
 use crate::WorkerMessage;
 use crate::WorkerStateTrait;
+use crate::WorkerStatus;
+use crate::pump_gamebound;
 use bevy::prelude::*;
 pub use crossbeam_channel::Receiver;
+use crossbeam_channel::RecvError;
+use crossbeam_channel::RecvTimeoutError;
 pub use crossbeam_channel::Sender;
+use crossbeam_channel::TryRecvError;
 use crossbeam_channel::bounded;
+use crossbeam_channel::unbounded;
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
 use std::thread;
+use std::time::Instant;
+#[cfg(windows)]
 use windows::Win32::System::Com::COINIT_MULTITHREADED;
+#[cfg(windows)]
 use windows::Win32::System::Com::CoInitializeEx;
+#[cfg(windows)]
+use windows::Win32::System::Com::CoUninitialize;
 
 pub fn create_worker_thread<ThreadboundMessage, GameboundMessage, WorkerState>(
     config: Res<WorkerConfig<ThreadboundMessage, GameboundMessage, WorkerState>>,
@@ -18,64 +34,286 @@ pub fn create_worker_thread<ThreadboundMessage, GameboundMessage, WorkerState>(
     GameboundMessage: WorkerMessage,
     WorkerState: WorkerStateTrait,
 {
+    let counters = WorkerCounters::default();
     let (game_tx, game_rx) = bounded::<GameboundMessage>(config.gamebound_channel_capacity);
+    let game_tx = match &config.gamebound_backpressure {
+        Backpressure::Block => game_tx,
+        _ => spawn_gamebound_pump(&config, game_tx, game_rx.clone(), &counters),
+    };
     let (thread_tx, thread_rx) = bounded::<ThreadboundMessage>(config.threadbound_channel_capacity);
+    let (status_tx, status_rx) = unbounded::<WorkerStatus>();
+    let cancellation = WorkerCancellation::default();
+    let entity = commands
+        .spawn((Name::new(config.name.clone()), WorkerStatus::Starting))
+        .id();
 
-    commands.insert_resource(Bridge {
-        sender: thread_tx,
-        receiver: game_rx,
-    });
-
+    let worker = config.clone();
     let name = config.name.clone();
-    let handler = config.handle_threadbound_message;
-    let handler_error_handler = config.handle_threadbound_message_error_handler;
-    let sleep_duration = config.sleep_duration;
-    let is_ui_automation_thread = config.is_ui_automation_thread;
-    let receiver = config.threadbound_message_receiver;
-    if let Err(e) = thread::Builder::new().name(name.clone()).spawn(move || {
-        if is_ui_automation_thread {
-            unsafe {
+    let thread_cancellation = cancellation.clone();
+    let thread_counters = counters.clone();
+    let worker_rx = thread_rx.clone();
+    let thread = match thread::Builder::new().name(name.clone()).spawn(move || {
+        thread_cancellation.set_current();
+        #[cfg(windows)]
+        let com_initialized = worker.is_ui_automation_thread
+            && unsafe {
                 // Initialize COM in MTA mode
                 // https://learn.microsoft.com/en-us/dotnet/framework/ui-automation/ui-automation-threading-issues
                 // https://learn.microsoft.com/en-us/windows/win32/com/multithreaded-apartments
-                if let Err(e) = CoInitializeEx(None, COINIT_MULTITHREADED).ok() {
-                    error!("[{}] Failed to initialize COM: {:?}", name, e);
+                match CoInitializeEx(None, COINIT_MULTITHREADED).ok() {
+                    Ok(()) => {
+                        debug!("[{}] COM initialized in MTA mode.", name);
+                        true
+                    }
+                    Err(e) => {
+                        error!("[{}] Failed to initialize COM: {:?}", name, e);
+                        false
+                    }
                 }
-                debug!("[{}] COM initialized in MTA mode.", name);
-            }
+            };
+
+        supervise_worker(
+            &worker,
+            &worker_rx,
+            &game_tx,
+            &status_tx,
+            &thread_cancellation,
+            &thread_counters,
+        );
+
+        #[cfg(windows)]
+        if com_initialized {
+            // The state is gone by now, so no COM objects outlive the apartment
+            unsafe { CoUninitialize() };
+            debug!("[{}] COM uninitialized.", name);
+        }
+        info!("[{}] Thread finished", name);
+    }) {
+        Ok(thread) => {
+            info!("[{}] Thread created", config.name);
+            Some(thread)
+        }
+        Err(e) => {
+            error!("[{}] Failed to spawn thread: {:?}", config.name, e);
+            None
         }
+    };
 
-        let Ok(mut state) = WorkerState::try_default() else {
-            error!("[{}] Failed to initialize state", name);
-            return;
+    commands.insert_resource(Bridge {
+        sender: thread_tx,
+        queued: thread_rx,
+        receiver: game_rx,
+        status: status_rx,
+        entity,
+        thread,
+        cancellation,
+        counters,
+    });
+}
+
+/// Starts a thread applying [`WorkerConfig::gamebound_backpressure`], returning the sender handlers should use.
+///
+/// The thread stops once the worker thread drops that sender.
+fn spawn_gamebound_pump<T, G, S>(
+    config: &WorkerConfig<T, G, S>,
+    game_tx: Sender<G>,
+    game_rx: Receiver<G>,
+    counters: &WorkerCounters,
+) -> Sender<G>
+where
+    T: WorkerMessage,
+    G: WorkerMessage,
+    S: WorkerStateTrait,
+{
+    let (staged_tx, staged_rx) = bounded::<G>(1);
+    let policy = config.gamebound_backpressure.clone();
+    let counters = counters.clone();
+    let unpumped = game_tx.clone();
+    let spawned = thread::Builder::new()
+        .name(format!("{} gamebound", config.name))
+        .spawn(move || {
+            pump_gamebound(&policy, &staged_rx, &game_tx, &game_rx, |stats| {
+                counters.record_gamebound_backpressure(stats)
+            });
+        });
+    match spawned {
+        Ok(_) => staged_tx,
+        Err(e) => {
+            error!(
+                "[{}] Failed to spawn gamebound pump thread, sending without backpressure: {:?}",
+                config.name, e
+            );
+            unpumped
+        }
+    }
+}
+
+/// Runs the worker, restarting it as [`WorkerConfig::supervision`] allows, until it stops or gives up.
+fn supervise_worker<ThreadboundMessage, GameboundMessage, WorkerState>(
+    worker: &WorkerConfig<ThreadboundMessage, GameboundMessage, WorkerState>,
+    thread_rx: &Receiver<ThreadboundMessage>,
+    game_tx: &Sender<GameboundMessage>,
+    status_tx: &Sender<WorkerStatus>,
+    cancellation: &WorkerCancellation,
+    counters: &WorkerCounters,
+) where
+    ThreadboundMessage: WorkerMessage,
+    GameboundMessage: WorkerMessage,
+    WorkerState: WorkerStateTrait,
+{
+    let name = &worker.name;
+    let supervision = &worker.supervision;
+    let mut restarts = 0;
+    loop {
+        _ = status_tx.send(WorkerStatus::Starting);
+        let run = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            run_worker(
+                worker,
+                thread_rx,
+                game_tx,
+                status_tx,
+                cancellation,
+                counters,
+            )
+        }));
+        let reason = match run {
+            Ok(None) => {
+                debug!("[{}] Worker stopped", name);
+                return;
+            }
+            Ok(Some(reason)) => reason,
+            Err(panic) => format!("panicked: {}", panic_message(panic.as_ref())),
         };
+        if cancellation.is_cancelled() {
+            debug!("[{}] Worker failed while shutting down: {}", name, reason);
+            return;
+        }
+        if restarts >= supervision.max_restarts {
+            error!(
+                "[{}] Worker failed after {} restarts, giving up: {}",
+                name, restarts, reason
+            );
+            _ = status_tx.send(WorkerStatus::Dead { reason });
+            return;
+        }
+        restarts += 1;
+        let backoff = supervision.backoff(restarts);
+        warn!(
+            "[{}] Worker failed, restart {}/{} in {:?}: {}",
+            name, restarts, supervision.max_restarts, backoff, reason
+        );
+        _ = status_tx.send(WorkerStatus::Restarting {
+            attempt: restarts,
+            backoff,
+            reason,
+        });
+        if cancellation.sleep(backoff) {
+            debug!("[{}] Shut down while waiting to restart", name);
+            return;
+        }
+    }
+}
 
-        loop {
-            let msg = match (receiver)(&thread_rx, &mut state) {
-                Ok(msg) => msg,
-                Err(e) => {
-                    error!("[{}] Threadbound channel receiver failure, quitting loop: {}", name, format!("{e:?}").trim());
-                    break;
-                }
-            };
-            if let Err(e) = (handler)(&msg, &game_tx, &mut state) {
-                // TODO: leave logging the error to the handler
+/// Builds the state and handles messages until something breaks.
+///
+/// Returns why the worker failed, or `None` once the app has hung up or cancelled it.
+fn run_worker<ThreadboundMessage, GameboundMessage, WorkerState>(
+    config: &WorkerConfig<ThreadboundMessage, GameboundMessage, WorkerState>,
+    thread_rx: &Receiver<ThreadboundMessage>,
+    game_tx: &Sender<GameboundMessage>,
+    status_tx: &Sender<WorkerStatus>,
+    cancellation: &WorkerCancellation,
+    counters: &WorkerCounters,
+) -> Option<String>
+where
+    ThreadboundMessage: WorkerMessage,
+    GameboundMessage: WorkerMessage,
+    WorkerState: WorkerStateTrait,
+{
+    let name = &config.name;
+    let mut guard = match WorkerState::try_default() {
+        Ok(state) => CleanupOnDrop { name, state },
+        Err(e) => {
+            error!("[{}] Failed to initialize state: {:?}", name, e);
+            return Some(format!("failed to initialize state: {e:?}"));
+        }
+    };
+    let state = &mut guard.state;
+    _ = status_tx.send(WorkerStatus::Running);
+
+    loop {
+        if cancellation.is_cancelled() {
+            break None;
+        }
+        let msg = match (config.threadbound_message_receiver)(thread_rx, state) {
+            Ok(msg) => msg,
+            Err(e) if is_disconnected(&e) => break None,
+            Err(e) => {
+                let e = format!("{e:?}");
                 error!(
-                    "[{}] Failed to process thread message {:?}, got error {:?}",
-                    name, msg, e
+                    "[{}] Threadbound channel receiver failure, quitting loop: {}",
+                    name,
+                    e.trim()
+                );
+                break Some(format!(
+                    "threadbound channel receiver failure: {}",
+                    e.trim()
+                ));
+            }
+        };
+        let started = Instant::now();
+        let handled = (config.handle_threadbound_message)(&msg, game_tx, state);
+        counters.record_handler(started.elapsed(), handled.is_err());
+        if let Err(e) = handled {
+            // TODO: leave logging the error to the handler
+            error!(
+                "[{}] Failed to process thread message {:?}, got error {:?}",
+                name, msg, e
+            );
+            if let Err(ee) =
+                (config.handle_threadbound_message_error_handler)(&msg, game_tx, state, &e)
+            {
+                error!(
+                    "[{}] BAD NEWS! Failed while processing error handler for message {:?} that produced error {:?}, got new error {:?}",
+                    name, msg, e, ee
                 );
-                if let Err(ee) = (handler_error_handler)(&msg, &game_tx, &mut state, &e) {
-                    error!(
-                        "[{}] BAD NEWS! Failed while processing error handler for message {:?} that produced error {:?}, got new error {:?}",
-                        name, msg, e, ee
-                    );
-                }
             }
-            std::thread::sleep(sleep_duration);
         }
-    }) {
-        error!("[{}] Failed to spawn thread: {:?}", config.name, e);
+        cancellation.sleep(config.sleep_duration);
+    }
+}
+
+/// Runs [`WorkerStateTrait::cleanup`] when the worker returns or a handler panics.
+struct CleanupOnDrop<'a, WorkerState: WorkerStateTrait> {
+    name: &'a str,
+    state: WorkerState,
+}
+impl<WorkerState: WorkerStateTrait> Drop for CleanupOnDrop<'_, WorkerState> {
+    fn drop(&mut self) {
+        debug!("[{}] Cleaning up worker state", self.name);
+        self.state.cleanup();
+    }
+}
+
+/// Whether a receiver failed only because every sender is gone, which is how the app says goodbye.
+fn is_disconnected(error: &BevyError) -> bool {
+    error.downcast_ref::<RecvError>().is_some()
+        || matches!(
+            error.downcast_ref::<TryRecvError>(),
+            Some(TryRecvError::Disconnected)
+        )
+        || matches!(
+            error.downcast_ref::<RecvTimeoutError>(),
+            Some(RecvTimeoutError::Disconnected)
+        )
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
     } else {
-        info!("[{}] Thread created", config.name);
+        "unknown panic payload"
     }
 }
@@ -1,17 +1,37 @@
+mod backpressure;
 mod bridge;
+mod builder;
+mod channel_overrides;
 mod config;
 mod create;
 mod message;
+mod metrics;
 mod phantom_holder;
 mod plugin;
+mod rpc;
+mod shutdown;
 mod state;
+mod supervision;
+#[cfg(any(test, feature = "test-support"))]
+mod testing;
+mod tick;
 
+pub use backpressure::*;
 pub use bridge::*;
+pub use builder::*;
+pub use channel_overrides::*;
 pub use config::*;
 pub use create::*;
 pub use message::*;
+pub use metrics::*;
 pub use phantom_holder::*;
 pub use plugin::*;
+pub use rpc::*;
+pub use shutdown::*;
 pub use state::*;
+pub use supervision::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use testing::*;
+pub use tick::*;
 
 pub use crossbeam_channel::*;
@@ -1,24 +1,52 @@
 use bevy::ecs::event::Event;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
+use std::sync::Arc;
 
 pub trait WorkerMessage: std::fmt::Debug + Event + Send + Sync + Clone + 'static {}
 impl<T> WorkerMessage for T where T: std::fmt::Debug + Event + Send + Sync + Clone + 'static {}
 
-pub type ThreadboundMessageHandler<ThreadboundMessage, GameboundMessage, WorkerState> =
-    fn(
-        msg: &ThreadboundMessage,
-        reply_tx: &Sender<GameboundMessage>,
-        state: &mut WorkerState,
-    ) -> bevy::prelude::Result<()>;
+/// Called with each message, its sender and the state.
+pub type ThreadboundMessageHandler<ThreadboundMessage, GameboundMessage, WorkerState> = Arc<
+    dyn Fn(
+            &ThreadboundMessage,
+            &Sender<GameboundMessage>,
+            &mut WorkerState,
+        ) -> bevy::prelude::Result<()>
+        + Send
+        + Sync,
+>;
 
-pub type ThreadboundMessageErrorHandler<ThreadboundMessage, GameboundMessage, WorkerState> =
-    fn(
-        msg: &ThreadboundMessage,
-        reply_tx: &Sender<GameboundMessage>,
-        state: &mut WorkerState,
-        error: &bevy::prelude::BevyError,
-    ) -> bevy::prelude::Result<()>;
+/// Called with each message the handler failed on and the error it failed with.
+pub type ThreadboundMessageErrorHandler<ThreadboundMessage, GameboundMessage, WorkerState> = Arc<
+    dyn Fn(
+            &ThreadboundMessage,
+            &Sender<GameboundMessage>,
+            &mut WorkerState,
+            &bevy::prelude::BevyError,
+        ) -> bevy::prelude::Result<()>
+        + Send
+        + Sync,
+>;
 
+/// Waits for the next message to handle, see [`crate::receive_blocking`] and [`crate::receive_latest_only`].
 pub type ThreadboundMessageReceiver<T, S> =
-    fn(thread_rx: &Receiver<T>, state: &mut S) -> bevy::prelude::Result<T>;
+    Arc<dyn Fn(&Receiver<T>, &mut S) -> bevy::prelude::Result<T> + Send + Sync>;
+
+/// Waits for the next message, the default.
+pub fn receive_blocking<T, S>(thread_rx: &Receiver<T>, _state: &mut S) -> bevy::prelude::Result<T> {
+    thread_rx.recv().map_err(bevy::prelude::BevyError::from)
+}
+
+/// Waits for the next message but skips to the newest one when several are queued.
+///
+/// For workers that poll something, where only the latest request matters once they fall behind.
+pub fn receive_latest_only<T, S>(
+    thread_rx: &Receiver<T>,
+    state: &mut S,
+) -> bevy::prelude::Result<T> {
+    match thread_rx.try_iter().last() {
+        Some(msg) => Ok(msg),
+        None => receive_blocking(thread_rx, state),
+    }
+}
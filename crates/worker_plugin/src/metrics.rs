@@ -0,0 +1,204 @@
+use crate::BackpressureStats;
+use crate::Bridge;
+use crate::WorkerConfig;
+use crate::WorkerMessage;
+use crate::WorkerStateTrait;
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Upper bounds of the handler latency buckets, anything slower lands in one last bucket.
+pub const LATENCY_BUCKET_BOUNDS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1000, 5000];
+
+/// How every worker is keeping up, by worker name.
+#[derive(Resource, Reflect, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct WorkerMetrics {
+    pub workers: BTreeMap<String, WorkerStats>,
+}
+
+#[derive(Reflect, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkerStats {
+    /// Threadbound messages queued for the thread.
+    pub messages_in: u64,
+    /// Gamebound messages the thread sent back.
+    pub messages_out: u64,
+    /// Threadbound messages that never made it into the channel because the thread was not keeping up.
+    pub dropped_full: u64,
+    /// Threadbound messages dropped because the thread was gone.
+    pub dropped_disconnected: u64,
+    pub handler_errors: u64,
+    pub threadbound_queue_depth: usize,
+    pub gamebound_queue_depth: usize,
+    pub threadbound_backpressure: BackpressureStats,
+    pub gamebound_backpressure: BackpressureStats,
+    pub handler_latency: LatencyHistogram,
+}
+
+#[derive(Reflect, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    /// Calls per bucket of [`LATENCY_BUCKET_BOUNDS_MS`], plus one for slower calls.
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub total_us: u64,
+    pub max_us: u64,
+}
+impl LatencyHistogram {
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.total_us / self.count))
+    }
+}
+
+/// What the worker thread counts itself, read by the app every frame.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerCounters(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    handler_errors: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKET_BOUNDS_MS.len() + 1],
+    count: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    /// Updated by the gamebound pump, if the worker has one.
+    gamebound_backpressure: Mutex<BackpressureStats>,
+}
+
+impl WorkerCounters {
+    pub fn record_handler(&self, latency: Duration, failed: bool) {
+        let counters = &self.0;
+        let latency_ms = latency.as_millis();
+        let bucket = LATENCY_BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| latency_ms < *bound as u128)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_MS.len());
+        let latency_us = latency.as_micros().min(u64::MAX as u128) as u64;
+        counters.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        counters.count.fetch_add(1, Ordering::Relaxed);
+        counters.total_us.fetch_add(latency_us, Ordering::Relaxed);
+        counters.max_us.fetch_max(latency_us, Ordering::Relaxed);
+        if failed {
+            counters.handler_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_gamebound_backpressure(&self, stats: &BackpressureStats) {
+        *self
+            .0
+            .gamebound_backpressure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = stats.clone();
+    }
+
+    fn read_into(&self, stats: &mut WorkerStats) {
+        let counters = &self.0;
+        stats.handler_errors = counters.handler_errors.load(Ordering::Relaxed);
+        stats.handler_latency = LatencyHistogram {
+            buckets: counters
+                .buckets
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Relaxed))
+                .collect(),
+            count: counters.count.load(Ordering::Relaxed),
+            total_us: counters.total_us.load(Ordering::Relaxed),
+            max_us: counters.max_us.load(Ordering::Relaxed),
+        };
+        stats.gamebound_backpressure = counters
+            .gamebound_backpressure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+    }
+}
+
+pub fn update_worker_metrics<T, G, S>(
+    config: Res<WorkerConfig<T, G, S>>,
+    bridge: Res<Bridge<T, G>>,
+    mut metrics: ResMut<WorkerMetrics>,
+) where
+    T: WorkerMessage,
+    G: WorkerMessage,
+    S: WorkerStateTrait,
+{
+    let stats = metrics.workers.entry(config.name.clone()).or_default();
+    stats.threadbound_queue_depth = bridge.sender.len();
+    stats.gamebound_queue_depth = bridge.receiver.len();
+    bridge.counters.read_into(stats);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Sender;
+    use crate::WorkerMetrics;
+    use crate::WorkerPlugin;
+    use bevy::prelude::*;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Work(u64);
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Done;
+
+    fn handle(msg: &Work, reply_tx: &Sender<Done>, _: &mut ()) -> Result<()> {
+        std::thread::sleep(Duration::from_millis(msg.0));
+        if msg.0 == 0 {
+            return Err("no work to do".into());
+        }
+        reply_tx.send(Done)?;
+        Ok(())
+    }
+
+    /// Updates until the worker has handled `count` messages in total.
+    fn wait_for_handled(app: &mut App, count: u64) {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            app.update();
+            let metrics = app.world().resource::<WorkerMetrics>();
+            if metrics.workers["Slow"].handler_latency.count == count {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("worker never handled {count} messages");
+    }
+
+    #[test]
+    fn slow_workers_show_up_in_the_metrics() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(
+            WorkerPlugin::<Work, Done, ()>::builder("Slow")
+                .on_message(handle)
+                .threadbound_channel_capacity(1)
+                .build(),
+        );
+        app.update();
+        app.world_mut().send_event(Work(50));
+        app.update();
+        // Once the thread is busy with the first message, one more fits in the channel and the rest are dropped
+        std::thread::sleep(Duration::from_millis(20));
+        for _ in 0..3 {
+            app.world_mut().send_event(Work(50));
+        }
+        wait_for_handled(&mut app, 2);
+        app.world_mut().send_event(Work(0));
+        wait_for_handled(&mut app, 3);
+
+        let stats = &app.world().resource::<WorkerMetrics>().workers["Slow"];
+        assert_eq!(stats.messages_in, 3, "{stats:?}");
+        assert_eq!(stats.dropped_full, 2, "{stats:?}");
+        assert_eq!(stats.messages_out, 2, "{stats:?}");
+        assert_eq!(stats.handler_errors, 1, "{stats:?}");
+        assert_eq!(stats.handler_latency.buckets[0], 1, "{stats:?}");
+        assert_eq!(stats.handler_latency.buckets[4], 2, "{stats:?}");
+        assert!(stats.handler_latency.mean().unwrap() >= Duration::from_millis(33));
+    }
+}
@@ -1,13 +1,24 @@
+use crate::TickControlSender;
+use crate::WorkerChannelOverrides;
 use crate::WorkerConfig;
 use crate::WorkerMessage;
+use crate::WorkerMetrics;
 use crate::WorkerStateTrait;
+use crate::WorkerStatus;
+use crate::WorkerTickControl;
+use crate::apply_worker_status;
 use crate::bridge_requests;
 use crate::bridge_responses;
 use crate::create_worker_thread;
+use crate::forward_tick_control;
+use crate::shutdown_worker_on_exit;
+use crate::update_worker_metrics;
 use bevy::app::App;
+use bevy::app::Last;
 use bevy::app::Plugin;
 use bevy::app::Startup;
 use bevy::app::Update;
+use bevy::ecs::schedule::IntoScheduleConfigs;
 use bevy::reflect::GetTypeRegistration;
 
 pub struct WorkerPlugin<ThreadboundMessage, GameboundMessage, WorkerState>
@@ -29,9 +40,30 @@ where
     fn build(&self, app: &mut App) {
         app.register_type::<ThreadboundMessage>();
         app.register_type::<GameboundMessage>();
+        app.register_type::<WorkerStatus>();
+        app.register_type::<WorkerMetrics>();
+        app.init_resource::<WorkerMetrics>();
         app.add_event::<ThreadboundMessage>();
         app.add_event::<GameboundMessage>();
-        app.insert_resource(self.config.clone());
+        let mut config = self.config.clone();
+        if let Some(channel_override) = app
+            .world()
+            .get_resource::<WorkerChannelOverrides>()
+            .and_then(|overrides| overrides.0.get(&config.name))
+        {
+            if let Some(capacity) = channel_override.gamebound_channel_capacity {
+                config.gamebound_channel_capacity = capacity;
+            }
+            if let Some(capacity) = channel_override.threadbound_channel_capacity {
+                config.threadbound_channel_capacity = capacity;
+            }
+        }
+        if let Some(tick_control) = config.tick_control.clone() {
+            app.add_event::<WorkerTickControl<ThreadboundMessage>>();
+            app.insert_resource(TickControlSender::<ThreadboundMessage>::new(tick_control));
+            app.add_systems(Update, forward_tick_control::<ThreadboundMessage>);
+        }
+        app.insert_resource(config);
         app.add_systems(
             Startup,
             create_worker_thread::<ThreadboundMessage, GameboundMessage, WorkerState>,
@@ -44,5 +76,18 @@ where
             Update,
             bridge_responses::<ThreadboundMessage, GameboundMessage, WorkerState>,
         );
+        app.add_systems(
+            Update,
+            update_worker_metrics::<ThreadboundMessage, GameboundMessage, WorkerState>
+                .after(bridge_responses::<ThreadboundMessage, GameboundMessage, WorkerState>),
+        );
+        app.add_systems(
+            Update,
+            apply_worker_status::<ThreadboundMessage, GameboundMessage, WorkerState>,
+        );
+        app.add_systems(
+            Last,
+            shutdown_worker_on_exit::<ThreadboundMessage, GameboundMessage, WorkerState>,
+        );
     }
 }
@@ -0,0 +1,341 @@
+use crate::Sender;
+use crate::WorkerPlugin;
+use crate::WorkerStateTrait;
+use crate::bridge_responses;
+use bevy::ecs::system::SystemParam;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
+use bevy::reflect::Typed;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// What a request or response sent through [`RpcPlugin`] must be.
+pub trait RpcMessage:
+    std::fmt::Debug + Clone + Send + Sync + FromReflect + Typed + GetTypeRegistration + 'static
+{
+}
+impl<T> RpcMessage for T where
+    T: std::fmt::Debug + Clone + Send + Sync + FromReflect + Typed + GetTypeRegistration + 'static
+{
+}
+
+/// Worker state that answers every request with exactly one response.
+pub trait RpcWorker: WorkerStateTrait {
+    type Request: RpcMessage;
+    type Response: RpcMessage;
+
+    /// An error is sent back to the caller as [`RpcOutcome::Failed`].
+    fn handle(&mut self, request: &Self::Request) -> Result<Self::Response>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct RpcRequestId(pub u64);
+
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct RpcRequest<Req: RpcMessage> {
+    pub id: RpcRequestId,
+    pub request: Req,
+}
+
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct RpcResponse<Resp: RpcMessage> {
+    pub id: RpcRequestId,
+    /// The handler's error is flattened to text, since [`BevyError`] cannot be cloned into an event.
+    pub result: Result<Resp, String>,
+}
+
+/// Sent when a request got no response within its timeout, its caller will not hear back.
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct RpcTimedOut<Req: RpcMessage> {
+    pub id: RpcRequestId,
+    pub request: Req,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcOutcome<Resp> {
+    Replied(Resp),
+    Failed(String),
+    TimedOut,
+}
+
+struct PendingCall<Req> {
+    request: Req,
+    timeout: Duration,
+    deadline: Duration,
+}
+
+struct FinishedCall<Resp> {
+    outcome: RpcOutcome<Resp>,
+    finished_at: Duration,
+}
+
+/// Calls in flight and outcomes waiting to be polled, for the worker whose state is `S`.
+#[derive(Resource)]
+pub struct RpcCalls<S: RpcWorker> {
+    pub default_timeout: Duration,
+    /// Outcomes nobody polls are forgotten after this long, callers that only read events never poll.
+    pub outcome_retention: Duration,
+    next_id: u64,
+    pending: HashMap<RpcRequestId, PendingCall<S::Request>>,
+    finished: HashMap<RpcRequestId, FinishedCall<S::Response>>,
+}
+impl<S: RpcWorker> RpcCalls<S> {
+    pub fn new(default_timeout: Duration) -> Self {
+        Self {
+            default_timeout,
+            outcome_retention: Duration::from_secs(30),
+            next_id: 1,
+            pending: HashMap::default(),
+            finished: HashMap::default(),
+        }
+    }
+}
+
+/// Sends requests to the worker whose state is `S` and polls for their outcomes.
+#[derive(SystemParam)]
+pub struct Rpc<'w, S: RpcWorker> {
+    calls: ResMut<'w, RpcCalls<S>>,
+    requests: EventWriter<'w, RpcRequest<<S as RpcWorker>::Request>>,
+    time: Res<'w, Time>,
+}
+impl<S: RpcWorker> Rpc<'_, S> {
+    /// Sends `request` with [`RpcCalls::default_timeout`].
+    pub fn call(&mut self, request: S::Request) -> RpcRequestId {
+        let timeout = self.calls.default_timeout;
+        self.call_with_timeout(request, timeout)
+    }
+
+    pub fn call_with_timeout(&mut self, request: S::Request, timeout: Duration) -> RpcRequestId {
+        let id = RpcRequestId(self.calls.next_id);
+        self.calls.next_id += 1;
+        self.calls.pending.insert(
+            id,
+            PendingCall {
+                request: request.clone(),
+                timeout,
+                deadline: self.time.elapsed() + timeout,
+            },
+        );
+        self.requests.write(RpcRequest { id, request });
+        id
+    }
+
+    /// Takes the outcome of `id` once there is one, `None` while the worker is still on it.
+    pub fn poll(&mut self, id: RpcRequestId) -> Option<RpcOutcome<S::Response>> {
+        self.calls
+            .finished
+            .remove(&id)
+            .map(|finished| finished.outcome)
+    }
+
+    pub fn is_pending(&self, id: RpcRequestId) -> bool {
+        self.calls.pending.contains_key(&id)
+    }
+}
+
+/// Adds a [`WorkerPlugin`] whose thread answers [`RpcRequest`]s through [`RpcWorker::handle`].
+pub struct RpcPlugin<S: RpcWorker> {
+    pub name: String,
+    pub default_timeout: Duration,
+    _state: PhantomData<fn() -> S>,
+}
+impl<S: RpcWorker> RpcPlugin<S> {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default_timeout: Duration::from_secs(5),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<S> Plugin for RpcPlugin<S>
+where
+    S: RpcWorker + Send + Sync,
+{
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            WorkerPlugin::<RpcRequest<S::Request>, RpcResponse<S::Response>, S>::builder(
+                self.name.clone(),
+            )
+            .on_message(handle_rpc_request::<S>)
+            .build(),
+        );
+        app.add_event::<RpcTimedOut<S::Request>>();
+        app.register_type::<RpcTimedOut<S::Request>>();
+        app.insert_resource(RpcCalls::<S>::new(self.default_timeout));
+        app.add_systems(
+            Update,
+            (collect_rpc_responses::<S>, expire_rpc_calls::<S>)
+                .chain()
+                .after(bridge_responses::<RpcRequest<S::Request>, RpcResponse<S::Response>, S>),
+        );
+    }
+}
+
+fn handle_rpc_request<S: RpcWorker>(
+    msg: &RpcRequest<S::Request>,
+    reply_tx: &Sender<RpcResponse<S::Response>>,
+    state: &mut S,
+) -> Result<()> {
+    // Errors go back to the caller rather than to the error handler, so it is not left waiting for the timeout
+    let result = state.handle(&msg.request).map_err(|e| e.to_string());
+    reply_tx.send(RpcResponse { id: msg.id, result })?;
+    Ok(())
+}
+
+fn collect_rpc_responses<S: RpcWorker>(
+    mut responses: EventReader<RpcResponse<S::Response>>,
+    mut calls: ResMut<RpcCalls<S>>,
+    time: Res<Time>,
+) {
+    for response in responses.read() {
+        if calls.pending.remove(&response.id).is_none() {
+            debug!(
+                "RPC response for {:?} arrived after it timed out, dropping it",
+                response.id
+            );
+            continue;
+        }
+        let outcome = match &response.result {
+            Ok(response) => RpcOutcome::Replied(response.clone()),
+            Err(error) => RpcOutcome::Failed(error.clone()),
+        };
+        calls.finished.insert(
+            response.id,
+            FinishedCall {
+                outcome,
+                finished_at: time.elapsed(),
+            },
+        );
+    }
+}
+
+fn expire_rpc_calls<S: RpcWorker>(
+    mut calls: ResMut<RpcCalls<S>>,
+    mut timeouts: EventWriter<RpcTimedOut<S::Request>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed();
+    let expired = calls
+        .pending
+        .iter()
+        .filter(|(_, call)| call.deadline <= now)
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    for id in expired {
+        let Some(call) = calls.pending.remove(&id) else {
+            continue;
+        };
+        warn!(
+            "RPC request {:?} timed out after {:?}: {:?}",
+            id, call.timeout, call.request
+        );
+        calls.finished.insert(
+            id,
+            FinishedCall {
+                outcome: RpcOutcome::TimedOut,
+                finished_at: now,
+            },
+        );
+        timeouts.write(RpcTimedOut {
+            id,
+            request: call.request,
+            timeout: call.timeout,
+        });
+    }
+    let retention = calls.outcome_retention;
+    calls
+        .finished
+        .retain(|_, finished| now.saturating_sub(finished.finished_at) < retention);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Rpc;
+    use crate::RpcOutcome;
+    use crate::RpcPlugin;
+    use crate::RpcRequestId;
+    use crate::RpcTimedOut;
+    use crate::RpcWorker;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Default)]
+    struct Doubler;
+    impl RpcWorker for Doubler {
+        type Request = u32;
+        type Response = u32;
+
+        fn handle(&mut self, request: &u32) -> Result<u32> {
+            match request {
+                0 => Err("zero has no double worth computing".into()),
+                n if *n > 1000 => {
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok(n * 2)
+                }
+                n => Ok(n * 2),
+            }
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(RpcPlugin::<Doubler>::new("Doubler"));
+        // Startup spawns the worker thread
+        app.update();
+        app
+    }
+
+    fn call(app: &mut App, request: u32, timeout: Duration) -> RpcRequestId {
+        app.world_mut()
+            .run_system_once(move |mut rpc: Rpc<Doubler>| rpc.call_with_timeout(request, timeout))
+            .unwrap()
+    }
+
+    /// Updates until `id` has an outcome, the worker replies on its own thread.
+    fn wait(app: &mut App, id: RpcRequestId) -> RpcOutcome<u32> {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            app.update();
+            let outcome = app
+                .world_mut()
+                .run_system_once(move |mut rpc: Rpc<Doubler>| rpc.poll(id))
+                .unwrap();
+            if let Some(outcome) = outcome {
+                return outcome;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("{id:?} never finished");
+    }
+
+    #[test]
+    fn replies_find_their_request() {
+        let mut app = app();
+        let first = call(&mut app, 2, Duration::from_secs(5));
+        let second = call(&mut app, 21, Duration::from_secs(5));
+        let failing = call(&mut app, 0, Duration::from_secs(5));
+        assert_eq!(wait(&mut app, second), RpcOutcome::Replied(42));
+        assert_eq!(wait(&mut app, first), RpcOutcome::Replied(4));
+        assert!(matches!(wait(&mut app, failing), RpcOutcome::Failed(_)));
+    }
+
+    #[test]
+    fn timeouts_are_events() {
+        let mut app = app();
+        let slow = call(&mut app, 5000, Duration::from_millis(20));
+        assert_eq!(wait(&mut app, slow), RpcOutcome::TimedOut);
+        let timeouts = app.world().resource::<Events<RpcTimedOut<u32>>>();
+        let timeouts = timeouts.iter_current_update_events().collect::<Vec<_>>();
+        assert!(
+            matches!(timeouts.as_slice(), [RpcTimedOut { id, request: 5000, .. }] if *id == slow),
+            "{timeouts:?}"
+        );
+    }
+}
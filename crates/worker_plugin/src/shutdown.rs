@@ -0,0 +1,212 @@
+use crate::Bridge;
+use crate::WorkerConfig;
+use crate::WorkerMessage;
+use crate::WorkerStateTrait;
+use bevy::prelude::*;
+use crossbeam_channel::bounded;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+type Waker = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Tells a worker thread to stop, shared between the app and the thread.
+#[derive(Clone, Default)]
+pub struct WorkerCancellation {
+    state: Arc<(Mutex<CancellationState>, Condvar)>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<WorkerCancellation>> = const { RefCell::new(None) };
+}
+
+impl WorkerCancellation {
+    /// The cancellation of the worker running on this thread, for handlers that block outside the threadbound channel.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    pub(crate) fn set_current(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.clone()));
+    }
+
+    pub fn cancel(&self) {
+        let (lock, condvar) = &*self.state;
+        let wakers = {
+            let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+            if state.cancelled {
+                return;
+            }
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+        condvar.notify_all();
+        for wake in wakers {
+            wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap_or_else(|e| e.into_inner()).cancelled
+    }
+
+    /// Runs `wake` once cancelled, or right away if already cancelled.
+    ///
+    /// For unblocking a handler stuck on something only it knows about, like accepting connections.
+    pub fn on_cancel(&self, wake: impl FnOnce() + Send + 'static) {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        if state.cancelled {
+            drop(state);
+            wake();
+        } else {
+            state.wakers.push(Box::new(wake));
+        }
+    }
+
+    /// Sleeps for `duration` unless cancelled first, returning whether it was.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let (lock, condvar) = &*self.state;
+        let state = lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (state, _) = condvar
+            .wait_timeout_while(state, duration, |state| !state.cancelled)
+            .unwrap_or_else(|e| e.into_inner());
+        state.cancelled
+    }
+}
+
+impl std::fmt::Debug for WorkerCancellation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkerCancellation")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Stops the worker thread once the app exits, waiting up to [`WorkerConfig::shutdown_timeout`] for it.
+pub fn shutdown_worker_on_exit<T, G, S>(
+    config: Res<WorkerConfig<T, G, S>>,
+    mut bridge: ResMut<Bridge<T, G>>,
+    mut exits: EventReader<AppExit>,
+) where
+    T: WorkerMessage,
+    G: WorkerMessage,
+    S: WorkerStateTrait,
+{
+    if exits.read().last().is_none() {
+        return;
+    }
+    let Some(thread) = bridge.thread.take() else {
+        return;
+    };
+    info!("[{}] Shutting down worker", config.name);
+    let started = Instant::now();
+    bridge.cancellation.cancel();
+    // Dropping the only sender wakes a receiver blocked on the channel
+    bridge.sender = bounded(0).0;
+    while !thread.is_finished() && started.elapsed() < config.shutdown_timeout {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    if !thread.is_finished() {
+        warn!(
+            "[{}] Worker did not stop within {:?}, leaving it behind",
+            config.name, config.shutdown_timeout
+        );
+        return;
+    }
+    match thread.join() {
+        Ok(()) => info!(
+            "[{}] Worker shut down after {:?}",
+            config.name,
+            started.elapsed()
+        ),
+        Err(_) => error!("[{}] Worker panicked while shutting down", config.name),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Bridge;
+    use crate::Sender;
+    use crate::WorkerCancellation;
+    use crate::WorkerPlugin;
+    use crate::WorkerStateTrait;
+    use bevy::prelude::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Block;
+
+    static CLEANED_UP: AtomicBool = AtomicBool::new(false);
+
+    struct Tidy;
+    impl WorkerStateTrait for Tidy {
+        type Error = ();
+        fn try_default() -> Result<Self, Self::Error> {
+            Ok(Tidy)
+        }
+        fn cleanup(&mut self) {
+            CLEANED_UP.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Blocks the way the IPC accept loop does, until its cancellation wakes it.
+    fn block_until_cancelled(_: &Block, _: &Sender<Block>, _: &mut Tidy) -> Result<()> {
+        let (wake_tx, wake_rx) = crossbeam_channel::bounded::<()>(1);
+        WorkerCancellation::current()
+            .unwrap()
+            .on_cancel(move || _ = wake_tx.send(()));
+        wake_rx.recv()?;
+        Ok(())
+    }
+
+    fn app(handler: fn(&Block, &Sender<Block>, &mut Tidy) -> Result<()>) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(
+            WorkerPlugin::<Block, Block, Tidy>::builder("Tidy")
+                .on_message(handler)
+                .shutdown_timeout(Duration::from_secs(5))
+                .build(),
+        );
+        app.update();
+        app
+    }
+
+    #[test]
+    fn exiting_joins_the_worker_and_cleans_up() {
+        let mut app = app(block_until_cancelled);
+        app.world_mut().send_event(Block);
+        app.update();
+        // Give the worker a moment to get stuck in the handler
+        std::thread::sleep(Duration::from_millis(20));
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        let bridge = app.world().resource::<Bridge<Block, Block>>();
+        assert!(bridge.thread.is_none());
+        assert!(bridge.cancellation.is_cancelled());
+        assert!(CLEANED_UP.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cancelling_interrupts_sleep() {
+        let cancellation = WorkerCancellation::default();
+        let sleeper = cancellation.clone();
+        let thread = std::thread::spawn(move || sleeper.sleep(Duration::from_secs(30)));
+        cancellation.cancel();
+        assert!(thread.join().unwrap());
+        assert!(!WorkerCancellation::default().sleep(Duration::ZERO));
+    }
+}
@@ -1,6 +1,12 @@
 pub trait WorkerStateTrait: 'static + Sized {
-    type Error;
+    type Error: std::fmt::Debug;
     fn try_default() -> Result<Self, Self::Error>;
+
+    /// Runs on the worker thread when it stops or fails, panics included, before the state is dropped.
+    ///
+    /// States that get this trait from [`Default`] cannot override it,
+    /// give them a [`Drop`] impl instead, which runs on the worker thread right after.
+    fn cleanup(&mut self) {}
 }
 impl<T> WorkerStateTrait for T
 where
@@ -0,0 +1,250 @@
+use crate::Bridge;
+use crate::WorkerConfig;
+use crate::WorkerMessage;
+use crate::WorkerStateTrait;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// What happens when a worker thread fails to build its state or its receiver stops working.
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct WorkerSupervision {
+    /// Restarts over the worker's whole life before giving up, zero never restarts it.
+    pub max_restarts: u32,
+    pub initial_backoff: Duration,
+    /// The backoff doubles after every restart, up to this.
+    pub max_backoff: Duration,
+    pub on_give_up: WorkerGiveUp,
+}
+impl Default for WorkerSupervision {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            on_give_up: WorkerGiveUp::StayDead,
+        }
+    }
+}
+impl WorkerSupervision {
+    /// Never restart and never take the app down, a failed worker just stays dead.
+    pub fn never_restart() -> Self {
+        Self {
+            max_restarts: 0,
+            ..default()
+        }
+    }
+
+    /// How long to wait before restart number `attempt`, counting from one.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkerGiveUp {
+    /// Keep the app running without the worker.
+    #[default]
+    StayDead,
+    /// Exit the app with an error, for workers it cannot do without.
+    ExitApp,
+}
+
+/// Where a worker thread is in its life, on the entity named after the worker.
+#[derive(Component, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub enum WorkerStatus {
+    Starting,
+    Running,
+    /// Failed and waiting out `backoff` before restart number `attempt`.
+    Restarting {
+        attempt: u32,
+        backoff: Duration,
+        reason: String,
+    },
+    /// Out of restarts, the worker no longer handles messages.
+    Dead {
+        reason: String,
+    },
+}
+
+pub fn apply_worker_status<T, G, S>(
+    config: Res<WorkerConfig<T, G, S>>,
+    bridge: Res<Bridge<T, G>>,
+    mut statuses: Query<&mut WorkerStatus>,
+    mut exit: EventWriter<AppExit>,
+) where
+    T: WorkerMessage,
+    G: WorkerMessage,
+    S: WorkerStateTrait,
+{
+    for status in bridge.status.try_iter() {
+        if let WorkerStatus::Dead { reason } = &status
+            && config.supervision.on_give_up == WorkerGiveUp::ExitApp
+        {
+            error!(
+                "[{}] Worker is dead and the app cannot run without it, exiting: {}",
+                config.name, reason
+            );
+            exit.write(AppExit::error());
+        }
+        if let Ok(mut current) = statuses.get_mut(bridge.entity) {
+            *current = status;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::WorkerConfig;
+    use crate::WorkerGiveUp;
+    use crate::WorkerPlugin;
+    use crate::WorkerStateTrait;
+    use crate::WorkerStatus;
+    use crate::WorkerSupervision;
+    use bevy::prelude::*;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Ping;
+
+    /// Fails to start every time.
+    struct Broken;
+    impl WorkerStateTrait for Broken {
+        type Error = String;
+        fn try_default() -> Result<Self, Self::Error> {
+            Err("deliberately broken".to_string())
+        }
+    }
+
+    /// Panics on every message, counting the cleanups that still happen.
+    struct Panicky;
+    static PANICKY_CLEANUPS: AtomicU32 = AtomicU32::new(0);
+    impl WorkerStateTrait for Panicky {
+        type Error = String;
+        fn try_default() -> Result<Self, Self::Error> {
+            Ok(Panicky)
+        }
+        fn cleanup(&mut self) {
+            PANICKY_CLEANUPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Fails to start twice, then works.
+    struct Flaky;
+    static FLAKY_STARTS: AtomicU32 = AtomicU32::new(0);
+    impl WorkerStateTrait for Flaky {
+        type Error = String;
+        fn try_default() -> Result<Self, Self::Error> {
+            if FLAKY_STARTS.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err("not yet".to_string())
+            } else {
+                Ok(Flaky)
+            }
+        }
+    }
+
+    fn supervision(max_restarts: u32, on_give_up: WorkerGiveUp) -> WorkerSupervision {
+        WorkerSupervision {
+            max_restarts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            on_give_up,
+        }
+    }
+
+    fn app<S: WorkerStateTrait + Send + Sync>(supervision: WorkerSupervision) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(WorkerPlugin {
+            config: WorkerConfig::<Ping, Ping, S> {
+                name: "Supervised".to_string(),
+                supervision,
+                ..default()
+            },
+        });
+        app
+    }
+
+    /// Updates until `done` holds for the worker's status, which changes on the worker's own thread.
+    fn statuses_until(app: &mut App, done: impl Fn(&WorkerStatus) -> bool) {
+        let mut seen: Vec<WorkerStatus> = Vec::new();
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            app.update();
+            let mut query = app.world_mut().query::<&WorkerStatus>();
+            let status = query.single(app.world()).unwrap().clone();
+            if seen.last() != Some(&status) {
+                seen.push(status.clone());
+            }
+            if done(&status) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("worker never got there, saw {seen:?}");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        let supervision = WorkerSupervision {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            ..default()
+        };
+        assert_eq!(supervision.backoff(1), Duration::from_millis(100));
+        assert_eq!(supervision.backoff(2), Duration::from_millis(200));
+        assert_eq!(supervision.backoff(3), Duration::from_millis(350));
+        assert_eq!(supervision.backoff(u32::MAX), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn broken_workers_die_after_their_restarts() {
+        let mut app = app::<Broken>(supervision(2, WorkerGiveUp::StayDead));
+        statuses_until(
+            &mut app,
+            |status| matches!(status, WorkerStatus::Dead { reason } if reason.contains("deliberately broken")),
+        );
+        assert!(app.world().resource::<Events<AppExit>>().is_empty());
+    }
+
+    #[test]
+    fn flaky_workers_recover() {
+        let mut app = app::<Flaky>(supervision(5, WorkerGiveUp::StayDead));
+        statuses_until(&mut app, |status| *status == WorkerStatus::Running);
+        assert_eq!(FLAKY_STARTS.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn panicking_workers_still_clean_up() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(
+            WorkerPlugin::<Ping, Ping, Panicky>::builder("Panicky")
+                .on_message(|_, _, _| panic!("deliberately panicking"))
+                .supervision(supervision(0, WorkerGiveUp::StayDead))
+                .build(),
+        );
+        app.update();
+        app.world_mut().send_event(Ping);
+        statuses_until(
+            &mut app,
+            |status| matches!(status, WorkerStatus::Dead { reason } if reason.contains("deliberately panicking")),
+        );
+        assert_eq!(PANICKY_CLEANUPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn essential_workers_take_the_app_down() {
+        let mut app = app::<Broken>(supervision(1, WorkerGiveUp::ExitApp));
+        statuses_until(&mut app, |status| {
+            matches!(status, WorkerStatus::Dead { .. })
+        });
+        assert_eq!(app.should_exit(), Some(AppExit::error()));
+    }
+}
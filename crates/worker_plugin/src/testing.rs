@@ -0,0 +1,190 @@
+use crate::Bridge;
+use crate::WorkerMessage;
+use crate::WorkerMetrics;
+use crate::WorkerPlugin;
+use crate::WorkerStateTrait;
+use crate::WorkerStats;
+use crate::WorkerStatus;
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A headless app around one [`WorkerPlugin`], for testing plugins built on it.
+///
+/// Every update collects the gamebound messages that arrived, so none are missed between waits.
+pub struct WorkerTestApp<T, G, S>
+where
+    T: WorkerMessage + GetTypeRegistration,
+    G: WorkerMessage + GetTypeRegistration,
+    S: WorkerStateTrait,
+{
+    pub app: App,
+    /// How long the `wait_*` methods keep updating before giving up.
+    pub timeout: Duration,
+    name: String,
+    cursor: EventCursor<G>,
+    received: Vec<G>,
+    _types: std::marker::PhantomData<fn() -> (T, S)>,
+}
+
+impl<T, G, S> WorkerTestApp<T, G, S>
+where
+    T: WorkerMessage + GetTypeRegistration,
+    G: WorkerMessage + GetTypeRegistration,
+    S: WorkerStateTrait,
+{
+    /// Adds `plugin` to a [`MinimalPlugins`] app and runs the first update, which starts the worker thread.
+    pub fn new(plugin: WorkerPlugin<T, G, S>) -> Self {
+        let name = plugin.config.name.clone();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(plugin);
+        let mut test_app = Self {
+            app,
+            timeout: Duration::from_secs(5),
+            name,
+            cursor: EventCursor::default(),
+            received: Vec::new(),
+            _types: std::marker::PhantomData,
+        };
+        test_app.update();
+        test_app
+    }
+
+    pub fn send(&mut self, msg: T) {
+        self.app.world_mut().send_event(msg);
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+        let events = self.app.world().resource::<Events<G>>();
+        self.received.extend(self.cursor.read(events).cloned());
+    }
+
+    /// Every gamebound message so far, oldest first.
+    pub fn received(&self) -> &[G] {
+        &self.received
+    }
+
+    /// Takes the gamebound messages so far, so later assertions only see newer ones.
+    pub fn take_received(&mut self) -> Vec<G> {
+        std::mem::take(&mut self.received)
+    }
+
+    /// Updates until a gamebound message matches, taking it and everything before it.
+    ///
+    /// `None` once [`Self::timeout`] passes.
+    pub fn try_wait_for(&mut self, matches: impl Fn(&G) -> bool) -> Option<G> {
+        let started = Instant::now();
+        loop {
+            if let Some(index) = self.received.iter().position(&matches) {
+                return self.received.drain(..=index).last();
+            }
+            if started.elapsed() > self.timeout {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            self.update();
+        }
+    }
+
+    /// Like [`Self::try_wait_for`] but panics on timeout, listing what did arrive.
+    pub fn wait_for(&mut self, matches: impl Fn(&G) -> bool) -> G {
+        match self.try_wait_for(matches) {
+            Some(msg) => msg,
+            None => panic!(
+                "[{}] No matching gamebound message within {:?}, got {:?}",
+                self.name, self.timeout, self.received
+            ),
+        }
+    }
+
+    /// Updates until the worker's metrics satisfy `done`, panicking on timeout.
+    pub fn wait_for_stats(&mut self, done: impl Fn(&WorkerStats) -> bool) -> WorkerStats {
+        let started = Instant::now();
+        loop {
+            let stats = self.stats();
+            if done(&stats) {
+                return stats;
+            }
+            if started.elapsed() > self.timeout {
+                panic!(
+                    "[{}] Metrics never got there within {:?}, ended at {:?}",
+                    self.name, self.timeout, stats
+                );
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            self.update();
+        }
+    }
+
+    /// Updates until the worker's status satisfies `done`, panicking on timeout.
+    pub fn wait_for_status(&mut self, done: impl Fn(&WorkerStatus) -> bool) -> WorkerStatus {
+        let started = Instant::now();
+        loop {
+            let status = self.status();
+            if done(&status) {
+                return status;
+            }
+            if started.elapsed() > self.timeout {
+                panic!(
+                    "[{}] Status never got there within {:?}, ended at {:?}",
+                    self.name, self.timeout, status
+                );
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            self.update();
+        }
+    }
+
+    pub fn stats(&self) -> WorkerStats {
+        self.app
+            .world()
+            .resource::<WorkerMetrics>()
+            .workers
+            .get(&self.name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> WorkerStatus {
+        let entity = self.app.world().resource::<Bridge<T, G>>().entity;
+        self.app
+            .world()
+            .get::<WorkerStatus>(entity)
+            .cloned()
+            .unwrap_or(WorkerStatus::Starting)
+    }
+
+    pub fn assert_no_drops(&self) {
+        let stats = self.stats();
+        assert_eq!(
+            (
+                stats.threadbound_backpressure.dropped(),
+                stats.dropped_disconnected,
+                stats.gamebound_backpressure.dropped(),
+            ),
+            (0, 0, 0),
+            "[{}] Dropped messages: {:?}",
+            self.name,
+            stats
+        );
+    }
+
+    pub fn assert_no_errors(&self) {
+        let stats = self.stats();
+        assert_eq!(
+            stats.handler_errors, 0,
+            "[{}] Handler failed: {:?}",
+            self.name, stats
+        );
+    }
+
+    /// Exits the app, which shuts the worker thread down.
+    pub fn exit(&mut self) {
+        self.app.world_mut().send_event(AppExit::Success);
+        self.update();
+    }
+}
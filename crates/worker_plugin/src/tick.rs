@@ -0,0 +1,261 @@
+use crate::WorkerMessage;
+use bevy::prelude::*;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use crossbeam_channel::select;
+use std::hash::BuildHasher;
+use std::hash::RandomState;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How often a worker ticks, kept on the worker thread so it does not depend on how often the app updates.
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct TickSchedule {
+    pub interval: Duration,
+    /// Up to this much is added to every interval, so workers that start together do not stay in step.
+    pub jitter: Duration,
+    pub paused: bool,
+}
+impl TickSchedule {
+    pub fn every(interval: Duration) -> Self {
+        Self {
+            interval,
+            jitter: Duration::ZERO,
+            paused: false,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Starts out paused, until a [`TickCommand::Resume`].
+    pub fn paused(mut self) -> Self {
+        self.paused = true;
+        self
+    }
+
+    fn next_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        // A fresh RandomState is randomly seeded, which is plenty for spreading ticks out
+        let random = RandomState::new().hash_one(Instant::now());
+        let jitter_ns = random % (self.jitter.as_nanos().min(u64::MAX as u128) as u64 + 1);
+        self.interval + Duration::from_nanos(jitter_ns)
+    }
+
+    fn apply(&mut self, command: &TickCommand) {
+        match command {
+            TickCommand::Pause => self.paused = true,
+            TickCommand::Resume => self.paused = false,
+            TickCommand::SetInterval(interval) => self.interval = *interval,
+            TickCommand::SetJitter(jitter) => self.jitter = *jitter,
+        }
+    }
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum TickCommand {
+    Pause,
+    Resume,
+    /// Takes effect from now, the pending tick is rescheduled.
+    SetInterval(Duration),
+    SetJitter(Duration),
+}
+
+/// Changes the [`TickSchedule`] of the ticking worker whose threadbound messages are `T`.
+#[derive(Event, Debug, Clone)]
+pub struct WorkerTickControl<T: WorkerMessage> {
+    pub command: TickCommand,
+    _worker: PhantomData<fn() -> T>,
+}
+impl<T: WorkerMessage> WorkerTickControl<T> {
+    pub fn new(command: TickCommand) -> Self {
+        Self {
+            command,
+            _worker: PhantomData,
+        }
+    }
+
+    pub fn pause() -> Self {
+        Self::new(TickCommand::Pause)
+    }
+
+    pub fn resume() -> Self {
+        Self::new(TickCommand::Resume)
+    }
+
+    pub fn set_interval(interval: Duration) -> Self {
+        Self::new(TickCommand::SetInterval(interval))
+    }
+}
+
+/// Carries [`WorkerTickControl`] events to the worker thread.
+#[derive(Resource)]
+pub struct TickControlSender<T> {
+    sender: Sender<TickCommand>,
+    _worker: PhantomData<fn() -> T>,
+}
+impl<T> TickControlSender<T> {
+    pub fn new(sender: Sender<TickCommand>) -> Self {
+        Self {
+            sender,
+            _worker: PhantomData,
+        }
+    }
+}
+
+pub fn forward_tick_control<T: WorkerMessage>(
+    mut events: EventReader<WorkerTickControl<T>>,
+    control: Res<TickControlSender<T>>,
+) {
+    for event in events.read() {
+        debug!(
+            "Tick control for {}: {:?}",
+            std::any::type_name::<T>(),
+            event.command
+        );
+        if control.sender.send(event.command.clone()).is_err() {
+            warn!(
+                "The {} worker is gone, dropping tick control {:?}",
+                std::any::type_name::<T>(),
+                event.command
+            );
+        }
+    }
+}
+
+struct TickState {
+    schedule: TickSchedule,
+    next_tick: Instant,
+}
+
+/// A receiver that returns `tick(state)` whenever the schedule is due, and messages as they arrive in between.
+pub fn ticking_receiver<T, S>(
+    schedule: TickSchedule,
+    control_rx: Receiver<TickCommand>,
+    tick: impl Fn(&mut S) -> T + Send + Sync + 'static,
+) -> impl Fn(&Receiver<T>, &mut S) -> Result<T> + Send + Sync + 'static
+where
+    T: Send + 'static,
+{
+    let ticks = Mutex::new(TickState {
+        next_tick: Instant::now() + schedule.next_delay(),
+        schedule,
+    });
+    move |thread_rx, state| {
+        let mut ticks = ticks.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let command = if ticks.schedule.paused {
+                select! {
+                    recv(thread_rx) -> msg => return msg.map_err(BevyError::from),
+                    recv(control_rx) -> command => command,
+                }
+            } else {
+                let due = ticks.next_tick.saturating_duration_since(Instant::now());
+                select! {
+                    recv(thread_rx) -> msg => return msg.map_err(BevyError::from),
+                    recv(control_rx) -> command => command,
+                    default(due) => {
+                        ticks.next_tick = Instant::now() + ticks.schedule.next_delay();
+                        return Ok(tick(state));
+                    }
+                }
+            };
+            let Ok(command) = command else {
+                // The app is gone too, the threadbound channel will say so next
+                return thread_rx.recv().map_err(BevyError::from);
+            };
+            ticks.schedule.apply(&command);
+            if matches!(command, TickCommand::SetInterval(_) | TickCommand::Resume) {
+                ticks.next_tick = Instant::now() + ticks.schedule.next_delay();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TickSchedule;
+    use crate::WorkerPlugin;
+    use crate::WorkerTickControl;
+    use bevy::prelude::*;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    enum Poll {
+        Tick,
+    }
+
+    #[derive(Debug, Clone, Event, Reflect)]
+    struct Polled;
+
+    fn app(schedule: TickSchedule) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(
+            WorkerPlugin::<Poll, Polled, ()>::builder("Poller")
+                .on_message(|_, reply_tx, _| {
+                    reply_tx.send(Polled)?;
+                    Ok(())
+                })
+                .tick_every(schedule, |_| Poll::Tick)
+                .build(),
+        );
+        app.update();
+        app
+    }
+
+    /// Counts replies over `period` without the app updating in between, like a throttled background window.
+    fn replies_over(app: &mut App, period: Duration) -> usize {
+        app.update();
+        app.world_mut().resource_mut::<Events<Polled>>().clear();
+        std::thread::sleep(period);
+        app.update();
+        app.world()
+            .resource::<Events<Polled>>()
+            .iter_current_update_events()
+            .count()
+    }
+
+    #[test]
+    fn ticks_keep_coming_while_the_app_sleeps() {
+        let mut app = app(TickSchedule::every(Duration::from_millis(10)));
+        let replies = replies_over(&mut app, Duration::from_millis(200));
+        assert!((5..=25).contains(&replies), "{replies} ticks");
+    }
+
+    #[test]
+    fn paused_workers_stop_ticking_until_resumed() {
+        let mut app = app(TickSchedule::every(Duration::from_millis(10)).paused());
+        assert_eq!(replies_over(&mut app, Duration::from_millis(100)), 0);
+
+        app.world_mut()
+            .send_event(WorkerTickControl::<Poll>::resume());
+        app.world_mut()
+            .send_event(WorkerTickControl::<Poll>::set_interval(
+                Duration::from_millis(5),
+            ));
+        assert!(replies_over(&mut app, Duration::from_millis(100)) >= 5);
+
+        app.world_mut()
+            .send_event(WorkerTickControl::<Poll>::pause());
+        app.update();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(replies_over(&mut app, Duration::from_millis(100)), 0);
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let schedule =
+            TickSchedule::every(Duration::from_millis(100)).with_jitter(Duration::from_millis(20));
+        for _ in 0..100 {
+            let delay = schedule.next_delay();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(120));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use ymb_worker_plugin::Sender;
+use ymb_worker_plugin::WorkerPlugin;
+use ymb_worker_plugin::WorkerStatus;
+use ymb_worker_plugin::WorkerTestApp;
+
+#[derive(Debug, Clone, Event, Reflect)]
+enum Sum {
+    Add(i64),
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Event, Reflect)]
+struct Total(i64);
+
+fn handle(msg: &Sum, reply_tx: &Sender<Total>, total: &mut i64) -> Result<()> {
+    match msg {
+        Sum::Add(n) => {
+            *total += n;
+            reply_tx.send(Total(*total))?;
+            Ok(())
+        }
+        Sum::Fail => Err("asked to fail".into()),
+    }
+}
+
+fn summer() -> WorkerTestApp<Sum, Total, i64> {
+    WorkerTestApp::new(
+        WorkerPlugin::<Sum, Total, i64>::builder("Summer")
+            .on_message(handle)
+            .build(),
+    )
+}
+
+#[test]
+fn replies_arrive_in_order() {
+    let mut app = summer();
+    app.wait_for_status(|status| *status == WorkerStatus::Running);
+    for n in 1..=3 {
+        app.send(Sum::Add(n));
+    }
+    assert_eq!(app.wait_for(|total| *total == Total(6)), Total(6));
+    app.assert_no_drops();
+    app.assert_no_errors();
+    app.exit();
+}
+
+#[test]
+fn handler_errors_are_counted() {
+    let mut app = summer();
+    app.send(Sum::Fail);
+    app.send(Sum::Add(1));
+    app.wait_for(|total| *total == Total(1));
+    let stats = app.wait_for_stats(|stats| stats.handler_latency.count == 2);
+    assert_eq!(stats.handler_errors, 1);
+    assert_eq!(stats.messages_out, 1);
+}
+
+#[test]
+fn waiting_gives_up_after_the_timeout() {
+    let mut app = summer();
+    app.timeout = Duration::from_millis(50);
+    app.send(Sum::Add(1));
+    assert_eq!(app.try_wait_for(|total| *total == Total(100)), None);
+    assert_eq!(app.received(), &[Total(1)]);
+}
+
+#[test]
+fn drops_show_up_when_the_worker_is_gone() {
+    let mut app = summer();
+    app.exit();
+    app.send(Sum::Add(1));
+    let stats = app.wait_for_stats(|stats| stats.dropped_disconnected > 0);
+    assert_eq!(stats.messages_in, 0);
+}
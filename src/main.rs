@@ -18,6 +18,26 @@ fn main() -> WindyResult<()> {
     eprintln!("Ahoy from fn main!");
     let args = Args::try_parse();
 
+    // Remote control only talks to the instance that is already running, so it skips relaunching and logging
+    if let Ok(Args {
+        command: Some(Command::Ctl(ctl)),
+        ..
+    }) = &args
+    {
+        ymb_ctl::run(ctl.clone())?;
+        return Ok(());
+    }
+
+    // Only one tray per user, a second launch shows the first one instead
+    if let Ok(Args {
+        command: None | Some(Command::Tray),
+        ..
+    }) = &args
+        && ymb_ctl::forward_launch()?
+    {
+        return Ok(());
+    }
+
     let is_tray_mode = args.as_ref().map_or(false, |a| a.tray_mode_active);
     eprintln!("Is tray mode active: {}", is_tray_mode);
 
@@ -101,6 +121,7 @@ fn main() -> WindyResult<()> {
             info!("Starting GUI process...");
             ymb_welcome_gui::run(&args.global)?;
         }
+        Some(Command::Ctl(_)) => unreachable!("ctl returns before relaunching"),
     }
 
     info!("Application finished successfully.");